@@ -0,0 +1,14 @@
+-- Restore the 001 users table layout
+
+DROP INDEX IF EXISTS idx_users_role;
+
+ALTER TABLE users ALTER COLUMN role DROP DEFAULT;
+ALTER TABLE users ALTER COLUMN role TYPE VARCHAR(50) USING role::text;
+ALTER TABLE users ALTER COLUMN role SET DEFAULT 'user';
+DROP TYPE user_role;
+
+ALTER TABLE users DROP CONSTRAINT users_username_key;
+ALTER TABLE users DROP COLUMN phone;
+ALTER TABLE users RENAME COLUMN is_active TO active;
+ALTER TABLE users RENAME COLUMN password TO password_hash;
+ALTER TABLE users RENAME COLUMN username TO name;
@@ -0,0 +1,29 @@
+-- Reconcile the users table with the User model (src/models/user.rs)
+-- 001 created name/password_hash/active and a VARCHAR role; the model reads
+-- username/password/phone/is_active and a user_role enum.
+
+CREATE TYPE user_role AS ENUM ('parent', 'admin', 'child');
+
+ALTER TABLE users RENAME COLUMN name TO username;
+ALTER TABLE users RENAME COLUMN password_hash TO password;
+ALTER TABLE users RENAME COLUMN active TO is_active;
+ALTER TABLE users ADD COLUMN phone VARCHAR(50);
+
+-- Display names were not unique; suffix duplicates before enforcing uniqueness
+UPDATE users u
+SET username = u.username || '-' || LEFT(u.id::text, 8)
+WHERE EXISTS (
+    SELECT 1 FROM users other
+    WHERE other.username = u.username
+      AND (other.created_at, other.id) < (u.created_at, u.id)
+);
+ALTER TABLE users ADD CONSTRAINT users_username_key UNIQUE (username);
+
+-- Anything that isn't a known role becomes a parent account, the model's default
+ALTER TABLE users ALTER COLUMN role DROP DEFAULT;
+ALTER TABLE users
+    ALTER COLUMN role TYPE user_role
+    USING (CASE WHEN role IN ('parent', 'admin', 'child') THEN role ELSE 'parent' END)::user_role;
+ALTER TABLE users ALTER COLUMN role SET DEFAULT 'parent';
+
+CREATE INDEX idx_users_role ON users(role);
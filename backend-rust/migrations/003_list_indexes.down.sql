@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_classics_author;
+DROP INDEX IF EXISTS idx_classics_dynasty;
+DROP INDEX IF EXISTS idx_classics_created_at_id;
@@ -0,0 +1,7 @@
+-- Indexes for paginated lists (src/utils/pagination.rs)
+-- Chapters and sentences page by their UNIQUE (parent_id, number) keys; the
+-- classics list pages by (created_at, id), which had no index.
+
+CREATE INDEX idx_classics_created_at_id ON classics(created_at, id);
+CREATE INDEX idx_classics_dynasty ON classics(dynasty);
+CREATE INDEX idx_classics_author ON classics(author);
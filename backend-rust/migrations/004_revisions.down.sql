@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS revisions;
+DROP FUNCTION IF EXISTS reject_revision_changes();
+DROP TYPE IF EXISTS revision_action;
+DROP TYPE IF EXISTS revision_entity;
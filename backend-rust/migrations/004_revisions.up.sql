@@ -0,0 +1,34 @@
+-- Append-only history of content edits (src/models/revision.rs)
+-- Each row holds an entity's state before and after one create, update,
+-- delete or restore. There are no foreign keys: history outlives the rows it describes
+-- and the accounts that made the edits.
+
+CREATE TYPE revision_entity AS ENUM ('classic', 'chapter', 'sentence');
+CREATE TYPE revision_action AS ENUM ('create', 'update', 'delete', 'restore');
+
+CREATE TABLE revisions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    entity_type revision_entity NOT NULL,
+    entity_id UUID NOT NULL,
+    -- 1, 2, 3… per entity
+    version INTEGER NOT NULL,
+    action revision_action NOT NULL,
+    before JSONB,
+    after JSONB,
+    editor_id UUID,
+    comment TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (entity_type, entity_id, version)
+);
+
+CREATE INDEX idx_revisions_editor_id ON revisions(editor_id);
+
+CREATE FUNCTION reject_revision_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'revisions are append-only';
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER revisions_append_only BEFORE UPDATE OR DELETE ON revisions
+    FOR EACH ROW EXECUTE FUNCTION reject_revision_changes();
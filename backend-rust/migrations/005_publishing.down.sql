@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS idx_classics_status;
+ALTER TABLE classics
+    DROP CONSTRAINT IF EXISTS classics_publish_window,
+    DROP COLUMN IF EXISTS unpublish_at,
+    DROP COLUMN IF EXISTS publish_at,
+    DROP COLUMN IF EXISTS status;
+DROP TYPE IF EXISTS publication_status;
@@ -0,0 +1,16 @@
+-- Draft → in review → published workflow for classics (src/models/publishing.rs)
+-- A classic is public while published and inside its optional publish window,
+-- so scheduled publishing needs no background job. Classics that already
+-- exist stay public; new ones start as drafts.
+
+CREATE TYPE publication_status AS ENUM ('draft', 'in_review', 'published');
+
+ALTER TABLE classics
+    ADD COLUMN status publication_status NOT NULL DEFAULT 'published',
+    ADD COLUMN publish_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN unpublish_at TIMESTAMP WITH TIME ZONE,
+    ADD CONSTRAINT classics_publish_window CHECK (unpublish_at > publish_at);
+
+ALTER TABLE classics ALTER COLUMN status SET DEFAULT 'draft';
+
+CREATE INDEX idx_classics_status ON classics(status);
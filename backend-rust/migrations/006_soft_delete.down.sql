@@ -0,0 +1,28 @@
+-- Rows still in the trash would come back as live content, so they go for good.
+DELETE FROM classics WHERE deleted_at IS NOT NULL;
+DELETE FROM chapters WHERE deleted_at IS NOT NULL;
+DELETE FROM sentences WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE practice_sessions
+    DROP CONSTRAINT practice_sessions_chapter_id_fkey,
+    ADD CONSTRAINT practice_sessions_chapter_id_fkey FOREIGN KEY (chapter_id) REFERENCES chapters(id);
+ALTER TABLE practice_sessions
+    DROP CONSTRAINT practice_sessions_classic_id_fkey,
+    ADD CONSTRAINT practice_sessions_classic_id_fkey FOREIGN KEY (classic_id) REFERENCES classics(id);
+
+DROP TRIGGER IF EXISTS sentences_live_chapter ON sentences;
+DROP TRIGGER IF EXISTS chapters_live_classic ON chapters;
+DROP FUNCTION IF EXISTS reject_deleted_parent();
+
+DROP INDEX IF EXISTS idx_sentences_deleted_at;
+DROP INDEX IF EXISTS idx_chapters_deleted_at;
+DROP INDEX IF EXISTS idx_classics_deleted_at;
+
+DROP INDEX IF EXISTS sentences_chapter_id_number_key;
+ALTER TABLE sentences ADD CONSTRAINT sentences_chapter_id_number_key UNIQUE (chapter_id, number);
+DROP INDEX IF EXISTS chapters_classic_id_number_key;
+ALTER TABLE chapters ADD CONSTRAINT chapters_classic_id_number_key UNIQUE (classic_id, number);
+
+ALTER TABLE sentences DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE chapters DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE classics DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,61 @@
+-- Soft delete and trash for content (src/models/trash.rs)
+-- Deleting a classic, chapter or sentence stamps deleted_at instead of removing
+-- the row, so learning progress that points at it survives until an admin
+-- purges it. A delete stamps the live children with the same time, which is
+-- how a restore tells them from children deleted on their own earlier.
+
+ALTER TABLE classics ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE chapters ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE sentences ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+-- Numbers are unique among live rows only, so a deleted chapter does not hold
+-- on to its number. The upserts in the models name the same predicate to keep
+-- these as their ON CONFLICT arbiters.
+ALTER TABLE chapters DROP CONSTRAINT chapters_classic_id_number_key;
+CREATE UNIQUE INDEX chapters_classic_id_number_key ON chapters(classic_id, number) WHERE deleted_at IS NULL;
+ALTER TABLE sentences DROP CONSTRAINT sentences_chapter_id_number_key;
+CREATE UNIQUE INDEX sentences_chapter_id_number_key ON sentences(chapter_id, number) WHERE deleted_at IS NULL;
+
+CREATE INDEX idx_classics_deleted_at ON classics(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_chapters_deleted_at ON chapters(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_sentences_deleted_at ON sentences(deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- Purging removes rows for good. Practice sessions are the learner's history,
+-- so they lose the reference rather than block the purge.
+ALTER TABLE practice_sessions
+    DROP CONSTRAINT practice_sessions_classic_id_fkey,
+    ADD CONSTRAINT practice_sessions_classic_id_fkey
+        FOREIGN KEY (classic_id) REFERENCES classics(id) ON DELETE SET NULL;
+ALTER TABLE practice_sessions
+    DROP CONSTRAINT practice_sessions_chapter_id_fkey,
+    ADD CONSTRAINT practice_sessions_chapter_id_fkey
+        FOREIGN KEY (chapter_id) REFERENCES chapters(id) ON DELETE SET NULL;
+
+-- A deleted parent counts as gone: live rows cannot be added under it.
+CREATE FUNCTION reject_deleted_parent()
+RETURNS TRIGGER AS $$
+DECLARE
+    parent_deleted BOOLEAN;
+BEGIN
+    IF NEW.deleted_at IS NOT NULL THEN
+        RETURN NEW;
+    END IF;
+    -- Separate statements: each only names the column its table has
+    IF TG_TABLE_NAME = 'chapters' THEN
+        SELECT deleted_at IS NOT NULL INTO parent_deleted FROM classics WHERE id = NEW.classic_id;
+    ELSE
+        SELECT deleted_at IS NOT NULL INTO parent_deleted FROM chapters WHERE id = NEW.chapter_id;
+    END IF;
+    IF parent_deleted THEN
+        RAISE foreign_key_violation USING MESSAGE = format(
+            'insert or update on table "%s" violates foreign key constraint "%s_%s_fkey"',
+            TG_TABLE_NAME, TG_TABLE_NAME, TG_ARGV[0]);
+    END IF;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER chapters_live_classic BEFORE INSERT OR UPDATE ON chapters
+    FOR EACH ROW EXECUTE FUNCTION reject_deleted_parent('classic_id');
+CREATE TRIGGER sentences_live_chapter BEFORE INSERT OR UPDATE ON sentences
+    FOR EACH ROW EXECUTE FUNCTION reject_deleted_parent('chapter_id');
@@ -0,0 +1,306 @@
+//! Operational commands for 小小读书郎, sharing configuration and models with the server.
+//!
+//! Run `admin --help` for the command list. Configuration is loaded exactly as the
+//! server loads it (`APP_ENV`, `config/{env}.toml`, environment variables).
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
+use dotenv::dotenv;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::Mutex;
+use tracing_subscriber::EnvFilter;
+
+use xiaoxiao_dushulang_backend::{
+    config::Config,
+    database::Database,
+    models::{CharacterData, Classic, ClassicFilter, CreateUserRequest, User, UserRole},
+    schema,
+    services::{cache, import_export},
+    utils::pagination::PageRequest,
+};
+
+/// Content tables touched by `index rebuild`.
+const CONTENT_TABLES: &[&str] = &["classics", "chapters", "sentences", "characters"];
+
+#[derive(Parser)]
+#[command(name = "admin", version, about = "小小读书郎后端管理工具")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Apply, revert or list database migrations
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// Compare the live schema with what the models expect
+    #[command(subcommand)]
+    Schema(SchemaCommand),
+    /// Manage user accounts
+    #[command(subcommand)]
+    User(UserCommand),
+    /// Import content from a JSON file (upserts in a single transaction)
+    #[command(subcommand)]
+    Import(ContentKind),
+    /// Export content as JSON
+    #[command(subcommand)]
+    Export(ExportCommand),
+    /// Rebuild the Redis cache
+    #[command(subcommand)]
+    Cache(RebuildCommand),
+    /// Rebuild content table indexes and refresh planner statistics
+    #[command(subcommand)]
+    Index(RebuildCommand),
+    /// Inspect the effective configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// Apply all pending migrations
+    Run,
+    /// Revert the most recently applied migrations
+    Revert {
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+    /// List migrations and whether each is applied
+    Status,
+}
+
+#[derive(Subcommand)]
+enum SchemaCommand {
+    Check,
+}
+
+#[derive(Subcommand)]
+enum UserCommand {
+    /// Create an administrator account
+    CreateAdmin {
+        #[arg(long)]
+        username: String,
+        #[arg(long)]
+        email: String,
+        #[command(flatten)]
+        password: PasswordArg,
+    },
+    /// Set a new password for an existing account
+    ResetPassword {
+        #[arg(long)]
+        email: String,
+        #[command(flatten)]
+        password: PasswordArg,
+    },
+}
+
+#[derive(Args)]
+struct PasswordArg {
+    /// Read from ADMIN_PASSWORD or, when neither is given, the first line of stdin
+    #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum ContentKind {
+    /// Classics with their chapters and sentences
+    Classics { file: PathBuf },
+    /// Character dictionary entries
+    Characters { file: PathBuf },
+}
+
+#[derive(Subcommand)]
+enum ExportCommand {
+    Classics {
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    Characters {
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RebuildCommand {
+    Rebuild,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the merged configuration with secrets redacted
+    Print,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    let cli = Cli::parse();
+    let config = Config::load()?;
+
+    // Logs go to stderr so exports can be piped from stdout
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_new(&config.telemetry.log_level).unwrap_or_else(|_| EnvFilter::new("info")))
+        .with_writer(io::stderr)
+        .init();
+
+    if let Command::Config(ConfigCommand::Print) = cli.command {
+        println!("{}", serde_json::to_string_pretty(&config.redacted())?);
+        return Ok(());
+    }
+
+    let db = Database::connect(&config.database).await?;
+    let result = run(cli.command, &config, &db).await;
+    db.close().await;
+    result
+}
+
+async fn run(command: Command, config: &Config, db: &Database) -> Result<()> {
+    match command {
+        Command::Migrate(MigrateCommand::Run) => db.migrate().await?,
+        Command::Migrate(MigrateCommand::Revert { steps }) => {
+            let reverted = db.revert(steps).await?;
+            if reverted.is_empty() {
+                eprintln!("没有已应用的迁移");
+            }
+            for version in reverted {
+                eprintln!("已回滚迁移 {}", version);
+            }
+        }
+        Command::Migrate(MigrateCommand::Status) => {
+            for migration in db.migration_status().await? {
+                println!(
+                    "{:>4}  {:<8}  {}{}",
+                    migration.version,
+                    if migration.applied { "applied" } else { "pending" },
+                    migration.description,
+                    if migration.reversible { "" } else { " (irreversible)" },
+                );
+            }
+        }
+        Command::Schema(SchemaCommand::Check) => {
+            schema::check(db.writer()).await?;
+            eprintln!("数据库结构检查通过");
+        }
+        Command::User(UserCommand::CreateAdmin { username, email, password }) => {
+            let password = password.resolve()?;
+            let user = User::create(
+                db.writer(),
+                CreateUserRequest {
+                    username,
+                    email,
+                    password,
+                    phone: None,
+                    role: Some(UserRole::Admin),
+                },
+            )
+            .await?;
+            eprintln!("已创建管理员 {} ({})", user.username, user.id);
+        }
+        Command::User(UserCommand::ResetPassword { email, password }) => {
+            let user = User::find_by_email(db.writer(), &email)
+                .await?
+                .with_context(|| format!("用户不存在: {}", email))?;
+            let password = password.resolve()?;
+            User::set_password(db.writer(), user.id, &password).await?;
+            eprintln!("已重置 {} 的密码", user.email);
+        }
+        Command::Import(ContentKind::Classics { file }) => {
+            let classics: Vec<import_export::ClassicExport> = read_json(&file)?;
+            let summary = import_export::import_classics(db.writer(), &classics).await?;
+            eprintln!(
+                "已导入 {} 部经典、{} 个章节、{} 个句子",
+                summary.classics, summary.chapters, summary.sentences
+            );
+            // Best effort: a stale list expires on its own if Redis is unreachable
+            match connect_redis(config).await {
+                Ok(redis) => cache::invalidate_prefix(&redis, cache::CLASSICS_LIST_PREFIX).await,
+                Err(e) => eprintln!("未能清除经典列表缓存: {}", e),
+            }
+        }
+        Command::Import(ContentKind::Characters { file }) => {
+            let characters: Vec<CharacterData> = read_json(&file)?;
+            let summary = import_export::import_characters(db.writer(), &characters).await?;
+            eprintln!("已导入 {} 个汉字", summary.characters);
+        }
+        Command::Export(ExportCommand::Classics { output }) => {
+            write_json(output, &import_export::export_classics(db.reader()).await?)?;
+        }
+        Command::Export(ExportCommand::Characters { output }) => {
+            write_json(output, &import_export::export_characters(db.reader()).await?)?;
+        }
+        Command::Cache(RebuildCommand::Rebuild) => {
+            let redis = connect_redis(config).await?;
+            cache::invalidate_prefix(&redis, cache::CLASSICS_LIST_PREFIX).await;
+            // Warm the first public page in the default order, the one most clients
+            // request; like the list handler, it must not outlive the next scheduled change
+            let filter = ClassicFilter { published_only: true, ..ClassicFilter::default() };
+            let page = PageRequest::default();
+            let classics = Classic::list(db.writer(), &filter, &page).await?;
+            let ttl = cache::ttl_until(Classic::next_scheduled_change(db.writer()).await?, Utc::now());
+            cache::set_json(&redis, &cache::classics_list_key(&filter, &page), &classics, ttl).await;
+            eprintln!("缓存已重建: {} 部经典", classics.items.len());
+        }
+        Command::Index(RebuildCommand::Rebuild) => {
+            for table in CONTENT_TABLES {
+                sqlx::query(&format!("REINDEX TABLE {}", table)).execute(db.writer()).await?;
+                sqlx::query(&format!("ANALYZE {}", table)).execute(db.writer()).await?;
+                eprintln!("已重建索引: {}", table);
+            }
+        }
+        Command::Config(ConfigCommand::Print) => unreachable!("handled before connecting"),
+    }
+
+    Ok(())
+}
+
+impl PasswordArg {
+    fn resolve(self) -> Result<String> {
+        let password = match self.password {
+            Some(password) => password,
+            None => {
+                eprint!("新密码: ");
+                io::stderr().flush()?;
+                let mut line = String::new();
+                io::stdin().lock().read_line(&mut line)?;
+                line.trim_end_matches(['\r', '\n']).to_string()
+            }
+        };
+
+        if password.len() < 8 {
+            bail!("密码至少需要 8 个字符");
+        }
+        Ok(password)
+    }
+}
+
+async fn connect_redis(config: &Config) -> Result<Arc<Mutex<redis::aio::Connection>>> {
+    let client = redis::Client::open(config.redis.url.clone())?;
+    let conn = client.get_async_connection().await?;
+    Ok(Arc::new(Mutex::new(conn)))
+}
+
+fn read_json<T: DeserializeOwned>(path: &PathBuf) -> Result<T> {
+    let file = File::open(path).with_context(|| format!("无法打开 {}", path.display()))?;
+    serde_json::from_reader(BufReader::new(file)).with_context(|| format!("无法解析 {}", path.display()))
+}
+
+fn write_json<T: Serialize>(output: Option<PathBuf>, value: &T) -> Result<()> {
+    let writer: Box<dyn Write> = match &output {
+        Some(path) => Box::new(File::create(path).with_context(|| format!("无法写入 {}", path.display()))?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+    serde_json::to_writer_pretty(&mut writer, value)?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
@@ -1,42 +1,215 @@
-use sqlx::{PgPool, Row};
-use tracing::{info, error};
+use std::str::FromStr;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+use std::time::Duration;
+
+use sqlx::{PgPool, migrate::{Migrate, Migrator}, postgres::{PgConnectOptions, PgPoolOptions}};
+use tracing::{info, warn, error};
 use anyhow::Result;
 
+use crate::config::DatabaseConfig;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// A migration known to the binary and whether it has been applied.
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+    pub reversible: bool,
+}
+
+/// A read replica and the result of its most recent health check.
+#[derive(Debug)]
+struct Replica {
+    pool: PgPool,
+    healthy: AtomicBool,
+}
+
 #[derive(Debug, Clone)]
 pub struct Database {
     pool: PgPool,
+    replicas: Arc<Vec<Replica>>,
+    next_replica: Arc<AtomicUsize>,
 }
 
 impl Database {
-    pub async fn connect(database_url: &str) -> Result<Self> {
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self> {
         info!("正在连接 PostgreSQL 数据库...");
-        
-        let pool = PgPool::connect(database_url).await?;
-        
+
+        let pool = connect_pool(&config.url, config).await?;
+
         // Test the connection
-        let row: (i64,) = sqlx::query_as("SELECT 1")
+        let row: (i32,) = sqlx::query_as("SELECT 1")
             .fetch_one(&pool)
             .await?;
-        
+
         info!("数据库连接测试成功，返回值: {}", row.0);
-        
-        Ok(Database { pool })
+        info!(
+            "数据库连接池已创建: 最大连接数 {}, 最小连接数 {}, 语句超时 {}ms",
+            config.max_connections, config.min_connections, config.statement_timeout_ms
+        );
+
+        // An unreachable replica must not stop the server; it starts out unhealthy
+        // and the health check picks it up once it responds.
+        let mut replicas = Vec::with_capacity(config.replica_urls.len());
+        for (index, url) in config.replica_urls.iter().enumerate() {
+            let options = PgConnectOptions::from_str(url)?;
+            let replica_pool = pool_options(config).connect_lazy_with(apply_statement_timeout(options, config));
+            let healthy = ping(&replica_pool).await;
+            if healthy {
+                info!("只读副本 #{} 连接成功", index);
+            } else {
+                warn!("只读副本 #{} 暂不可用，读请求将回退到主库", index);
+            }
+            replicas.push(Replica {
+                pool: replica_pool,
+                healthy: AtomicBool::new(healthy),
+            });
+        }
+
+        Ok(Database {
+            pool,
+            replicas: Arc::new(replicas),
+            next_replica: Arc::new(AtomicUsize::new(0)),
+        })
     }
 
     pub async fn migrate(&self) -> Result<()> {
         info!("开始运行数据库迁移...");
-        
+
         // Run migrations
-        sqlx::migrate!("./migrations").run(&self.pool).await?;
-        
+        MIGRATOR.run(&self.pool).await?;
+
         info!("数据库迁移完成");
         Ok(())
     }
 
-    pub fn pool(&self) -> &PgPool {
+    /// Every migration in `migrations/`, oldest first, with its applied state.
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        let applied = self.applied_migrations().await?;
+
+        Ok(MIGRATOR
+            .iter()
+            .filter(|migration| migration.migration_type.is_up_migration())
+            .map(|migration| MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                applied: applied.contains(&migration.version),
+                reversible: is_reversible(migration.version),
+            })
+            .collect())
+    }
+
+    /// Revert the most recent `steps` applied migrations and return their versions.
+    /// Fails without touching the database if any of them has no down script.
+    pub async fn revert(&self, steps: usize) -> Result<Vec<i64>> {
+        let mut applied = self.applied_migrations().await?;
+        applied.sort_unstable_by(|a, b| b.cmp(a));
+
+        let reverted: Vec<i64> = applied.iter().take(steps).copied().collect();
+        if let Some(version) = reverted.iter().find(|version| !is_reversible(**version)) {
+            anyhow::bail!("迁移 {} 没有 down 脚本，无法回滚", version);
+        }
+
+        let target = applied.get(steps).copied().unwrap_or(0);
+        info!("回滚数据库迁移到版本 {}...", target);
+        MIGRATOR.undo(&self.pool, target).await?;
+
+        Ok(reverted)
+    }
+
+    async fn applied_migrations(&self) -> Result<Vec<i64>> {
+        let mut conn = self.pool.acquire().await?;
+        conn.ensure_migrations_table().await?;
+        let applied = conn.list_applied_migrations().await?;
+        Ok(applied.into_iter().map(|migration| migration.version).collect())
+    }
+
+    /// The primary pool, for writes and reads that must see them.
+    pub fn writer(&self) -> &PgPool {
         &self.pool
     }
 
+    /// A healthy replica chosen round-robin, or the primary when there is none.
+    /// Replicas lag slightly, so anything reading its own writes should use `writer()`.
+    pub fn reader(&self) -> &PgPool {
+        let count = self.replicas.len();
+        if count == 0 {
+            return &self.pool;
+        }
+
+        let start = self.next_replica.fetch_add(1, Ordering::Relaxed);
+        (0..count)
+            .map(|offset| &self.replicas[(start + offset) % count])
+            .find(|replica| replica.healthy.load(Ordering::Relaxed))
+            .map(|replica| &replica.pool)
+            .unwrap_or(&self.pool)
+    }
+
+    /// Every pool with a label for metrics: `primary`, `replica-0`, ...
+    pub fn pools(&self) -> Vec<(String, &PgPool)> {
+        std::iter::once(("primary".to_string(), &self.pool))
+            .chain(
+                self.replicas
+                    .iter()
+                    .enumerate()
+                    .map(|(index, replica)| (format!("replica-{}", index), &replica.pool)),
+            )
+            .collect()
+    }
+
+    /// Ping each replica on an interval, taking failing ones out of rotation
+    /// and putting them back once they recover.
+    pub fn spawn_replica_health_checks(&self, interval: Duration) {
+        if self.replicas.is_empty() {
+            return;
+        }
+
+        let replicas = self.replicas.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                for (index, replica) in replicas.iter().enumerate() {
+                    let healthy = ping(&replica.pool).await;
+                    let was_healthy = replica.healthy.swap(healthy, Ordering::Relaxed);
+                    if was_healthy && !healthy {
+                        warn!("只读副本 #{} 健康检查失败，已切换到主库", index);
+                    } else if !was_healthy && healthy {
+                        info!("只读副本 #{} 已恢复", index);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Close the primary and replica pools, waiting for checked-out connections
+    /// to be returned first.
+    pub async fn close(&self) {
+        self.pool.close().await;
+        for replica in self.replicas.iter() {
+            replica.pool.close().await;
+        }
+        info!("数据库连接池已关闭");
+    }
+
+    /// Like [`close`](Self::close), but gives up after `deadline`. Requests cut
+    /// off by the drain timeout can still hold connections, and `PgPool::close`
+    /// would otherwise wait for them. Returns `false` if the deadline was hit.
+    pub async fn close_within(&self, deadline: Duration) -> bool {
+        match tokio::time::timeout(deadline, self.close()).await {
+            Ok(()) => true,
+            Err(_) => {
+                warn!("等待数据库连接归还超时 ({}s)，强制关闭", deadline.as_secs());
+                false
+            }
+        }
+    }
+
     pub async fn health_check(&self) -> Result<bool> {
         match sqlx::query("SELECT 1").fetch_one(&self.pool).await {
             Ok(_) => Ok(true),
@@ -46,4 +219,48 @@ impl Database {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+fn is_reversible(version: i64) -> bool {
+    MIGRATOR
+        .iter()
+        .any(|migration| migration.version == version && migration.migration_type.is_down_migration())
+}
+
+async fn connect_pool(url: &str, config: &DatabaseConfig) -> Result<PgPool> {
+    let options = PgConnectOptions::from_str(url)?;
+    let pool = pool_options(config)
+        .connect_with(apply_statement_timeout(options, config))
+        .await?;
+    Ok(pool)
+}
+
+fn pool_options(config: &DatabaseConfig) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+        .idle_timeout((config.idle_timeout_secs > 0).then(|| Duration::from_secs(config.idle_timeout_secs)))
+}
+
+fn apply_statement_timeout(options: PgConnectOptions, config: &DatabaseConfig) -> PgConnectOptions {
+    if config.statement_timeout_ms == 0 {
+        return options;
+    }
+    // Applied server-side per connection; Postgres cancels overrunning queries with 57014
+    options.options([("statement_timeout", config.statement_timeout_ms.to_string())])
+}
+
+async fn ping(pool: &PgPool) -> bool {
+    match tokio::time::timeout(Duration::from_secs(2), sqlx::query("SELECT 1").fetch_one(pool)).await {
+        Ok(Ok(_)) => true,
+        Ok(Err(e)) => {
+            tracing::debug!("只读副本健康检查失败: {}", e);
+            false
+        }
+        Err(_) => {
+            tracing::debug!("只读副本健康检查超时");
+            false
+        }
+    }
+}
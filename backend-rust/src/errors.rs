@@ -1,11 +1,17 @@
-use warp::{Rejection, Reply, reply};
-use serde_json::json;
+use warp::{Rejection, Reply, reply, http::StatusCode};
+use serde::Serialize;
 use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::middleware::rate_limit::{self, RateLimitDecision};
 
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("数据库错误: {0}")]
-    Database(#[from] sqlx::Error),
+    Database(sqlx::Error),
+
+    #[error("数据库操作超时")]
+    Timeout,
     
     #[error("Redis错误: {0}")]
     Redis(#[from] redis::RedisError),
@@ -24,6 +30,12 @@ pub enum AppError {
     
     #[error("未授权")]
     Unauthorized,
+
+    #[error("权限不足")]
+    Forbidden,
+
+    #[error("请求过于频繁")]
+    RateLimited(RateLimitDecision),
     
     #[error("内部服务器错误")]
     Internal,
@@ -31,6 +43,62 @@ pub enum AppError {
 
 impl warp::reject::Reject for AppError {}
 
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        if is_timeout(&err) {
+            AppError::Timeout
+        } else {
+            AppError::Database(err)
+        }
+    }
+}
+
+// Models return `anyhow::Result`; recover the underlying sqlx error where there
+// is one, and pass through the `AppError`s models raise themselves
+impl From<anyhow::Error> for AppError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<AppError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        match err.downcast::<sqlx::Error>() {
+            Ok(err) => err.into(),
+            Err(_) => AppError::Internal,
+        }
+    }
+}
+
+/// Body of every error response. Handlers that answer directly send the same
+/// shape as `ApiResponse` without `status_code`; rejections recovered by
+/// `handle_rejection` include it.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub success: bool,
+    pub error: String,
+    #[schema(format = DateTime)]
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+}
+
+/// Waiting longer than `acquire_timeout` for a pooled connection, or Postgres
+/// cancelling a query that exceeded `statement_timeout` (SQLSTATE 57014).
+pub fn is_timeout(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(db_err) => db_err.code().as_deref() == Some("57014"),
+        _ => false,
+    }
+}
+
+/// Status for a failed model call: 503 when the database timed out, 500 otherwise.
+pub fn status_for(err: &anyhow::Error) -> StatusCode {
+    match err.downcast_ref::<sqlx::Error>() {
+        Some(err) if is_timeout(err) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, std::convert::Infallible> {
     let code;
     let message;
@@ -48,10 +116,22 @@ pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, std::convert
                 code = warp::http::StatusCode::UNAUTHORIZED;
                 message = "未授权访问";
             }
+            AppError::Forbidden => {
+                code = warp::http::StatusCode::FORBIDDEN;
+                message = "权限不足";
+            }
+            AppError::RateLimited(_) => {
+                code = warp::http::StatusCode::TOO_MANY_REQUESTS;
+                message = "请求过于频繁，请稍后再试";
+            }
             AppError::Validation(msg) => {
                 code = warp::http::StatusCode::BAD_REQUEST;
                 message = msg;
             }
+            AppError::Timeout => {
+                code = warp::http::StatusCode::SERVICE_UNAVAILABLE;
+                message = "服务繁忙，请稍后重试";
+            }
             AppError::Database(_) | AppError::Redis(_) | AppError::Internal => {
                 code = warp::http::StatusCode::INTERNAL_SERVER_ERROR;
                 message = "内部服务器错误";
@@ -64,6 +144,15 @@ pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, std::convert
     } else if err.find::<warp::filters::body::BodyDeserializeError>().is_some() {
         code = warp::http::StatusCode::BAD_REQUEST;
         message = "请求体格式错误";
+    } else if err
+        .find::<warp::reject::MissingHeader>()
+        .is_some_and(|missing| missing.name() == "authorization")
+    {
+        // Checked before 405: the header is only looked for once a route's
+        // method matched, so other routes on the path rejecting the method
+        // must not hide it
+        code = warp::http::StatusCode::UNAUTHORIZED;
+        message = "需要身份验证";
     } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
         code = warp::http::StatusCode::METHOD_NOT_ALLOWED;
         message = "HTTP方法不允许";
@@ -73,12 +162,24 @@ pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, std::convert
         message = "内部服务器错误";
     }
 
-    let json = json!({
-        "success": false,
-        "error": message,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        "status_code": code.as_u16()
-    });
+    let body = ErrorResponse {
+        success: false,
+        error: message.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        status_code: Some(code.as_u16()),
+    };
+
+    let mut response = reply::with_status(reply::json(&body), code).into_response();
+
+    match err.find::<AppError>() {
+        Some(AppError::RateLimited(decision)) => {
+            rate_limit::insert_headers(response.headers_mut(), decision);
+        }
+        Some(AppError::Timeout) => {
+            response.headers_mut().insert(warp::http::header::RETRY_AFTER, 1.into());
+        }
+        _ => {}
+    }
 
-    Ok(reply::with_status(reply::json(&json), code))
+    Ok(response)
 }
\ No newline at end of file
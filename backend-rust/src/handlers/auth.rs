@@ -1,12 +1,12 @@
 use warp::{Filter, Reply, Rejection, reply::json};
-use serde_json::json;
-use uuid::Uuid;
 
 use crate::{
-    models::{User, CreateUserRequest, LoginRequest},
+    models::{AuthResponse, CreateUserRequest, LoginRequest},
+    errors::{self, ErrorResponse},
     handlers::AppState,
+    metrics,
     utils::{
-        api_response::{success_response, error_response},
+        api_response::{success_response, error_response, ApiResponse, MessageResponse},
         jwt::create_jwt_token,
     },
 };
@@ -66,6 +66,17 @@ fn me(
         .and_then(me_handler)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/register",
+    tag = "auth",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 201, description = "注册成功", body = ApiResponse<AuthResponse>),
+        (status = 400, description = "用户名、邮箱或密码为空", body = ErrorResponse),
+        (status = 409, description = "邮箱或用户名已被使用", body = ErrorResponse),
+    )
+)]
 async fn register_handler(
     register_req: CreateUserRequest,
     state: AppState
@@ -77,7 +88,7 @@ async fn register_handler(
     }
 
     // Check if user already exists
-    match User::find_by_email(state.db.pool(), &register_req.email).await {
+    match state.repos.users.find_by_email(&register_req.email).await {
         Ok(Some(_)) => {
             let response = error_response("该邮箱已被注册");
             return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::CONFLICT));
@@ -86,12 +97,12 @@ async fn register_handler(
         Err(e) => {
             tracing::error!("检查用户邮箱失败: {}", e);
             let response = error_response("注册失败");
-            return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::INTERNAL_SERVER_ERROR));
+            return Ok(warp::reply::with_status(json(&response), errors::status_for(&e)));
         }
     }
 
     // Check if username already exists
-    match User::find_by_username(state.db.pool(), &register_req.username).await {
+    match state.repos.users.find_by_username(&register_req.username).await {
         Ok(Some(_)) => {
             let response = error_response("该用户名已被使用");
             return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::CONFLICT));
@@ -100,29 +111,43 @@ async fn register_handler(
         Err(e) => {
             tracing::error!("检查用户名失败: {}", e);
             let response = error_response("注册失败");
-            return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::INTERNAL_SERVER_ERROR));
+            return Ok(warp::reply::with_status(json(&response), errors::status_for(&e)));
         }
     }
 
     // Create user
-    match User::create(state.db.pool(), register_req).await {
+    match state.repos.users.create(register_req).await {
         Ok(mut user) => {
+            metrics::USERS_REGISTERED_TOTAL.inc();
+
             // Remove password from response
             user.password = "".to_string();
-            let response = success_response(json!({
-                "user": user,
-                "message": "注册成功"
-            }));
+            let response = success_response(AuthResponse {
+                user,
+                token: None,
+                message: "注册成功".to_string(),
+            });
             Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::CREATED))
         }
         Err(e) => {
             tracing::error!("创建用户失败: {}", e);
             let response = error_response("注册失败");
-            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::INTERNAL_SERVER_ERROR))
+            Ok(warp::reply::with_status(json(&response), errors::status_for(&e)))
         }
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "登录成功，返回 JWT", body = ApiResponse<AuthResponse>),
+        (status = 400, description = "邮箱或密码为空", body = ErrorResponse),
+        (status = 401, description = "用户名或密码错误，或账户已被禁用", body = ErrorResponse),
+    )
+)]
 async fn login_handler(
     login_req: LoginRequest,
     state: AppState
@@ -134,16 +159,17 @@ async fn login_handler(
     }
 
     // Find user by email
-    let user = match User::find_by_email(state.db.pool(), &login_req.email).await {
+    let user = match state.repos.users.find_by_email(&login_req.email).await {
         Ok(Some(user)) => user,
         Ok(None) => {
+            metrics::USER_LOGINS_TOTAL.with_label_values(&["failure"]).inc();
             let response = error_response("用户名或密码错误");
             return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::UNAUTHORIZED));
         }
         Err(e) => {
             tracing::error!("查找用户失败: {}", e);
             let response = error_response("登录失败");
-            return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::INTERNAL_SERVER_ERROR));
+            return Ok(warp::reply::with_status(json(&response), errors::status_for(&e)));
         }
     };
 
@@ -157,12 +183,12 @@ async fn login_handler(
     match user.verify_password(&login_req.password) {
         Ok(true) => {
             // Update last login
-            if let Err(e) = User::update_last_login(state.db.pool(), user.id).await {
+            if let Err(e) = state.repos.users.update_last_login(user.id).await {
                 tracing::warn!("更新最后登录时间失败: {}", e);
             }
 
             // Create JWT token
-            let token = match create_jwt_token(user.id, &state.config.jwt_secret) {
+            let token = match create_jwt_token(user.id, &state.config.auth.jwt_secret) {
                 Ok(token) => token,
                 Err(e) => {
                     tracing::error!("创建JWT令牌失败: {}", e);
@@ -175,14 +201,17 @@ async fn login_handler(
             let mut user_data = user.clone();
             user_data.password = "".to_string();
 
-            let response = success_response(json!({
-                "user": user_data,
-                "token": token,
-                "message": "登录成功"
-            }));
+            metrics::USER_LOGINS_TOTAL.with_label_values(&["success"]).inc();
+
+            let response = success_response(AuthResponse {
+                user: user_data,
+                token: Some(token),
+                message: "登录成功".to_string(),
+            });
             Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::OK))
         }
         Ok(false) => {
+            metrics::USER_LOGINS_TOTAL.with_label_values(&["failure"]).inc();
             let response = error_response("用户名或密码错误");
             Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::UNAUTHORIZED))
         }
@@ -194,18 +223,33 @@ async fn login_handler(
     }
 }
 
-async fn logout_handler(state: AppState) -> Result<impl Reply, Rejection> {
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "auth",
+    responses(
+        (status = 200, description = "登出成功", body = ApiResponse<MessageResponse>),
+    )
+)]
+async fn logout_handler(_state: AppState) -> Result<impl Reply, Rejection> {
     // In a stateless JWT system, logout is typically handled client-side
     // by removing the token. For additional security, you might want to 
     // implement a token blacklist using Redis.
     
-    let response = success_response(json!({
-        "message": "登出成功"
-    }));
+    let response = success_response(MessageResponse::new("登出成功"));
     Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::OK))
 }
 
-async fn me_handler(state: AppState) -> Result<impl Reply, Rejection> {
+#[utoipa::path(
+    get,
+    path = "/api/auth/me",
+    tag = "auth",
+    security(("bearer" = [])),
+    responses(
+        (status = 401, description = "需要身份验证", body = ErrorResponse),
+    )
+)]
+async fn me_handler(_state: AppState) -> Result<impl Reply, Rejection> {
     // This endpoint would typically require JWT authentication middleware
     // For now, return a placeholder response
     let response = error_response("需要身份验证");
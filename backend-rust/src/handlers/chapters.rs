@@ -1,9 +1,16 @@
 use warp::{Filter, Reply, Rejection, reply::json};
 use uuid::Uuid;
-use crate::handlers::AppState;
-use crate::models::chapter::{Chapter, CreateChapterRequest, UpdateChapterRequest, ChapterResponse};
-use crate::utils::api_response::{ApiResponse, success_response, error_response};
-use crate::errors::AppError;
+use crate::handlers::{with_audience, with_editor, AppState};
+use crate::models::publishing::{Audience, Editor};
+use crate::models::chapter::{Chapter, CreateChapterRequest, UpdateChapterRequest, ChapterListQuery, ChapterResponse};
+use crate::models::editing::{InsertChapterRequest, MoveChapterRequest, RenumberRequest};
+use crate::models::segmentation::{SegmentChapterRequest, SegmentationPreview};
+use crate::models::sentence::SentenceResponse;
+use crate::services::{publishing, segmentation};
+use crate::utils::api_response::{success_response, ApiResponse};
+use crate::utils::conditional::{self, ConditionalHeaders, IfMatch, Stale, Validators};
+use crate::utils::{merge_patch, pagination};
+use crate::errors::{AppError, ErrorResponse};
 
 pub fn routes(
     state: AppState
@@ -11,47 +18,110 @@ pub fn routes(
     let chapters_base = warp::path("chapters");
     
     let get_all_chapters = chapters_base
-        .and(warp::get())
         .and(warp::path::end())
+        .and(warp::get())
+        .and(pagination::query::<ChapterListQuery>())
+        .and(conditional::headers())
+        .and(with_audience(state.clone()))
         .and(with_state(state.clone()))
         .and_then(get_all_chapters_handler);
 
     let get_chapter_by_id = chapters_base
-        .and(warp::get())
         .and(warp::path::param::<Uuid>())
         .and(warp::path::end())
+        .and(warp::get())
+        .and(conditional::headers())
+        .and(with_audience(state.clone()))
         .and(with_state(state.clone()))
         .and_then(get_chapter_by_id_handler);
 
     let get_chapters_by_classic = warp::path("classics")
         .and(warp::path::param::<Uuid>())
         .and(warp::path("chapters"))
-        .and(warp::get())
         .and(warp::path::end())
+        .and(warp::get())
+        .and(pagination::query::<ChapterListQuery>())
+        .and(conditional::headers())
+        .and(with_audience(state.clone()))
         .and(with_state(state.clone()))
         .and_then(get_chapters_by_classic_handler);
 
     let create_chapter = chapters_base
-        .and(warp::post())
         .and(warp::path::end())
+        .and(warp::post())
         .and(warp::body::json())
+        .and(with_editor(state.clone()))
         .and(with_state(state.clone()))
         .and_then(create_chapter_handler);
 
     let update_chapter = chapters_base
-        .and(warp::put())
         .and(warp::path::param::<Uuid>())
         .and(warp::path::end())
-        .and(warp::body::json())
+        .and(warp::put().or(warp::patch()).unify())
+        .and(merge_patch::body())
+        .and(conditional::if_match())
+        .and(with_editor(state.clone()))
         .and(with_state(state.clone()))
         .and_then(update_chapter_handler);
 
     let delete_chapter = chapters_base
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
         .and(warp::delete())
+        .and(with_editor(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(delete_chapter_handler);
+
+    let insert_chapter = warp::path("classics")
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("chapters"))
+        .and(warp::path("insert"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_editor(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(insert_chapter_handler);
+
+    let renumber_chapters = warp::path("classics")
         .and(warp::path::param::<Uuid>())
+        .and(warp::path("chapters"))
+        .and(warp::path("renumber"))
         .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_editor(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(renumber_chapters_handler);
+
+    let move_chapter = chapters_base
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("move"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_editor(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(move_chapter_handler);
+
+    let preview_segmentation = chapters_base
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("segmentation"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_audience(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(preview_segmentation_handler);
+
+    let segment_chapter = chapters_base
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("segmentation"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_editor(state.clone()))
         .and(with_state(state))
-        .and_then(delete_chapter_handler);
+        .and_then(segment_chapter_handler);
 
     get_all_chapters
         .or(get_chapter_by_id)
@@ -59,6 +129,14 @@ pub fn routes(
         .or(create_chapter)
         .or(update_chapter)
         .or(delete_chapter)
+        .or(insert_chapter)
+        .or(renumber_chapters)
+        .or(move_chapter)
+        .or(preview_segmentation)
+        .or(segment_chapter)
+        // Boxed so the route futures live on the heap; unboxed they overflow
+        // the stack of a debug build
+        .boxed()
 }
 
 fn with_state(
@@ -67,61 +145,129 @@ fn with_state(
     warp::any().map(move || state.clone())
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/chapters",
+    tag = "chapters",
+    params(ChapterListQuery, ConditionalHeaders),
+    responses(
+        (status = 200, description = "One page of chapters", body = ApiResponse<Vec<ChapterResponse>>),
+        (status = 304, description = "Cached copy is still current"),
+        (status = 400, description = "Invalid pagination, sort or filter", body = ErrorResponse),
+    )
+)]
 async fn get_all_chapters_handler(
+    query: ChapterListQuery,
+    conditional: ConditionalHeaders,
+    audience: Audience,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Chapter::find_all(&state.db_pool).await {
+    let (mut filter, page) = query.into_parts().map_err(|e| warp::reject::custom(AppError::Validation(e)))?;
+    filter.published_only = audience.is_public();
+
+    match state.repos.chapters.list(&filter, &page).await {
         Ok(chapters) => {
-            let responses: Vec<ChapterResponse> = chapters.into_iter().map(|c| c.into()).collect();
-            Ok(json(&success_response(responses)))
+            let validators = Validators::from_page(&chapters, |c| (c.id, c.updated_at));
+            Ok(conditional::reply_page(chapters.map(ChapterResponse::from), validators, &conditional))
         }
         Err(e) => {
             tracing::error!("Failed to fetch chapters: {}", e);
-            Err(warp::reject::custom(AppError::DatabaseError))
+            Err(warp::reject::custom(AppError::from(e)))
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/chapters/{id}",
+    tag = "chapters",
+    params(("id" = Uuid, Path, description = "Chapter id"), ConditionalHeaders),
+    responses(
+        (status = 200, description = "The chapter", body = ApiResponse<ChapterResponse>),
+        (status = 304, description = "Cached copy is still current"),
+        (status = 404, description = "No such chapter", body = ErrorResponse),
+    )
+)]
 async fn get_chapter_by_id_handler(
     id: Uuid,
+    conditional: ConditionalHeaders,
+    audience: Audience,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Chapter::find_by_id(&state.db_pool, id).await {
+    match publishing::find_chapter(&state.repos, id, audience).await {
         Ok(Some(chapter)) => {
+            let validators = Validators::single(chapter.id, chapter.updated_at);
             let response: ChapterResponse = chapter.into();
-            Ok(json(&success_response(response)))
+            Ok(conditional::reply(response, validators, &conditional))
         }
         Ok(None) => {
-            Err(warp::reject::custom(AppError::NotFound("Chapter not found".to_string())))
+            Err(warp::reject::custom(AppError::NotFound))
         }
         Err(e) => {
             tracing::error!("Failed to fetch chapter {}: {}", id, e);
-            Err(warp::reject::custom(AppError::DatabaseError))
+            Err(warp::reject::custom(AppError::from(e)))
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/classics/{classic_id}/chapters",
+    tag = "chapters",
+    params(("classic_id" = Uuid, Path, description = "Classic id"), ChapterListQuery, ConditionalHeaders),
+    responses(
+        (status = 200, description = "Chapters of the classic, in order", body = ApiResponse<Vec<ChapterResponse>>),
+        (status = 304, description = "Cached copy is still current"),
+        (status = 400, description = "Invalid pagination or sort", body = ErrorResponse),
+    )
+)]
 async fn get_chapters_by_classic_handler(
     classic_id: Uuid,
+    mut query: ChapterListQuery,
+    conditional: ConditionalHeaders,
+    audience: Audience,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Chapter::find_by_classic_id(&state.db_pool, classic_id).await {
+    // The path decides the classic, whatever the query string says
+    query.classic_id = Some(classic_id);
+    let (mut filter, page) = query.into_parts().map_err(|e| warp::reject::custom(AppError::Validation(e)))?;
+    filter.published_only = audience.is_public();
+
+    match state.repos.chapters.list(&filter, &page).await {
         Ok(chapters) => {
-            let responses: Vec<ChapterResponse> = chapters.into_iter().map(|c| c.into()).collect();
-            Ok(json(&success_response(responses)))
+            let validators = Validators::from_page(&chapters, |c| (c.id, c.updated_at));
+            Ok(conditional::reply_page(chapters.map(ChapterResponse::from), validators, &conditional))
         }
         Err(e) => {
             tracing::error!("Failed to fetch chapters for classic {}: {}", classic_id, e);
-            Err(warp::reject::custom(AppError::DatabaseError))
+            Err(warp::reject::custom(AppError::from(e)))
         }
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/chapters",
+    tag = "chapters",
+    request_body = CreateChapterRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "Created", body = ApiResponse<ChapterResponse>),
+        (status = 400, description = "Number already taken", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such classic", body = ErrorResponse),
+    )
+)]
 async fn create_chapter_handler(
     req: CreateChapterRequest,
+    editor: Editor,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Chapter::create(&state.db_pool, req).await {
+    publishing::check_classic_editable(&state.repos, req.classic_id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    match state.repos.chapters.create(req, &editor.edit(None)).await {
         Ok(chapter) => {
             let response: ChapterResponse = chapter.into();
             Ok(warp::reply::with_status(
@@ -132,48 +278,292 @@ async fn create_chapter_handler(
         Err(e) => {
             tracing::error!("Failed to create chapter: {}", e);
             if e.to_string().contains("duplicate key") {
-                Err(warp::reject::custom(AppError::BadRequest("Chapter number already exists for this classic".to_string())))
+                Err(warp::reject::custom(AppError::Validation("Chapter number already exists for this classic".to_string())))
             } else {
-                Err(warp::reject::custom(AppError::DatabaseError))
+                Err(warp::reject::custom(AppError::from(e)))
             }
         }
     }
 }
 
+#[utoipa::path(
+    method(put, patch),
+    path = "/api/chapters/{id}",
+    tag = "chapters",
+    params(("id" = Uuid, Path, description = "Chapter id"), IfMatch),
+    request_body(content((UpdateChapterRequest = "application/merge-patch+json"), (UpdateChapterRequest = "application/json"))),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Updated; the ETag is the new version's. No `data` unless the caller is an admin", body = ApiResponse<ChapterResponse>),
+        (status = 400, description = "`null` for `number` or `title`, or resegmenting a chapter without content", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such chapter", body = ErrorResponse),
+        (status = 412, description = "Stale `If-Match`; the ETag, and `data` for admins, are the current version", body = ApiResponse<ChapterResponse>),
+    )
+)]
 async fn update_chapter_handler(
     id: Uuid,
     req: UpdateChapterRequest,
+    if_match: IfMatch,
+    editor: Editor,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Chapter::update(&state.db_pool, id, req).await {
+    publishing::check_chapter_editable(&state.repos, id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    let readable = !editor.audience.is_public();
+    let edit = editor.edit(req.comment.clone());
+    match state.repos.chapters.update(id, req, &if_match, &edit).await {
         Ok(Some(chapter)) => {
-            let response: ChapterResponse = chapter.into();
-            Ok(json(&success_response(response)))
+            let validators = Validators::single(chapter.id, chapter.updated_at);
+            let response = readable.then(|| ChapterResponse::from(chapter));
+            Ok(conditional::reply_updated(response, validators))
         }
         Ok(None) => {
-            Err(warp::reject::custom(AppError::NotFound("Chapter not found".to_string())))
-        }
-        Err(e) => {
-            tracing::error!("Failed to update chapter {}: {}", id, e);
-            Err(warp::reject::custom(AppError::DatabaseError))
+            Err(warp::reject::custom(AppError::NotFound))
         }
+        Err(e) => match e.downcast::<Stale<Chapter>>() {
+            Ok(Stale(current)) => {
+                let validators = Validators::single(current.id, current.updated_at);
+                let response = readable.then(|| ChapterResponse::from(current));
+                Ok(conditional::reply_stale(response, validators, "Chapter was changed since it was read"))
+            }
+            Err(e) => {
+                tracing::error!("Failed to update chapter {}: {}", id, e);
+                Err(warp::reject::custom(AppError::from(e)))
+            }
+        },
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/chapters/{id}",
+    tag = "chapters",
+    params(("id" = Uuid, Path, description = "Chapter id")),
+    responses(
+        (status = 200, description = "Moved to the trash along with its sentences", body = ApiResponse<String>),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such chapter", body = ErrorResponse),
+    )
+)]
 async fn delete_chapter_handler(
     id: Uuid,
+    editor: Editor,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Chapter::delete(&state.db_pool, id).await {
+    publishing::check_chapter_editable(&state.repos, id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    let edit = editor.edit(None);
+    match state.repos.chapters.delete(id, &edit).await {
         Ok(true) => {
             Ok(json(&success_response("Chapter deleted successfully")))
         }
         Ok(false) => {
-            Err(warp::reject::custom(AppError::NotFound("Chapter not found".to_string())))
+            Err(warp::reject::custom(AppError::NotFound))
         }
         Err(e) => {
             tracing::error!("Failed to delete chapter {}: {}", id, e);
-            Err(warp::reject::custom(AppError::DatabaseError))
+            Err(warp::reject::custom(AppError::from(e)))
         }
     }
-}
\ No newline at end of file
+}
+#[utoipa::path(
+    post,
+    path = "/api/classics/{classic_id}/chapters/insert",
+    tag = "chapters",
+    params(("classic_id" = Uuid, Path, description = "Classic id")),
+    request_body = InsertChapterRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "Inserted; later chapters moved down by one", body = ApiResponse<ChapterResponse>),
+        (status = 400, description = "Position out of range", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such classic", body = ErrorResponse),
+    )
+)]
+async fn insert_chapter_handler(
+    classic_id: Uuid,
+    req: InsertChapterRequest,
+    editor: Editor,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    publishing::check_classic_editable(&state.repos, classic_id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    match state.repos.chapters.insert_at(classic_id, req, &editor.edit(None)).await {
+        Ok(Some(chapter)) => {
+            let response: ChapterResponse = chapter.into();
+            Ok(warp::reply::with_status(
+                json(&success_response(response)),
+                warp::http::StatusCode::CREATED
+            ))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to insert chapter into classic {}: {}", classic_id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/classics/{classic_id}/chapters/renumber",
+    tag = "chapters",
+    params(("classic_id" = Uuid, Path, description = "Classic id")),
+    request_body = RenumberRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Chapters of the classic, numbered from 1 in the new order", body = ApiResponse<Vec<ChapterResponse>>),
+        (status = 400, description = "Order does not list every chapter once", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such classic", body = ErrorResponse),
+    )
+)]
+async fn renumber_chapters_handler(
+    classic_id: Uuid,
+    req: RenumberRequest,
+    editor: Editor,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    publishing::check_classic_editable(&state.repos, classic_id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    match state.repos.chapters.renumber(classic_id, req.order, &editor.edit(None)).await {
+        Ok(Some(chapters)) => {
+            let response: Vec<ChapterResponse> = chapters.into_iter().map(ChapterResponse::from).collect();
+            Ok(json(&success_response(response)))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to renumber chapters of classic {}: {}", classic_id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/chapters/{id}/move",
+    tag = "chapters",
+    params(("id" = Uuid, Path, description = "Chapter id")),
+    request_body = MoveChapterRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Moved; the classic's chapters renumbered", body = ApiResponse<ChapterResponse>),
+        (status = 400, description = "Position out of range", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such chapter", body = ErrorResponse),
+    )
+)]
+async fn move_chapter_handler(
+    id: Uuid,
+    req: MoveChapterRequest,
+    editor: Editor,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    publishing::check_chapter_editable(&state.repos, id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    match state.repos.chapters.move_to(id, req.position, &editor.edit(None)).await {
+        Ok(Some(chapter)) => {
+            let response: ChapterResponse = chapter.into();
+            Ok(json(&success_response(response)))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to move chapter {}: {}", id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/chapters/{id}/segmentation",
+    tag = "chapters",
+    params(("id" = Uuid, Path, description = "Chapter id")),
+    responses(
+        (status = 200, description = "Sentences the chapter content would be split into", body = ApiResponse<SegmentationPreview>),
+        (status = 404, description = "No such chapter", body = ErrorResponse),
+    )
+)]
+async fn preview_segmentation_handler(
+    id: Uuid,
+    audience: Audience,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    let chapter = match publishing::find_chapter(&state.repos, id, audience).await {
+        Ok(Some(chapter)) => chapter,
+        Ok(None) => return Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to fetch chapter {}: {}", id, e);
+            return Err(warp::reject::custom(AppError::from(e)));
+        }
+    };
+
+    match segmentation::preview(&state.repos, &chapter).await {
+        Ok(preview) => Ok(json(&success_response(preview))),
+        Err(e) => {
+            tracing::error!("Failed to preview segmentation of chapter {}: {}", id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/chapters/{id}/segmentation",
+    tag = "chapters",
+    params(("id" = Uuid, Path, description = "Chapter id")),
+    request_body = SegmentChapterRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "Sentences created from the chapter content, in order", body = ApiResponse<Vec<SentenceResponse>>),
+        (status = 400, description = "No content, or sentences exist and replace was not set", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such chapter", body = ErrorResponse),
+    )
+)]
+async fn segment_chapter_handler(
+    id: Uuid,
+    req: SegmentChapterRequest,
+    editor: Editor,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    publishing::check_chapter_editable(&state.repos, id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    let chapter = match state.repos.chapters.find_by_id(id).await {
+        Ok(Some(chapter)) => chapter,
+        Ok(None) => return Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to fetch chapter {}: {}", id, e);
+            return Err(warp::reject::custom(AppError::from(e)));
+        }
+    };
+
+    let edit = editor.edit(None);
+    match segmentation::apply(&state.repos, &chapter, req.replace, &edit).await {
+        Ok(Some(sentences)) => {
+            let response: Vec<SentenceResponse> = sentences.into_iter().map(SentenceResponse::from).collect();
+            Ok(warp::reply::with_status(
+                json(&success_response(response)),
+                warp::http::StatusCode::CREATED
+            ))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to segment chapter {}: {}", id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use warp::{Filter, Reply, Rejection};
 use crate::handlers::AppState;
+use crate::utils::api_response::MessageResponse;
 
 pub fn routes(
     _state: AppState
@@ -7,7 +8,17 @@ pub fn routes(
     // Placeholder for characters routes
     warp::path("characters")
         .and(warp::get())
-        .map(|| warp::reply::json(&serde_json::json!({
-            "message": "Characters API - 待实现"
-        })))
-}
\ No newline at end of file
+        .map(characters_handler)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/characters",
+    tag = "characters",
+    responses(
+        (status = 200, description = "尚未实现", body = MessageResponse),
+    )
+)]
+fn characters_handler() -> impl Reply {
+    warp::reply::json(&MessageResponse::new("Characters API - 待实现"))
+}
@@ -1,11 +1,22 @@
 use warp::{Filter, Reply, Rejection, reply::json};
-use serde_json::json;
+use chrono::Utc;
 use uuid::Uuid;
 
 use crate::{
-    models::{Classic, CreateClassicRequest, UpdateClassicRequest},
-    handlers::AppState,
-    utils::api_response::{ApiResponse, success_response, error_response},
+    middleware,
+    models::{
+        Audience, ChangeStatusRequest, Classic, ClassicListQuery, ClassicTree, ClassicTreeQuery,
+        CreateClassicRequest, Edit, Editor, UpdateClassicRequest,
+    },
+    errors::{self, AppError, ErrorResponse},
+    handlers::{with_audience, with_editor, AppState},
+    services::{cache, classic_tree, publishing},
+    utils::{
+        api_response::{success_response, error_response, ApiResponse, MessageResponse},
+        conditional::{self, ConditionalHeaders, IfMatch, Stale, Validators},
+        merge_patch,
+        pagination::{self, Page},
+    },
 };
 
 pub fn routes(
@@ -13,9 +24,11 @@ pub fn routes(
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     list_classics(state.clone())
         .or(get_classic(state.clone()))
+        .or(get_classic_tree(state.clone()))
         .or(create_classic(state.clone()))
         .or(update_classic(state.clone()))
-        .or(delete_classic(state))
+        .or(delete_classic(state.clone()))
+        .or(change_status(state))
 }
 
 // GET /api/classics
@@ -23,7 +36,11 @@ fn list_classics(
     state: AppState
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path("classics")
+        .and(warp::path::end())
         .and(warp::get())
+        .and(pagination::query::<ClassicListQuery>())
+        .and(conditional::headers())
+        .and(with_audience(state.clone()))
         .and(warp::any().map(move || state.clone()))
         .and_then(list_classics_handler)
 }
@@ -34,30 +51,54 @@ fn get_classic(
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path("classics")
         .and(warp::path::param::<String>())
+        .and(warp::path::end())
         .and(warp::get())
+        .and(conditional::headers())
+        .and(with_audience(state.clone()))
         .and(warp::any().map(move || state.clone()))
         .and_then(get_classic_handler)
 }
 
+// GET /api/classics/:slug/tree
+fn get_classic_tree(
+    state: AppState
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path("classics")
+        .and(warp::path::param::<String>())
+        .and(warp::path("tree"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(pagination::query::<ClassicTreeQuery>())
+        .and(conditional::headers())
+        .and(with_audience(state.clone()))
+        .and(warp::any().map(move || state.clone()))
+        .and_then(get_classic_tree_handler)
+}
+
 // POST /api/classics
 fn create_classic(
     state: AppState
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path("classics")
+        .and(warp::path::end())
         .and(warp::post())
         .and(warp::body::json())
+        .and(with_editor(state.clone()))
         .and(warp::any().map(move || state.clone()))
         .and_then(create_classic_handler)
 }
 
-// PUT /api/classics/:id
+// PUT or PATCH /api/classics/:id
 fn update_classic(
     state: AppState
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path("classics")
         .and(warp::path::param::<Uuid>())
-        .and(warp::put())
-        .and(warp::body::json())
+        .and(warp::path::end())
+        .and(warp::put().or(warp::patch()).unify())
+        .and(merge_patch::body())
+        .and(conditional::if_match())
+        .and(with_editor(state.clone()))
         .and(warp::any().map(move || state.clone()))
         .and_then(update_classic_handler)
 }
@@ -68,51 +109,200 @@ fn delete_classic(
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path("classics")
         .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
         .and(warp::delete())
+        .and(with_editor(state.clone()))
         .and(warp::any().map(move || state.clone()))
         .and_then(delete_classic_handler)
 }
 
-async fn list_classics_handler(state: AppState) -> Result<impl Reply, Rejection> {
-    match Classic::find_all(state.db.pool()).await {
+// POST /api/classics/:id/status
+fn change_status(
+    state: AppState
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path("classics")
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("status"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(middleware::auth::with_auth(state.config.auth.jwt_secret.clone()))
+        .and(warp::any().map(move || state.clone()))
+        .and_then(change_status_handler)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/classics",
+    tag = "classics",
+    params(ClassicListQuery, ConditionalHeaders),
+    responses(
+        (status = 200, description = "一页经典", body = ApiResponse<Vec<Classic>>),
+        (status = 304, description = "缓存仍然有效"),
+        (status = 400, description = "分页、排序或筛选参数无效", body = ErrorResponse),
+        (status = 500, description = "获取失败", body = ErrorResponse),
+        (status = 503, description = "数据库超时", body = ErrorResponse),
+    )
+)]
+async fn list_classics_handler(
+    query: ClassicListQuery,
+    conditional: ConditionalHeaders,
+    audience: Audience,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    let (mut filter, page) = match query.into_parts() {
+        Ok(parts) => parts,
+        Err(message) => {
+            let response = error_response(&message);
+            return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::BAD_REQUEST).into_response());
+        }
+    };
+    filter.published_only = audience.is_public();
+    let cache_key = cache::classics_list_key(&filter, &page);
+
+    if let Some(redis) = &state.redis {
+        if let Some(classics) = cache::get_json::<Page<Classic>>(redis, &cache_key).await {
+            let validators = Validators::from_page(&classics, |c| (c.id, c.updated_at));
+            return Ok(conditional::reply_page(classics, validators, &conditional));
+        }
+    }
+
+    match state.repos.classics.list(&filter, &page).await {
         Ok(classics) => {
-            let response = success_response(classics);
-            Ok(json(&response))
+            if let Some(redis) = &state.redis {
+                // A public page must not outlive the next scheduled publish or unpublish
+                let ttl = if filter.published_only {
+                    state
+                        .repos
+                        .classics
+                        .next_scheduled_change()
+                        .await
+                        .map(|next| cache::ttl_until(next, Utc::now()))
+                } else {
+                    Ok(cache::DEFAULT_TTL_SECS)
+                };
+                match ttl {
+                    Ok(ttl) => cache::set_json(redis, &cache_key, &classics, ttl).await,
+                    Err(e) => tracing::warn!("获取定时发布时间失败，不缓存经典列表: {}", e),
+                }
+            }
+            let validators = Validators::from_page(&classics, |c| (c.id, c.updated_at));
+            Ok(conditional::reply_page(classics, validators, &conditional))
         }
         Err(e) => {
             tracing::error!("获取经典列表失败: {}", e);
             let response = error_response("获取经典列表失败");
-            Ok(json(&response))
+            Ok(warp::reply::with_status(json(&response), errors::status_for(&e)).into_response())
         }
     }
 }
 
-async fn get_classic_handler(slug: String, state: AppState) -> Result<impl Reply, Rejection> {
+#[utoipa::path(
+    get,
+    path = "/api/classics/{slug}",
+    tag = "classics",
+    params(("slug" = String, Path, description = "经典标识符"), ConditionalHeaders),
+    responses(
+        (status = 200, description = "经典详情", body = ApiResponse<Classic>),
+        (status = 304, description = "缓存仍然有效"),
+        (status = 400, description = "无效的经典标识符", body = ErrorResponse),
+        (status = 404, description = "经典未找到", body = ErrorResponse),
+    )
+)]
+async fn get_classic_handler(
+    slug: String,
+    conditional: ConditionalHeaders,
+    audience: Audience,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
     // Validate slug format
     if !slug.chars().all(|c| c.is_alphanumeric() || c == '-') {
         let response = error_response("无效的经典标识符");
-        return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::BAD_REQUEST));
+        return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::BAD_REQUEST).into_response());
     }
 
-    match Classic::find_by_slug(state.db.pool(), &slug).await {
-        Ok(Some(classic)) => {
-            let response = success_response(classic);
-            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::OK))
+    match state.repos.classics.find_by_slug(&slug).await {
+        Ok(Some(classic)) if !audience.is_public() || classic.is_public(Utc::now()) => {
+            let validators = Validators::single(classic.id, classic.updated_at);
+            Ok(conditional::reply(classic, validators, &conditional))
         }
-        Ok(None) => {
+        Ok(_) => {
             let response = error_response("经典未找到");
-            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::NOT_FOUND))
+            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::NOT_FOUND).into_response())
         }
         Err(e) => {
             tracing::error!("获取经典详情失败: {}", e);
             let response = error_response("获取经典详情失败");
-            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::INTERNAL_SERVER_ERROR))
+            Ok(warp::reply::with_status(json(&response), errors::status_for(&e)).into_response())
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/classics/{slug}/tree",
+    tag = "classics",
+    params(("slug" = String, Path, description = "经典标识符"), ClassicTreeQuery, ConditionalHeaders),
+    responses(
+        (status = 200, description = "经典及其全部章节，按章节序号排列", body = ApiResponse<ClassicTree>),
+        (status = 304, description = "缓存仍然有效"),
+        (status = 400, description = "无效的经典标识符或 include 参数", body = ErrorResponse),
+        (status = 404, description = "经典未找到", body = ErrorResponse),
+    )
+)]
+async fn get_classic_tree_handler(
+    slug: String,
+    query: ClassicTreeQuery,
+    conditional: ConditionalHeaders,
+    audience: Audience,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    if !slug.chars().all(|c| c.is_alphanumeric() || c == '-') {
+        let response = error_response("无效的经典标识符");
+        return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::BAD_REQUEST).into_response());
+    }
+    let include = match query.include() {
+        Ok(include) => include,
+        Err(message) => {
+            let response = error_response(&message);
+            return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::BAD_REQUEST).into_response());
+        }
+    };
+
+    match classic_tree::load(&state.repos, &slug, include).await {
+        Ok(Some(tree)) if !audience.is_public() || tree.classic.is_public(Utc::now()) => {
+            let validators = classic_tree::validators(&tree, include);
+            Ok(conditional::reply(tree, validators, &conditional))
+        }
+        Ok(_) => {
+            let response = error_response("经典未找到");
+            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::NOT_FOUND).into_response())
+        }
+        Err(e) => {
+            tracing::error!("获取经典目录失败: {}", e);
+            let response = error_response("获取经典目录失败");
+            Ok(warp::reply::with_status(json(&response), errors::status_for(&e)).into_response())
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/classics",
+    tag = "classics",
+    request_body = CreateClassicRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "创建成功，新经典为草稿", body = ApiResponse<Classic>),
+        (status = 400, description = "标题或标识符为空", body = ErrorResponse),
+        (status = 401, description = "未登录", body = ErrorResponse),
+        (status = 403, description = "没有编辑权限", body = ErrorResponse),
+        (status = 409, description = "标识符已存在", body = ErrorResponse),
+    )
+)]
 async fn create_classic_handler(
     create_req: CreateClassicRequest,
+    editor: Editor,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
     // Basic validation
@@ -122,58 +312,111 @@ async fn create_classic_handler(
     }
 
     // Check if slug already exists
-    match Classic::find_by_slug(state.db.pool(), &create_req.slug).await {
-        Ok(Some(_)) => {
+    match state.repos.classics.slug_exists(&create_req.slug).await {
+        Ok(true) => {
             let response = error_response("该标识符已存在");
             return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::CONFLICT));
         }
-        Ok(None) => {}
+        Ok(false) => {}
         Err(e) => {
             tracing::error!("检查标识符唯一性失败: {}", e);
             let response = error_response("创建经典失败");
-            return Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::INTERNAL_SERVER_ERROR));
+            return Ok(warp::reply::with_status(json(&response), errors::status_for(&e)));
         }
     }
 
-    match Classic::create(state.db.pool(), create_req).await {
+    match state.repos.classics.create(create_req, &editor.edit(None)).await {
         Ok(classic) => {
+            invalidate_list_cache(&state).await;
             let response = success_response(classic);
             Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::CREATED))
         }
         Err(e) => {
             tracing::error!("创建经典失败: {}", e);
             let response = error_response("创建经典失败");
-            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::INTERNAL_SERVER_ERROR))
+            Ok(warp::reply::with_status(json(&response), errors::status_for(&e)))
         }
     }
 }
 
+#[utoipa::path(
+    method(put, patch),
+    path = "/api/classics/{id}",
+    tag = "classics",
+    params(("id" = Uuid, Path, description = "经典 ID"), IfMatch),
+    request_body(content((UpdateClassicRequest = "application/merge-patch+json"), (UpdateClassicRequest = "application/json"))),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "更新成功，ETag 为新版本；非管理员不返回 data", body = ApiResponse<Classic>),
+        (status = 400, description = "请求体无效，或 title 为 null", body = ErrorResponse),
+        (status = 401, description = "未登录", body = ErrorResponse),
+        (status = 403, description = "没有编辑权限，或经典已发布而调用者不是管理员", body = ErrorResponse),
+        (status = 404, description = "经典未找到", body = ErrorResponse),
+        (status = 412, description = "If-Match 已过期，data 为当前版本（非管理员不返回），ETag 为其标签", body = ApiResponse<Classic>),
+    )
+)]
 async fn update_classic_handler(
     id: Uuid,
     update_req: UpdateClassicRequest,
+    if_match: IfMatch,
+    editor: Editor,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Classic::update(state.db.pool(), id, update_req).await {
+    if let Some(refusal) = refuse_change(&state, id, &editor).await {
+        return Ok(refusal.into_response());
+    }
+    let readable = !editor.audience.is_public();
+    let edit = editor.edit(update_req.comment.clone());
+    match state.repos.classics.update(id, update_req, &if_match, &edit).await {
         Ok(Some(classic)) => {
-            let response = success_response(classic);
-            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::OK))
+            invalidate_list_cache(&state).await;
+            let validators = Validators::single(classic.id, classic.updated_at);
+            Ok(conditional::reply_updated(readable.then_some(classic), validators))
         }
         Ok(None) => {
             let response = error_response("经典未找到");
-            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::NOT_FOUND))
-        }
-        Err(e) => {
-            tracing::error!("更新经典失败: {}", e);
-            let response = error_response("更新经典失败");
-            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::INTERNAL_SERVER_ERROR))
+            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::NOT_FOUND).into_response())
         }
+        Err(e) => match e.downcast::<Stale<Classic>>() {
+            Ok(Stale(current)) => {
+                let validators = Validators::single(current.id, current.updated_at);
+                Ok(conditional::reply_stale(readable.then_some(current), validators, "经典已被他人修改，请基于当前版本重新提交"))
+            }
+            Err(e) => {
+                tracing::error!("更新经典失败: {}", e);
+                let response = error_response("更新经典失败");
+                Ok(warp::reply::with_status(json(&response), errors::status_for(&e)).into_response())
+            }
+        },
     }
 }
 
-async fn delete_classic_handler(id: Uuid, state: AppState) -> Result<impl Reply, Rejection> {
-    match Classic::delete(state.db.pool(), id).await {
+#[utoipa::path(
+    delete,
+    path = "/api/classics/{id}",
+    tag = "classics",
+    params(("id" = Uuid, Path, description = "经典 ID")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "已移入回收站，章节和句子一并移入，可从回收站恢复", body = ApiResponse<MessageResponse>),
+        (status = 401, description = "未登录", body = ErrorResponse),
+        (status = 403, description = "没有编辑权限，或经典已发布而调用者不是管理员", body = ErrorResponse),
+        (status = 404, description = "经典未找到", body = ErrorResponse),
+    )
+)]
+async fn delete_classic_handler(
+    id: Uuid,
+    editor: Editor,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    if let Some(refusal) = refuse_change(&state, id, &editor).await {
+        return Ok(refusal);
+    }
+    let edit = editor.edit(None);
+    match state.repos.classics.delete(id, &edit).await {
         Ok(true) => {
-            let response = success_response(json!({"message": "经典删除成功"}));
+            invalidate_list_cache(&state).await;
+            let response = success_response(MessageResponse::new("经典删除成功"));
             Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::OK))
         }
         Ok(false) => {
@@ -183,7 +426,87 @@ async fn delete_classic_handler(id: Uuid, state: AppState) -> Result<impl Reply,
         Err(e) => {
             tracing::error!("删除经典失败: {}", e);
             let response = error_response("删除经典失败");
-            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::INTERNAL_SERVER_ERROR))
+            Ok(warp::reply::with_status(json(&response), errors::status_for(&e)))
         }
     }
-}
\ No newline at end of file
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/classics/{id}/status",
+    tag = "classics",
+    params(("id" = Uuid, Path, description = "经典 ID")),
+    request_body = ChangeStatusRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "状态已更改，并记录一次修订", body = ApiResponse<Classic>),
+        (status = 400, description = "不允许的状态变化或无效的上线、下线时间", body = ErrorResponse),
+        (status = 401, description = "未登录", body = ErrorResponse),
+        (status = 403, description = "发布、撤回或调整定时需要管理员", body = ErrorResponse),
+        (status = 404, description = "经典未找到", body = ErrorResponse),
+    )
+)]
+async fn change_status_handler(
+    id: Uuid,
+    req: ChangeStatusRequest,
+    user_id: Uuid,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    let edit = Edit { editor_id: Some(user_id), comment: req.comment.clone() };
+    let result = async {
+        let audience = publishing::audience(&state.repos, Some(user_id)).await?;
+        state.repos.classics.change_status(id, &req, audience, &edit).await
+    }
+    .await;
+
+    match result {
+        Ok(Some(classic)) => {
+            invalidate_list_cache(&state).await;
+            let response = success_response(classic);
+            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::OK))
+        }
+        Ok(None) => {
+            let response = error_response("经典未找到");
+            Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::NOT_FOUND))
+        }
+        Err(e) => match e.downcast_ref::<AppError>() {
+            Some(AppError::Validation(message)) => {
+                let response = error_response(message);
+                Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::BAD_REQUEST))
+            }
+            Some(AppError::Forbidden) => {
+                let response = error_response("只有管理员可以发布、撤回或调整定时");
+                Ok(warp::reply::with_status(json(&response), warp::http::StatusCode::FORBIDDEN))
+            }
+            _ => {
+                tracing::error!("更改经典状态失败: {}", e);
+                let response = error_response("更改经典状态失败");
+                Ok(warp::reply::with_status(json(&response), errors::status_for(&e)))
+            }
+        },
+    }
+}
+
+/// The reply refusing `editor` a change to classic `id`, if they may not make it.
+async fn refuse_change(
+    state: &AppState,
+    id: Uuid,
+    editor: &Editor
+) -> Option<warp::reply::WithStatus<warp::reply::Json>> {
+    let e = publishing::check_classic_editable(&state.repos, id, editor).await.err()?;
+    let (status, message) = match e.downcast_ref::<AppError>() {
+        Some(AppError::NotFound) => (warp::http::StatusCode::NOT_FOUND, "经典未找到"),
+        Some(AppError::Forbidden) => (warp::http::StatusCode::FORBIDDEN, "已发布的经典只有管理员可以修改"),
+        _ => {
+            tracing::error!("获取经典失败: {}", e);
+            (errors::status_for(&e), "获取经典失败")
+        }
+    };
+    Some(warp::reply::with_status(json(&error_response(message)), status))
+}
+
+async fn invalidate_list_cache(state: &AppState) {
+    if let Some(redis) = &state.redis {
+        cache::invalidate_prefix(redis, cache::CLASSICS_LIST_PREFIX).await;
+    }
+}
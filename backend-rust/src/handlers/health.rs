@@ -1,20 +1,46 @@
+use serde::Serialize;
+use utoipa::ToSchema;
 use warp::{Reply, Rejection};
-use serde_json::json;
-use std::convert::Infallible;
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthResponse {
+    pub status: &'static str,
+    pub service: &'static str,
+    pub version: &'static str,
+    #[schema(format = DateTime)]
+    pub timestamp: String,
+    pub environment: String,
+    pub services: ServiceStatus,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ServiceStatus {
+    pub database: &'static str,
+    pub redis: &'static str,
+    pub api: &'static str,
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "服务运行中", body = HealthResponse),
+    )
+)]
 pub async fn health_check() -> Result<impl Reply, Rejection> {
-    let response = json!({
-        "status": "healthy",
-        "service": "xiaoxiao-dushulang-rust-backend",
-        "version": "2.0.0",
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        "environment": std::env::var("NODE_ENV").unwrap_or_else(|_| "development".to_string()),
-        "services": {
-            "database": "connected",
-            "redis": "connected",
-            "api": "running"
-        }
-    });
+    let response = HealthResponse {
+        status: "healthy",
+        service: "xiaoxiao-dushulang-rust-backend",
+        version: "2.0.0",
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        environment: std::env::var("NODE_ENV").unwrap_or_else(|_| "development".to_string()),
+        services: ServiceStatus {
+            database: "connected",
+            redis: "connected",
+            api: "running",
+        },
+    };
 
     Ok(warp::reply::json(&response))
-}
\ No newline at end of file
+}
@@ -0,0 +1,33 @@
+use warp::{Filter, Reply, Rejection, http::header::CONTENT_TYPE};
+
+use crate::{errors::AppError, handlers::AppState, metrics};
+
+// GET /metrics
+pub fn routes(
+    state: AppState
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path("metrics")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::any().map(move || state.clone()))
+        .and_then(metrics_handler)
+}
+
+async fn metrics_handler(state: AppState) -> Result<impl Reply, Rejection> {
+    // Pool gauges are sampled at scrape time
+    if let Some(db) = &state.db {
+        metrics::record_pool_stats(db);
+    }
+
+    match metrics::gather() {
+        Ok(body) => Ok(warp::reply::with_header(
+            body,
+            CONTENT_TYPE,
+            prometheus::TEXT_FORMAT,
+        )),
+        Err(e) => {
+            tracing::error!("导出指标失败: {}", e);
+            Err(warp::reject::custom(AppError::Internal))
+        }
+    }
+}
@@ -2,28 +2,115 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use redis::aio::Connection;
 
-use crate::{database::Database, config::Config};
+use warp::{Filter, Rejection};
+
+use crate::{
+    database::Database, config::Config, errors::AppError, middleware,
+    models::{Audience, Editor, EntityType}, repositories::Repositories, services::publishing,
+};
 
 pub mod health;
 pub mod classics;
 pub mod chapters;
 pub mod sentences;
+pub mod revisions;
+pub mod trash;
 pub mod characters;
 pub mod auth;
+pub mod metrics;
 
 #[derive(Clone)]
 pub struct AppState {
-    pub db_pool: sqlx::PgPool,
-    pub redis: Arc<Mutex<Connection>>,
+    pub repos: Repositories,
+    /// Connection pools, sampled for metrics; `None` with in-memory repositories
+    pub db: Option<Database>,
+    /// `None` disables response caching and sends Redis rate limiting to memory
+    pub redis: Option<Arc<Mutex<Connection>>>,
     pub config: Config,
 }
 
 impl AppState {
     pub fn new(db: Database, redis: Connection, config: Config) -> Self {
         Self {
-            db_pool: db.pool,
-            redis: Arc::new(Mutex::new(redis)),
+            repos: Repositories::postgres(db.clone()),
+            db: Some(db),
+            redis: Some(Arc::new(Mutex::new(redis))),
+            config,
+        }
+    }
+
+    /// State backed by an empty in-memory store and no Redis, for exercising
+    /// the routes without external services.
+    pub fn in_memory(config: Config) -> Self {
+        Self {
+            repos: Repositories::in_memory(),
+            db: None,
+            redis: None,
             config,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Who is calling, from an optional bearer token; decides whether unpublished
+/// content is shown. An invalid token is rejected rather than read as anonymous.
+pub fn with_audience(
+    state: AppState
+) -> impl Filter<Extract = (Audience,), Error = Rejection> + Clone {
+    middleware::auth::optional_auth(state.config.auth.jwt_secret.clone())
+        .and_then(move |user_id: Option<uuid::Uuid>| {
+            let state = state.clone();
+            async move {
+                publishing::audience(&state.repos, user_id)
+                    .await
+                    .map_err(|e| warp::reject::custom(AppError::from(e)))
+            }
+        })
+}
+
+/// The signed-in caller, who must be an active admin: 401 without a valid
+/// token, 403 for anyone else.
+pub fn with_admin(
+    state: AppState
+) -> impl Filter<Extract = (uuid::Uuid,), Error = Rejection> + Clone {
+    middleware::auth::with_auth(state.config.auth.jwt_secret.clone())
+        .and_then(move |user_id: uuid::Uuid| {
+            let state = state.clone();
+            async move {
+                match publishing::audience(&state.repos, Some(user_id)).await {
+                    Ok(Audience::Admin) => Ok(user_id),
+                    Ok(Audience::Public) => Err(warp::reject::custom(AppError::Forbidden)),
+                    Err(e) => Err(warp::reject::custom(AppError::from(e))),
+                }
+            }
+        })
+}
+
+/// The signed-in caller, who must be allowed to change content: 401 without a
+/// valid token, 403 for inactive users and children.
+pub fn with_editor(
+    state: AppState
+) -> impl Filter<Extract = (Editor,), Error = Rejection> + Clone {
+    middleware::auth::with_auth(state.config.auth.jwt_secret.clone())
+        .and_then(move |user_id: uuid::Uuid| {
+            let state = state.clone();
+            async move {
+                match publishing::editor(&state.repos, user_id).await {
+                    Ok(Some(editor)) => Ok(editor),
+                    Ok(None) => Err(warp::reject::custom(AppError::Forbidden)),
+                    Err(e) => Err(warp::reject::custom(AppError::from(e))),
+                }
+            }
+        })
+}
+
+/// A `classics`, `chapters` or `sentences` path segment, as the kind of entity it names.
+pub fn entity_type() -> impl Filter<Extract = (EntityType,), Error = Rejection> + Clone {
+    let kind = |segment: &'static str, entity_type: EntityType| {
+        warp::path(segment).map(move || entity_type)
+    };
+    kind("classics", EntityType::Classic)
+        .or(kind("chapters", EntityType::Chapter))
+        .unify()
+        .or(kind("sentences", EntityType::Sentence))
+        .unify()
+}
@@ -0,0 +1,175 @@
+use serde_json::Value;
+use uuid::Uuid;
+use warp::{Filter, Reply, Rejection, reply::json};
+
+use crate::{
+    errors::{AppError, ErrorResponse},
+    handlers::{self, AppState},
+    models::{Edit, EntityType, RestoreRevisionRequest, Revision, RevisionDiff, RevisionDiffQuery},
+    services::{cache, revisions},
+    utils::{api_response::{success_response, ApiResponse}, pagination},
+};
+
+pub fn routes(
+    state: AppState
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    // GET /api/{kind}/:id/revisions
+    let list = revisioned()
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(handlers::with_admin(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(list_revisions_handler);
+
+    // GET /api/{kind}/:id/revisions/diff?from=&to=
+    let diff = revisioned()
+        .and(warp::path("diff"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(pagination::query::<RevisionDiffQuery>())
+        .and(handlers::with_admin(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(diff_revisions_handler);
+
+    // POST /api/{kind}/:id/revisions/:version/restore
+    let restore = revisioned()
+        .and(warp::path::param::<i32>())
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(handlers::with_admin(state.clone()))
+        .and(with_state(state))
+        .and_then(restore_revision_handler);
+
+    list.or(diff).or(restore)
+}
+
+/// `/{kind}/:id/revisions`, for each kind of entity that keeps revisions.
+fn revisioned() -> impl Filter<Extract = (EntityType, Uuid), Error = Rejection> + Clone {
+    handlers::entity_type()
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("revisions"))
+}
+
+fn with_state(
+    state: AppState
+) -> impl Filter<Extract = (AppState,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || state.clone())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/{kind}/{id}/revisions",
+    tag = "revisions",
+    params(
+        ("kind" = String, Path, description = "classics、chapters 或 sentences"),
+        ("id" = Uuid, Path, description = "经典、章节或句子 ID"),
+    ),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "修订记录，按版本从旧到新；内容删除后仍可查询", body = ApiResponse<Vec<Revision>>),
+        (status = 401, description = "未登录", body = ErrorResponse),
+        (status = 403, description = "修订记录含未发布的内容，查看需要管理员", body = ErrorResponse),
+        (status = 404, description = "内容不存在且没有修订记录", body = ErrorResponse),
+    )
+)]
+async fn list_revisions_handler(
+    entity_type: EntityType,
+    id: Uuid,
+    _admin_id: Uuid,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    match revisions::list(&state.repos, entity_type, id).await {
+        Ok(Some(revisions)) => Ok(json(&success_response(revisions))),
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("获取修订记录失败 {}: {}", id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/{kind}/{id}/revisions/diff",
+    tag = "revisions",
+    params(
+        ("kind" = String, Path, description = "classics、chapters 或 sentences"),
+        ("id" = Uuid, Path, description = "经典、章节或句子 ID"),
+        RevisionDiffQuery,
+    ),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "两个版本之间变化的字段", body = ApiResponse<RevisionDiff>),
+        (status = 400, description = "版本不存在", body = ErrorResponse),
+        (status = 401, description = "未登录", body = ErrorResponse),
+        (status = 403, description = "修订记录含未发布的内容，查看需要管理员", body = ErrorResponse),
+        (status = 404, description = "内容不存在且没有修订记录", body = ErrorResponse),
+    )
+)]
+async fn diff_revisions_handler(
+    entity_type: EntityType,
+    id: Uuid,
+    query: RevisionDiffQuery,
+    _admin_id: Uuid,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    let revisions = match revisions::list(&state.repos, entity_type, id).await {
+        Ok(Some(revisions)) => revisions,
+        Ok(None) => return Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("获取修订记录失败 {}: {}", id, e);
+            return Err(warp::reject::custom(AppError::from(e)));
+        }
+    };
+    match revisions::diff(&revisions, query.from, query.to) {
+        Ok(diff) => Ok(json(&success_response(diff))),
+        Err(e) => Err(warp::reject::custom(AppError::from(e))),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/{kind}/{id}/revisions/{version}/restore",
+    tag = "revisions",
+    params(
+        ("kind" = String, Path, description = "classics、chapters 或 sentences"),
+        ("id" = Uuid, Path, description = "经典、章节或句子 ID"),
+        ("version" = i32, Path, description = "要恢复的版本，0 为首次修订之前"),
+    ),
+    request_body = RestoreRevisionRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "已恢复标题与正文等内容字段（编号和标识符不变），并记录一次恢复修订", body = ApiResponse<Object>),
+        (status = 400, description = "版本不存在，或该版本中内容已删除", body = ErrorResponse),
+        (status = 401, description = "未登录", body = ErrorResponse),
+        (status = 403, description = "恢复需要管理员", body = ErrorResponse),
+        (status = 404, description = "内容已不存在", body = ErrorResponse),
+    )
+)]
+async fn restore_revision_handler(
+    entity_type: EntityType,
+    id: Uuid,
+    version: i32,
+    req: RestoreRevisionRequest,
+    editor_id: Uuid,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    let edit = Edit { editor_id: Some(editor_id), comment: req.comment };
+    match revisions::restore(&state.repos, entity_type, id, version, &edit).await {
+        Ok(Some(entity)) => {
+            if entity_type == EntityType::Classic {
+                if let Some(redis) = &state.redis {
+                    cache::invalidate_prefix(redis, cache::CLASSICS_LIST_PREFIX).await;
+                }
+            }
+            Ok(json(&success_response::<Value>(entity)))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("恢复修订失败 {} 版本 {}: {}", id, version, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
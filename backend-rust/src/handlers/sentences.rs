@@ -1,9 +1,14 @@
 use warp::{Filter, Reply, Rejection, reply::json};
 use uuid::Uuid;
-use crate::handlers::AppState;
-use crate::models::sentence::{Sentence, CreateSentenceRequest, UpdateSentenceRequest, SentenceResponse};
-use crate::utils::api_response::{ApiResponse, success_response, error_response};
-use crate::errors::AppError;
+use crate::handlers::{with_audience, with_editor, AppState};
+use crate::models::sentence::{Sentence, CreateSentenceRequest, UpdateSentenceRequest, SentenceContext, SentenceListQuery, SentenceResponse};
+use crate::models::editing::{InsertSentenceRequest, MoveSentenceRequest, RenumberRequest, SplitSentenceRequest};
+use crate::models::publishing::{Audience, Editor};
+use crate::services::{publishing, sentence_context};
+use crate::utils::api_response::{success_response, ApiResponse};
+use crate::utils::conditional::{self, ConditionalHeaders, IfMatch, Stale, Validators};
+use crate::utils::{merge_patch, pagination};
+use crate::errors::{AppError, ErrorResponse};
 
 pub fn routes(
     state: AppState
@@ -11,54 +16,136 @@ pub fn routes(
     let sentences_base = warp::path("sentences");
     
     let get_all_sentences = sentences_base
-        .and(warp::get())
         .and(warp::path::end())
+        .and(warp::get())
+        .and(pagination::query::<SentenceListQuery>())
+        .and(conditional::headers())
+        .and(with_audience(state.clone()))
         .and(with_state(state.clone()))
         .and_then(get_all_sentences_handler);
 
     let get_sentence_by_id = sentences_base
-        .and(warp::get())
         .and(warp::path::param::<Uuid>())
         .and(warp::path::end())
+        .and(warp::get())
+        .and(conditional::headers())
+        .and(with_audience(state.clone()))
         .and(with_state(state.clone()))
         .and_then(get_sentence_by_id_handler);
 
+    let get_sentence_context = sentences_base
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("context"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(conditional::headers())
+        .and(with_audience(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(get_sentence_context_handler);
+
     let get_sentences_by_chapter = warp::path("chapters")
         .and(warp::path::param::<Uuid>())
         .and(warp::path("sentences"))
-        .and(warp::get())
         .and(warp::path::end())
+        .and(warp::get())
+        .and(pagination::query::<SentenceListQuery>())
+        .and(conditional::headers())
+        .and(with_audience(state.clone()))
         .and(with_state(state.clone()))
         .and_then(get_sentences_by_chapter_handler);
 
     let create_sentence = sentences_base
-        .and(warp::post())
         .and(warp::path::end())
+        .and(warp::post())
         .and(warp::body::json())
+        .and(with_editor(state.clone()))
         .and(with_state(state.clone()))
         .and_then(create_sentence_handler);
 
     let update_sentence = sentences_base
-        .and(warp::put())
         .and(warp::path::param::<Uuid>())
         .and(warp::path::end())
-        .and(warp::body::json())
+        .and(warp::put().or(warp::patch()).unify())
+        .and(merge_patch::body())
+        .and(conditional::if_match())
+        .and(with_editor(state.clone()))
         .and(with_state(state.clone()))
         .and_then(update_sentence_handler);
 
     let delete_sentence = sentences_base
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path::end())
         .and(warp::delete())
+        .and(with_editor(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(delete_sentence_handler);
+
+    let insert_sentence = warp::path("chapters")
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("sentences"))
+        .and(warp::path("insert"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_editor(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(insert_sentence_handler);
+
+    let renumber_sentences = warp::path("chapters")
         .and(warp::path::param::<Uuid>())
+        .and(warp::path("sentences"))
+        .and(warp::path("renumber"))
         .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_editor(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(renumber_sentences_handler);
+
+    let move_sentence = sentences_base
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("move"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_editor(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(move_sentence_handler);
+
+    let split_sentence = sentences_base
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("split"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_editor(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(split_sentence_handler);
+
+    let merge_sentence = sentences_base
+        .and(warp::path::param::<Uuid>())
+        .and(warp::path("merge"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_editor(state.clone()))
         .and(with_state(state))
-        .and_then(delete_sentence_handler);
+        .and_then(merge_sentence_handler);
 
     get_all_sentences
         .or(get_sentence_by_id)
+        .or(get_sentence_context)
         .or(get_sentences_by_chapter)
         .or(create_sentence)
         .or(update_sentence)
         .or(delete_sentence)
+        .or(insert_sentence)
+        .or(renumber_sentences)
+        .or(move_sentence)
+        .or(split_sentence)
+        .or(merge_sentence)
+        // Boxed so the route futures live on the heap; unboxed they overflow
+        // the stack of a debug build
+        .boxed()
 }
 
 fn with_state(
@@ -67,61 +154,156 @@ fn with_state(
     warp::any().map(move || state.clone())
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/sentences",
+    tag = "sentences",
+    params(SentenceListQuery, ConditionalHeaders),
+    responses(
+        (status = 200, description = "One page of sentences", body = ApiResponse<Vec<SentenceResponse>>),
+        (status = 304, description = "Cached copy is still current"),
+        (status = 400, description = "Invalid pagination, sort or filter", body = ErrorResponse),
+    )
+)]
 async fn get_all_sentences_handler(
+    query: SentenceListQuery,
+    conditional: ConditionalHeaders,
+    audience: Audience,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Sentence::find_all(&state.db_pool).await {
+    let (mut filter, page) = query.into_parts().map_err(|e| warp::reject::custom(AppError::Validation(e)))?;
+    filter.published_only = audience.is_public();
+
+    match state.repos.sentences.list(&filter, &page).await {
         Ok(sentences) => {
-            let responses: Vec<SentenceResponse> = sentences.into_iter().map(|s| s.into()).collect();
-            Ok(json(&success_response(responses)))
+            let validators = Validators::from_page(&sentences, |s| (s.id, s.updated_at));
+            Ok(conditional::reply_page(sentences.map(SentenceResponse::from), validators, &conditional))
         }
         Err(e) => {
             tracing::error!("Failed to fetch sentences: {}", e);
-            Err(warp::reject::custom(AppError::DatabaseError))
+            Err(warp::reject::custom(AppError::from(e)))
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/sentences/{id}",
+    tag = "sentences",
+    params(("id" = Uuid, Path, description = "Sentence id"), ConditionalHeaders),
+    responses(
+        (status = 200, description = "The sentence", body = ApiResponse<SentenceResponse>),
+        (status = 304, description = "Cached copy is still current"),
+        (status = 404, description = "No such sentence", body = ErrorResponse),
+    )
+)]
 async fn get_sentence_by_id_handler(
     id: Uuid,
+    conditional: ConditionalHeaders,
+    audience: Audience,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Sentence::find_by_id(&state.db_pool, id).await {
+    match publishing::find_sentence(&state.repos, id, audience).await {
         Ok(Some(sentence)) => {
+            let validators = Validators::single(sentence.id, sentence.updated_at);
             let response: SentenceResponse = sentence.into();
-            Ok(json(&success_response(response)))
+            Ok(conditional::reply(response, validators, &conditional))
         }
         Ok(None) => {
-            Err(warp::reject::custom(AppError::NotFound("Sentence not found".to_string())))
+            Err(warp::reject::custom(AppError::NotFound))
         }
         Err(e) => {
             tracing::error!("Failed to fetch sentence {}: {}", id, e);
-            Err(warp::reject::custom(AppError::DatabaseError))
+            Err(warp::reject::custom(AppError::from(e)))
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/sentences/{id}/context",
+    tag = "sentences",
+    params(("id" = Uuid, Path, description = "Sentence id"), ConditionalHeaders),
+    responses(
+        (status = 200, description = "The sentence with its chapter, classic and neighbours in reading order", body = ApiResponse<SentenceContext>),
+        (status = 304, description = "Cached copy is still current"),
+        (status = 404, description = "No such sentence", body = ErrorResponse),
+    )
+)]
+async fn get_sentence_context_handler(
+    id: Uuid,
+    conditional: ConditionalHeaders,
+    audience: Audience,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    match sentence_context::load(&state.repos, id, audience).await {
+        Ok(Some((context, validators))) => Ok(conditional::reply(context, validators, &conditional)),
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to fetch context of sentence {}: {}", id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/chapters/{chapter_id}/sentences",
+    tag = "sentences",
+    params(("chapter_id" = Uuid, Path, description = "Chapter id"), SentenceListQuery, ConditionalHeaders),
+    responses(
+        (status = 200, description = "Sentences of the chapter, in order", body = ApiResponse<Vec<SentenceResponse>>),
+        (status = 304, description = "Cached copy is still current"),
+        (status = 400, description = "Invalid pagination or sort", body = ErrorResponse),
+    )
+)]
 async fn get_sentences_by_chapter_handler(
     chapter_id: Uuid,
+    mut query: SentenceListQuery,
+    conditional: ConditionalHeaders,
+    audience: Audience,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Sentence::find_by_chapter_id(&state.db_pool, chapter_id).await {
+    // The path decides the chapter, whatever the query string says
+    query.chapter_id = Some(chapter_id);
+    let (mut filter, page) = query.into_parts().map_err(|e| warp::reject::custom(AppError::Validation(e)))?;
+    filter.published_only = audience.is_public();
+
+    match state.repos.sentences.list(&filter, &page).await {
         Ok(sentences) => {
-            let responses: Vec<SentenceResponse> = sentences.into_iter().map(|s| s.into()).collect();
-            Ok(json(&success_response(responses)))
+            let validators = Validators::from_page(&sentences, |s| (s.id, s.updated_at));
+            Ok(conditional::reply_page(sentences.map(SentenceResponse::from), validators, &conditional))
         }
         Err(e) => {
             tracing::error!("Failed to fetch sentences for chapter {}: {}", chapter_id, e);
-            Err(warp::reject::custom(AppError::DatabaseError))
+            Err(warp::reject::custom(AppError::from(e)))
         }
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/sentences",
+    tag = "sentences",
+    request_body = CreateSentenceRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "Created", body = ApiResponse<SentenceResponse>),
+        (status = 400, description = "Number already taken", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such chapter", body = ErrorResponse),
+    )
+)]
 async fn create_sentence_handler(
     req: CreateSentenceRequest,
+    editor: Editor,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Sentence::create(&state.db_pool, req).await {
+    publishing::check_chapter_editable(&state.repos, req.chapter_id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    match state.repos.sentences.create(req, &editor.edit(None)).await {
         Ok(sentence) => {
             let response: SentenceResponse = sentence.into();
             Ok(warp::reply::with_status(
@@ -132,48 +314,294 @@ async fn create_sentence_handler(
         Err(e) => {
             tracing::error!("Failed to create sentence: {}", e);
             if e.to_string().contains("duplicate key") {
-                Err(warp::reject::custom(AppError::BadRequest("Sentence number already exists for this chapter".to_string())))
+                Err(warp::reject::custom(AppError::Validation("Sentence number already exists for this chapter".to_string())))
             } else {
-                Err(warp::reject::custom(AppError::DatabaseError))
+                Err(warp::reject::custom(AppError::from(e)))
             }
         }
     }
 }
 
+#[utoipa::path(
+    method(put, patch),
+    path = "/api/sentences/{id}",
+    tag = "sentences",
+    params(("id" = Uuid, Path, description = "Sentence id"), IfMatch),
+    request_body(content((UpdateSentenceRequest = "application/merge-patch+json"), (UpdateSentenceRequest = "application/json"))),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Updated; the ETag is the new version's. No `data` unless the caller is an admin", body = ApiResponse<SentenceResponse>),
+        (status = 400, description = "`null` for `number` or `text`", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such sentence", body = ErrorResponse),
+        (status = 412, description = "Stale `If-Match`; the ETag, and `data` for admins, are the current version", body = ApiResponse<SentenceResponse>),
+    )
+)]
 async fn update_sentence_handler(
     id: Uuid,
     req: UpdateSentenceRequest,
+    if_match: IfMatch,
+    editor: Editor,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Sentence::update(&state.db_pool, id, req).await {
+    publishing::check_sentence_editable(&state.repos, id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    let readable = !editor.audience.is_public();
+    let edit = editor.edit(req.comment.clone());
+    match state.repos.sentences.update(id, req, &if_match, &edit).await {
         Ok(Some(sentence)) => {
-            let response: SentenceResponse = sentence.into();
-            Ok(json(&success_response(response)))
+            let validators = Validators::single(sentence.id, sentence.updated_at);
+            let response = readable.then(|| SentenceResponse::from(sentence));
+            Ok(conditional::reply_updated(response, validators))
         }
         Ok(None) => {
-            Err(warp::reject::custom(AppError::NotFound("Sentence not found".to_string())))
-        }
-        Err(e) => {
-            tracing::error!("Failed to update sentence {}: {}", id, e);
-            Err(warp::reject::custom(AppError::DatabaseError))
+            Err(warp::reject::custom(AppError::NotFound))
         }
+        Err(e) => match e.downcast::<Stale<Sentence>>() {
+            Ok(Stale(current)) => {
+                let validators = Validators::single(current.id, current.updated_at);
+                let response = readable.then(|| SentenceResponse::from(current));
+                Ok(conditional::reply_stale(response, validators, "Sentence was changed since it was read"))
+            }
+            Err(e) => {
+                tracing::error!("Failed to update sentence {}: {}", id, e);
+                Err(warp::reject::custom(AppError::from(e)))
+            }
+        },
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/sentences/{id}",
+    tag = "sentences",
+    params(("id" = Uuid, Path, description = "Sentence id")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Moved to the trash", body = ApiResponse<String>),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such sentence", body = ErrorResponse),
+    )
+)]
 async fn delete_sentence_handler(
     id: Uuid,
+    editor: Editor,
     state: AppState
 ) -> Result<impl Reply, Rejection> {
-    match Sentence::delete(&state.db_pool, id).await {
+    publishing::check_sentence_editable(&state.repos, id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    let edit = editor.edit(None);
+    match state.repos.sentences.delete(id, &edit).await {
         Ok(true) => {
             Ok(json(&success_response("Sentence deleted successfully")))
         }
         Ok(false) => {
-            Err(warp::reject::custom(AppError::NotFound("Sentence not found".to_string())))
+            Err(warp::reject::custom(AppError::NotFound))
         }
         Err(e) => {
             tracing::error!("Failed to delete sentence {}: {}", id, e);
-            Err(warp::reject::custom(AppError::DatabaseError))
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+#[utoipa::path(
+    post,
+    path = "/api/chapters/{chapter_id}/sentences/insert",
+    tag = "sentences",
+    params(("chapter_id" = Uuid, Path, description = "Chapter id")),
+    request_body = InsertSentenceRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "Inserted; later sentences moved down by one", body = ApiResponse<SentenceResponse>),
+        (status = 400, description = "Position out of range", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such chapter", body = ErrorResponse),
+    )
+)]
+async fn insert_sentence_handler(
+    chapter_id: Uuid,
+    req: InsertSentenceRequest,
+    editor: Editor,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    publishing::check_chapter_editable(&state.repos, chapter_id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    match state.repos.sentences.insert_at(chapter_id, req, &editor.edit(None)).await {
+        Ok(Some(sentence)) => {
+            let response: SentenceResponse = sentence.into();
+            Ok(warp::reply::with_status(
+                json(&success_response(response)),
+                warp::http::StatusCode::CREATED
+            ))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to insert sentence into chapter {}: {}", chapter_id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/chapters/{chapter_id}/sentences/renumber",
+    tag = "sentences",
+    params(("chapter_id" = Uuid, Path, description = "Chapter id")),
+    request_body = RenumberRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Sentences of the chapter, numbered from 1 in the new order", body = ApiResponse<Vec<SentenceResponse>>),
+        (status = 400, description = "Order does not list every sentence once", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such chapter", body = ErrorResponse),
+    )
+)]
+async fn renumber_sentences_handler(
+    chapter_id: Uuid,
+    req: RenumberRequest,
+    editor: Editor,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    publishing::check_chapter_editable(&state.repos, chapter_id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    match state.repos.sentences.renumber(chapter_id, req.order, &editor.edit(None)).await {
+        Ok(Some(sentences)) => {
+            let response: Vec<SentenceResponse> = sentences.into_iter().map(SentenceResponse::from).collect();
+            Ok(json(&success_response(response)))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to renumber sentences of chapter {}: {}", chapter_id, e);
+            Err(warp::reject::custom(AppError::from(e)))
         }
     }
-}
\ No newline at end of file
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/sentences/{id}/move",
+    tag = "sentences",
+    params(("id" = Uuid, Path, description = "Sentence id")),
+    request_body = MoveSentenceRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Moved; both chapters renumbered", body = ApiResponse<SentenceResponse>),
+        (status = 400, description = "Position out of range or no such target chapter", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such sentence", body = ErrorResponse),
+    )
+)]
+async fn move_sentence_handler(
+    id: Uuid,
+    req: MoveSentenceRequest,
+    editor: Editor,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    publishing::check_sentence_editable(&state.repos, id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    if let Some(chapter_id) = req.chapter_id {
+        match publishing::check_chapter_editable(&state.repos, chapter_id, &editor).await {
+            // A missing target chapter is reported by the move itself
+            Err(e) if !matches!(e.downcast_ref::<AppError>(), Some(AppError::NotFound)) => {
+                return Err(warp::reject::custom(AppError::from(e)));
+            }
+            _ => {}
+        }
+    }
+    let edit = editor.edit(None);
+    match state.repos.sentences.move_to(id, req, &edit).await {
+        Ok(Some(sentence)) => {
+            let response: SentenceResponse = sentence.into();
+            Ok(json(&success_response(response)))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to move sentence {}: {}", id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/sentences/{id}/split",
+    tag = "sentences",
+    params(("id" = Uuid, Path, description = "Sentence id")),
+    request_body = SplitSentenceRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The two parts, in order; both are left without pinyin and translation", body = ApiResponse<Vec<SentenceResponse>>),
+        (status = 400, description = "Offset would leave a part empty", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such sentence", body = ErrorResponse),
+    )
+)]
+async fn split_sentence_handler(
+    id: Uuid,
+    req: SplitSentenceRequest,
+    editor: Editor,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    publishing::check_sentence_editable(&state.repos, id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    let edit = editor.edit(None);
+    match state.repos.sentences.split(id, req.offset, &edit).await {
+        Ok(Some((first, second))) => {
+            let response: Vec<SentenceResponse> = vec![first.into(), second.into()];
+            Ok(json(&success_response(response)))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to split sentence {}: {}", id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/sentences/{id}/merge",
+    tag = "sentences",
+    params(("id" = Uuid, Path, description = "Sentence id")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Merged with the following sentence of its chapter", body = ApiResponse<SentenceResponse>),
+        (status = 400, description = "Last sentence of its chapter", body = ErrorResponse),
+        (status = 401, description = "Not signed in", body = ErrorResponse),
+        (status = 403, description = "Not allowed to edit, or the classic is published and the caller is not an admin", body = ErrorResponse),
+        (status = 404, description = "No such sentence", body = ErrorResponse),
+    )
+)]
+async fn merge_sentence_handler(
+    id: Uuid,
+    editor: Editor,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    publishing::check_sentence_editable(&state.repos, id, &editor)
+        .await
+        .map_err(|e| warp::reject::custom(AppError::from(e)))?;
+    let edit = editor.edit(None);
+    match state.repos.sentences.merge_with_next(id, &edit).await {
+        Ok(Some(sentence)) => {
+            let response: SentenceResponse = sentence.into();
+            Ok(json(&success_response(response)))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("Failed to merge sentence {}: {}", id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
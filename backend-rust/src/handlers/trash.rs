@@ -0,0 +1,162 @@
+use serde_json::Value;
+use uuid::Uuid;
+use warp::{Filter, Reply, Rejection, reply::json};
+
+use crate::{
+    errors::{AppError, ErrorResponse},
+    handlers::{self, AppState},
+    models::{Edit, EntityType, RestoreRevisionRequest, TrashItem, TrashQuery},
+    services::{cache, trash},
+    utils::{api_response::{success_response, ApiResponse, MessageResponse}, pagination},
+};
+
+pub fn routes(
+    state: AppState
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    // GET /api/trash?entity_type=
+    let list = warp::path("trash")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(pagination::query::<TrashQuery>())
+        .and(handlers::with_admin(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(list_trash_handler);
+
+    // POST /api/trash/{kind}/:id/restore
+    let restore = trashed()
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(handlers::with_admin(state.clone()))
+        .and(with_state(state.clone()))
+        .and_then(restore_handler);
+
+    // DELETE /api/trash/{kind}/:id
+    let purge = trashed()
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(handlers::with_admin(state.clone()))
+        .and(with_state(state))
+        .and_then(purge_handler);
+
+    list.or(restore).or(purge)
+}
+
+/// `/trash/{kind}/:id`
+fn trashed() -> impl Filter<Extract = (EntityType, Uuid), Error = Rejection> + Clone {
+    warp::path("trash")
+        .and(handlers::entity_type())
+        .and(warp::path::param::<Uuid>())
+}
+
+fn with_state(
+    state: AppState
+) -> impl Filter<Extract = (AppState,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || state.clone())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/trash",
+    tag = "trash",
+    params(TrashQuery),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "回收站中单独删除的内容，最近删除的在前；随经典或章节一并删除的不单独列出", body = ApiResponse<Vec<TrashItem>>),
+        (status = 400, description = "查询参数无效", body = ErrorResponse),
+        (status = 401, description = "未登录", body = ErrorResponse),
+        (status = 403, description = "查看回收站需要管理员", body = ErrorResponse),
+    )
+)]
+async fn list_trash_handler(
+    query: TrashQuery,
+    _admin_id: Uuid,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    match state.repos.trash.list(query.entity_type).await {
+        Ok(items) => Ok(json(&success_response(items))),
+        Err(e) => {
+            tracing::error!("获取回收站失败: {}", e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/trash/{kind}/{id}/restore",
+    tag = "trash",
+    params(
+        ("kind" = String, Path, description = "classics、chapters 或 sentences"),
+        ("id" = Uuid, Path, description = "经典、章节或句子 ID"),
+    ),
+    request_body = RestoreRevisionRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "已恢复，随之删除的章节和句子一并恢复，并记录一次恢复修订", body = ApiResponse<Object>),
+        (status = 400, description = "所属经典或章节仍在回收站中，或编号已被占用", body = ErrorResponse),
+        (status = 401, description = "未登录", body = ErrorResponse),
+        (status = 403, description = "恢复需要管理员", body = ErrorResponse),
+        (status = 404, description = "回收站中没有该内容", body = ErrorResponse),
+    )
+)]
+async fn restore_handler(
+    entity_type: EntityType,
+    id: Uuid,
+    req: RestoreRevisionRequest,
+    editor_id: Uuid,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    let edit = Edit { editor_id: Some(editor_id), comment: req.comment };
+    match trash::restore(&state.repos, entity_type, id, &edit).await {
+        Ok(Some(entity)) => {
+            if entity_type == EntityType::Classic {
+                if let Some(redis) = &state.redis {
+                    cache::invalidate_prefix(redis, cache::CLASSICS_LIST_PREFIX).await;
+                }
+            }
+            Ok(json(&success_response::<Value>(entity)))
+        }
+        Ok(None) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("从回收站恢复失败 {}: {}", id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/trash/{kind}/{id}",
+    tag = "trash",
+    params(
+        ("kind" = String, Path, description = "classics、chapters 或 sentences"),
+        ("id" = Uuid, Path, description = "经典、章节或句子 ID"),
+    ),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "已彻底删除，连同其下的章节、句子和相关学习进度", body = ApiResponse<MessageResponse>),
+        (status = 401, description = "未登录", body = ErrorResponse),
+        (status = 403, description = "彻底删除需要管理员", body = ErrorResponse),
+        (status = 404, description = "回收站中没有该内容", body = ErrorResponse),
+    )
+)]
+async fn purge_handler(
+    entity_type: EntityType,
+    id: Uuid,
+    user_id: Uuid,
+    state: AppState
+) -> Result<impl Reply, Rejection> {
+    match trash::purge(&state.repos, entity_type, id).await {
+        Ok(true) => {
+            tracing::info!("用户 {} 彻底删除 {:?} {}", user_id, entity_type, id);
+            Ok(json(&success_response(MessageResponse::new("已彻底删除"))))
+        }
+        Ok(false) => Err(warp::reject::custom(AppError::NotFound)),
+        Err(e) => {
+            tracing::error!("彻底删除失败 {}: {}", id, e);
+            Err(warp::reject::custom(AppError::from(e)))
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! 小小读书郎后端 — modules shared by the API server (`server`) and the
+//! operations CLI (`admin`).
+
+pub mod config;
+pub mod database;
+pub mod models;
+pub mod handlers;
+pub mod openapi;
+pub mod repositories;
+pub mod routes;
+pub mod middleware;
+pub mod services;
+pub mod errors;
+pub mod metrics;
+pub mod schema;
+pub mod shutdown;
+pub mod telemetry;
+pub mod tls;
+pub mod utils;
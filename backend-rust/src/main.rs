@@ -1,104 +1,100 @@
 use std::net::SocketAddr;
-use warp::Filter;
-use tracing::{info, error};
+use std::time::Duration;
+use tracing::info;
 use dotenv::dotenv;
 
-mod config;
-mod database;
-mod models;
-mod handlers;
-mod middleware;
-mod services;
-mod errors;
-mod utils;
-
-use config::Config;
-use database::Database;
-use errors::AppError;
+use xiaoxiao_dushulang_backend::{
+    config::Config,
+    database::Database,
+    handlers, routes, schema, shutdown, telemetry, tls,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load environment variables
     dotenv().ok();
 
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+    // Load configuration
+    let config = Config::load()?;
 
-    info!("启动小小读书郎 Rust 后端服务器...");
+    // Initialize tracing (and the optional OTLP exporter)
+    telemetry::init(&config)?;
 
-    // Load configuration
-    let config = Config::from_env()?;
+    info!("启动小小读书郎 Rust 后端服务器...");
     info!("配置加载完成");
 
     // Initialize database
-    let db = Database::connect(&config.database_url).await?;
+    let db = Database::connect(&config.database).await?;
+    db.spawn_replica_health_checks(Duration::from_secs(config.database.replica_health_check_secs));
     info!("数据库连接建立成功");
 
     // Run migrations
     db.migrate().await?;
     info!("数据库迁移完成");
 
+    // Refuse to serve if the schema doesn't match what the models query
+    schema::check(db.writer()).await?;
+    info!("数据库结构检查通过");
+
     // Initialize Redis cache
-    let redis_client = redis::Client::open(config.redis_url.clone())?;
+    let redis_client = redis::Client::open(config.redis.url.clone())?;
     let redis_conn = redis_client.get_async_connection().await?;
     info!("Redis 缓存连接建立成功");
 
     // Create shared app state
     let app_state = handlers::AppState::new(db.clone(), redis_conn, config.clone());
+    let redis = app_state.redis.clone();
 
     // Build routes
-    let routes = build_routes(app_state).await;
+    let routes = routes::build(app_state);
 
     // Parse bind address
-    let addr: SocketAddr = format!("{}:{}", config.host, config.port)
+    let addr: SocketAddr = format!("{}:{}", config.server.host, config.server.port)
         .parse()
         .expect("有效的地址格式");
 
-    info!("服务器启动在 http://{}", addr);
-    info!("小小读书郎 Rust API 服务器启动成功！");
-    info!("API 地址: http://{}/api", addr);
-    info!("健康检查: http://{}/health", addr);
-
-    // Start the server
-    warp::serve(routes)
-        .run(addr)
-        .await;
+    let shutdown = shutdown::Shutdown::on_signal();
+    let drain_deadline = Duration::from_secs(config.server.shutdown_timeout_secs);
+
+    // Serve until SIGTERM / Ctrl-C, then drain in-flight requests
+    let remaining = if config.tls.enabled {
+        let tls_server = tls::TlsServer::from_config(&config.tls)?;
+        let (addr, server) = tls_server.bind(addr, routes, shutdown.clone()).await?;
+
+        if let Some(port) = config.tls.redirect_http_port {
+            let (redirect_addr, redirect) = warp::serve(tls::redirect_to_https(addr.port()))
+                .try_bind_with_graceful_shutdown(SocketAddr::new(addr.ip(), port), shutdown.clone().requested())?;
+            tokio::spawn(redirect);
+            info!("HTTP 重定向: http://{} -> https", redirect_addr);
+        }
+
+        log_listening("https", addr);
+        shutdown.drain(server, drain_deadline).await
+    } else {
+        let (addr, server) = warp::serve(routes)
+            .try_bind_with_graceful_shutdown(addr, shutdown.clone().requested())?;
+
+        log_listening("http", addr);
+        shutdown.drain(server, drain_deadline).await
+    };
+
+    // Release connections and flush spans, within whatever the drain left of the deadline
+    db.close_within(remaining).await;
+    if let Some(redis) = redis {
+        if let Err(e) = redis::cmd("QUIT").query_async::<_, ()>(&mut *redis.lock().await).await {
+            tracing::warn!("关闭 Redis 连接失败: {}", e);
+        }
+    }
+    info!("服务器已关闭");
+    telemetry::shutdown();
 
     Ok(())
 }
 
-async fn build_routes(
-    state: handlers::AppState
-) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
-    // Health check route
-    let health = warp::path("health")
-        .and(warp::get())
-        .and_then(handlers::health::health_check);
-
-    // API routes
-    let api_routes = warp::path("api")
-        .and(
-            // Classics routes
-            handlers::classics::routes(state.clone())
-                // Chapters routes
-                .or(handlers::chapters::routes(state.clone()))
-                // Sentences routes
-                .or(handlers::sentences::routes(state.clone()))
-                // Auth routes
-                .or(handlers::auth::routes(state.clone()))
-                // Character routes
-                .or(handlers::characters::routes(state.clone()))
-        );
-
-    // Combine all routes
-    let routes = health
-        .or(api_routes)
-        .with(middleware::cors::cors())
-        .with(middleware::logging::log())
-        .with(middleware::security::security_headers())
-        .recover(errors::handle_rejection);
-
-    routes
-}
\ No newline at end of file
+fn log_listening(scheme: &str, addr: SocketAddr) {
+    info!("服务器启动在 {}://{}", scheme, addr);
+    info!("小小读书郎 Rust API 服务器启动成功！");
+    info!("API 地址: {}://{}/api", scheme, addr);
+    info!("健康检查: {}://{}/health", scheme, addr);
+    info!("监控指标: {}://{}/metrics", scheme, addr);
+}
@@ -0,0 +1,181 @@
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::database::Database;
+
+const NAMESPACE: &str = "xiaoxiao";
+
+pub static REGISTRY: Lazy<Registry> = Lazy::new(|| {
+    let registry = Registry::new_custom(Some(NAMESPACE.to_string()), None)
+        .expect("有效的指标命名空间");
+
+    registry.register(Box::new(HTTP_REQUESTS_TOTAL.clone())).expect("注册指标失败");
+    registry.register(Box::new(HTTP_REQUEST_DURATION_SECONDS.clone())).expect("注册指标失败");
+    registry.register(Box::new(DB_POOL_CONNECTIONS.clone())).expect("注册指标失败");
+    registry.register(Box::new(DB_POOL_MAX_CONNECTIONS.clone())).expect("注册指标失败");
+    registry.register(Box::new(CACHE_REQUESTS_TOTAL.clone())).expect("注册指标失败");
+    registry.register(Box::new(USERS_REGISTERED_TOTAL.clone())).expect("注册指标失败");
+    registry.register(Box::new(USER_LOGINS_TOTAL.clone())).expect("注册指标失败");
+    registry.register(Box::new(REVIEWS_SUBMITTED_TOTAL.clone())).expect("注册指标失败");
+    registry.register(Box::new(SESSIONS_COMPLETED_TOTAL.clone())).expect("注册指标失败");
+
+    registry
+});
+
+// HTTP
+pub static HTTP_REQUESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new("http_requests_total", "Total HTTP requests by method, route and status"),
+        &["method", "route", "status"],
+    )
+    .expect("有效的指标定义")
+});
+
+pub static HTTP_REQUEST_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    HistogramVec::new(
+        HistogramOpts::new(
+            "http_request_duration_seconds",
+            "HTTP request latency by method, route and status",
+        )
+        .buckets(vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+        &["method", "route", "status"],
+    )
+    .expect("有效的指标定义")
+});
+
+// Database pool
+pub static DB_POOL_CONNECTIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        Opts::new("db_pool_connections", "sqlx pool connections by pool (primary / replica-N) and state (idle / in_use)"),
+        &["pool", "state"],
+    )
+    .expect("有效的指标定义")
+});
+
+pub static DB_POOL_MAX_CONNECTIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        Opts::new("db_pool_max_connections", "Configured sqlx pool size"),
+        &["pool"],
+    )
+    .expect("有效的指标定义")
+});
+
+// Redis cache
+pub static CACHE_REQUESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new("cache_requests_total", "Redis cache lookups by namespace and result (hit / miss / error)"),
+        &["cache", "result"],
+    )
+    .expect("有效的指标定义")
+});
+
+// Domain
+pub static USERS_REGISTERED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    IntCounter::new("users_registered_total", "Users registered")
+        .expect("有效的指标定义")
+});
+
+pub static USER_LOGINS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new("user_logins_total", "Login attempts by result (success / failure)"),
+        &["result"],
+    )
+    .expect("有效的指标定义")
+});
+
+pub static REVIEWS_SUBMITTED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    IntCounter::new("reviews_submitted_total", "Character reviews submitted")
+        .expect("有效的指标定义")
+});
+
+pub static SESSIONS_COMPLETED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new("sessions_completed_total", "Practice sessions completed by session type"),
+        &["session_type"],
+    )
+    .expect("有效的指标定义")
+});
+
+/// Session types of `practice_sessions.session_type`; anything else is counted as "other".
+const SESSION_TYPES: &[&str] = &["reading", "writing", "recognition", "comprehension"];
+
+pub fn observe_http_request(method: &str, path: &str, status: u16, elapsed: Duration) {
+    let route = route_label(path, status);
+    let status = status.to_string();
+    let labels = [method, route.as_str(), status.as_str()];
+
+    HTTP_REQUESTS_TOTAL.with_label_values(&labels).inc();
+    HTTP_REQUEST_DURATION_SECONDS
+        .with_label_values(&labels)
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn observe_cache_lookup(key: &str, result: &str) {
+    // Label by namespace ("classics:all" -> "classics") to keep cardinality bounded
+    let cache = key.split(':').next().unwrap_or(key);
+    CACHE_REQUESTS_TOTAL.with_label_values(&[cache, result]).inc();
+}
+
+/// Count a character review once its result is stored in `character_progress`.
+pub fn observe_review_submitted() {
+    REVIEWS_SUBMITTED_TOTAL.inc();
+}
+
+/// Count a practice session once it is stored as completed.
+pub fn observe_session_completed(session_type: &str) {
+    // The type comes from the client; keep the label to the known set
+    let session_type = SESSION_TYPES.iter().find(|t| **t == session_type).copied().unwrap_or("other");
+    SESSIONS_COMPLETED_TOTAL.with_label_values(&[session_type]).inc();
+}
+
+pub fn record_pool_stats(db: &Database) {
+    for (name, pool) in db.pools() {
+        let size = pool.size() as i64;
+        let idle = pool.num_idle() as i64;
+
+        DB_POOL_CONNECTIONS.with_label_values(&[&name, "idle"]).set(idle);
+        DB_POOL_CONNECTIONS.with_label_values(&[&name, "in_use"]).set((size - idle).max(0));
+        DB_POOL_MAX_CONNECTIONS
+            .with_label_values(&[&name])
+            .set(pool.options().get_max_connections() as i64);
+    }
+}
+
+pub fn gather() -> anyhow::Result<String> {
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&REGISTRY.gather(), &mut buffer)?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+/// Collapse a request path into a low-cardinality route label,
+/// e.g. `/api/classics/daodejing` -> `/api/classics/:slug`.
+fn route_label(path: &str, status: u16) -> String {
+    // Unmatched paths are attacker-controlled; never use them as label values
+    if status == 404 || status == 405 {
+        return "unmatched".to_string();
+    }
+
+    let mut previous = "";
+    let segments: Vec<&str> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|segment| {
+            let label = if Uuid::parse_str(segment).is_ok() || segment.parse::<i64>().is_ok() {
+                ":id"
+            } else if previous == "classics" {
+                ":slug"
+            } else {
+                segment
+            };
+            previous = segment;
+            label
+        })
+        .collect();
+
+    format!("/{}", segments.join("/"))
+}
@@ -7,21 +7,35 @@ pub fn with_auth(
     warp::header::<String>("authorization")
         .and_then(move |auth_header: String| {
             let secret = secret.clone();
-            async move {
-                if let Some(token) = auth_header.strip_prefix("Bearer ") {
-                    match verify_jwt_token(token, &secret) {
-                        Ok(claims) => {
-                            if let Ok(user_id) = uuid::Uuid::parse_str(&claims.sub) {
-                                Ok(user_id)
-                            } else {
-                                Err(warp::reject::custom(crate::errors::AppError::Unauthorized))
-                            }
-                        }
-                        Err(_) => Err(warp::reject::custom(crate::errors::AppError::Unauthorized)),
-                    }
+            async move { user_id(&auth_header, &secret) }
+        })
+}
+
+/// The signed-in user, if any. Anonymous requests pass with `None`; a token
+/// that is present but invalid is still rejected.
+pub fn optional_auth(
+    secret: String,
+) -> impl Filter<Extract = (Option<uuid::Uuid>,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and_then(move |auth_header: Option<String>| {
+            let secret = secret.clone();
+            async move { auth_header.map(|header| user_id(&header, &secret)).transpose() }
+        })
+}
+
+fn user_id(auth_header: &str, secret: &str) -> Result<uuid::Uuid, Rejection> {
+    if let Some(token) = auth_header.strip_prefix("Bearer ") {
+        match verify_jwt_token(token, secret) {
+            Ok(claims) => {
+                if let Ok(user_id) = uuid::Uuid::parse_str(&claims.sub) {
+                    Ok(user_id)
                 } else {
                     Err(warp::reject::custom(crate::errors::AppError::Unauthorized))
                 }
             }
-        })
-}
\ No newline at end of file
+            Err(_) => Err(warp::reject::custom(crate::errors::AppError::Unauthorized)),
+        }
+    } else {
+        Err(warp::reject::custom(crate::errors::AppError::Unauthorized))
+    }
+}
@@ -0,0 +1,204 @@
+use std::convert::Infallible;
+use std::io::Write;
+
+use flate2::{write::GzEncoder, Compression};
+use warp::{
+    Filter, Reply,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    hyper::body::{self, Body},
+};
+
+/// Bodies smaller than this don't gain enough to be worth the CPU.
+const MIN_COMPRESS_SIZE: usize = 860;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    fn encode(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Encoding::Brotli => {
+                let mut output = Vec::new();
+                {
+                    // Quality 5 keeps latency low while still beating gzip on CJK JSON
+                    let mut writer = brotli::CompressorWriter::new(&mut output, 4096, 5, 22);
+                    writer.write_all(data)?;
+                }
+                Ok(output)
+            }
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Pick the best supported encoding from an `Accept-Encoding` header,
+/// honouring q-values and preferring brotli on ties.
+pub fn negotiate(accept_encoding: &str) -> Option<Encoding> {
+    let mut best: Option<(Encoding, f32)> = None;
+
+    for item in accept_encoding.split(',') {
+        let mut parts = item.split(';').map(str::trim);
+        let name = parts.next().unwrap_or("").to_ascii_lowercase();
+        let quality = parts
+            .find_map(|param| param.strip_prefix("q="))
+            .and_then(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        if quality <= 0.0 {
+            continue;
+        }
+
+        let candidates: &[Encoding] = match name.as_str() {
+            "br" => &[Encoding::Brotli],
+            "gzip" | "x-gzip" => &[Encoding::Gzip],
+            "*" => &[Encoding::Brotli, Encoding::Gzip],
+            _ => &[],
+        };
+
+        for &encoding in candidates {
+            let better = match best {
+                None => true,
+                Some((current, q)) => {
+                    quality > q || (quality == q && encoding == Encoding::Brotli && current != Encoding::Brotli)
+                }
+            };
+            if better {
+                best = Some((encoding, quality));
+            }
+        }
+    }
+
+    best.map(|(encoding, _)| encoding)
+}
+
+pub fn accept_encoding() -> impl Filter<Extract = (Option<Encoding>,), Error = Infallible> + Clone {
+    warp::header::optional::<String>("accept-encoding")
+        .or(warp::any().map(|| None))
+        .unify()
+        .map(|value: Option<String>| value.as_deref().and_then(negotiate))
+}
+
+/// The `If-None-Match` of the request, so a 304 can name the representation
+/// the client holds.
+pub fn if_none_match() -> impl Filter<Extract = (Option<String>,), Error = Infallible> + Clone {
+    warp::header::optional::<String>("if-none-match")
+        .or(warp::any().map(|| None))
+        .unify()
+}
+
+pub async fn compress(
+    encoding: Option<Encoding>,
+    if_none_match: Option<String>,
+    reply: impl Reply,
+) -> Result<warp::reply::Response, Infallible> {
+    let mut response = reply.into_response();
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(not_modified(response, encoding, if_none_match.as_deref()));
+    }
+    if !is_compressible(&response) {
+        return Ok(response);
+    }
+
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+
+    let Some(encoding) = encoding else {
+        return Ok(response);
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("读取响应体失败: {}", e);
+            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    };
+
+    if bytes.len() < MIN_COMPRESS_SIZE {
+        return Ok(warp::reply::Response::from_parts(parts, Body::from(bytes)));
+    }
+
+    match encoding.encode(&bytes) {
+        Ok(compressed) => {
+            parts.headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(encoding.as_str()));
+            parts.headers.remove(header::CONTENT_LENGTH);
+
+            // The encoded representation needs its own strong validator
+            if let Some(tagged) = encoded_etag(&parts.headers, encoding) {
+                parts.headers.insert(header::ETAG, tagged);
+            }
+
+            Ok(warp::reply::Response::from_parts(parts, Body::from(compressed)))
+        }
+        Err(e) => {
+            tracing::warn!("响应压缩失败: {}", e);
+            Ok(warp::reply::Response::from_parts(parts, Body::from(bytes)))
+        }
+    }
+}
+
+fn encoded_etag(headers: &HeaderMap, encoding: Encoding) -> Option<HeaderValue> {
+    let etag = headers.get(header::ETAG)?.to_str().ok()?;
+    format!("{}-{}\"", etag.trim_end_matches('"'), encoding.as_str()).parse().ok()
+}
+
+/// A 304 stands in for the representation the client cached, so it varies on
+/// `Accept-Encoding` too and carries the encoded ETag when that is the one the
+/// client sent. Whether a 200 would have been encoded depends on its size,
+/// which a 304 does not know; the client's tag says.
+fn not_modified(
+    mut response: warp::reply::Response,
+    encoding: Option<Encoding>,
+    if_none_match: Option<&str>,
+) -> warp::reply::Response {
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+
+    let Some(tagged) = encoding.and_then(|encoding| encoded_etag(response.headers(), encoding)) else {
+        return response;
+    };
+    let held = if_none_match.is_some_and(|tags| {
+        tags.split(',')
+            .map(str::trim)
+            .any(|tag| tag.strip_prefix("W/").unwrap_or(tag).as_bytes() == tagged.as_bytes())
+    });
+    if held {
+        response.headers_mut().insert(header::ETAG, tagged);
+    }
+    response
+}
+
+fn is_compressible(response: &warp::reply::Response) -> bool {
+    if response.status() == StatusCode::NO_CONTENT
+        || response.headers().contains_key(header::CONTENT_ENCODING)
+    {
+        return false;
+    }
+
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|content_type| {
+            content_type.starts_with("application/json")
+                || content_type.starts_with("text/")
+        })
+        .unwrap_or(false)
+}
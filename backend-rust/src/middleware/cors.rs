@@ -1,16 +1,211 @@
-use warp::Filter;
-
-pub fn cors() -> warp::cors::Builder {
-    warp::cors()
-        .allow_any_origin()
-        .allow_headers(vec![
-            "content-type",
-            "authorization",
-            "accept",
-            "origin",
-            "user-agent",
-            "x-requested-with",
-        ])
-        .allow_methods(vec!["GET", "POST", "PUT", "DELETE", "PATCH", "OPTIONS"])
-        .max_age(3600)
-}
\ No newline at end of file
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use warp::{
+    Filter, Rejection, Reply,
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+};
+
+use crate::config::CorsConfig;
+
+const ALLOWED_METHODS: &str = "GET, POST, PUT, DELETE, PATCH, OPTIONS";
+const ALLOWED_HEADERS: &[&str] = &[
+    "content-type",
+    "authorization",
+    "accept",
+    "origin",
+    "user-agent",
+    "x-requested-with",
+    "x-request-id",
+    "if-match",
+    "if-none-match",
+];
+const EXPOSED_HEADERS: &str = "x-request-id, etag, ratelimit-limit, ratelimit-remaining, ratelimit-reset, retry-after";
+
+#[derive(Debug, Clone, PartialEq)]
+enum OriginPattern {
+    Any,
+    Exact(String),
+    /// `https://*.example.com` matches any subdomain, but not the apex
+    Subdomain { scheme: String, suffix: String, port: Option<String> },
+}
+
+impl OriginPattern {
+    fn parse(pattern: &str) -> Self {
+        let pattern = pattern.trim().trim_end_matches('/');
+        if pattern == "*" {
+            return OriginPattern::Any;
+        }
+
+        if let Some((scheme, rest)) = pattern.split_once("://") {
+            if let Some(host) = rest.strip_prefix("*.") {
+                let (host, port) = split_port(host);
+                return OriginPattern::Subdomain {
+                    scheme: scheme.to_ascii_lowercase(),
+                    suffix: format!(".{}", host.to_ascii_lowercase()),
+                    port,
+                };
+            }
+        }
+
+        OriginPattern::Exact(pattern.to_ascii_lowercase())
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        match self {
+            OriginPattern::Any => true,
+            OriginPattern::Exact(exact) => exact.eq_ignore_ascii_case(origin),
+            OriginPattern::Subdomain { scheme, suffix, port } => {
+                let origin = origin.to_ascii_lowercase();
+                let Some((origin_scheme, rest)) = origin.split_once("://") else {
+                    return false;
+                };
+                let (host, origin_port) = split_port(rest);
+
+                origin_scheme == scheme
+                    && origin_port == *port
+                    && host.len() > suffix.len()
+                    && host.ends_with(suffix.as_str())
+            }
+        }
+    }
+}
+
+fn split_port(host: &str) -> (&str, Option<String>) {
+    match host.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => (host, Some(port.to_string())),
+        _ => (host, None),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CorsPolicy {
+    origins: Arc<Vec<OriginPattern>>,
+    allow_credentials: bool,
+    max_age_secs: u64,
+}
+
+impl CorsPolicy {
+    pub fn from_config(config: &CorsConfig) -> Self {
+        let origins: Vec<OriginPattern> = config
+            .allowed_origins
+            .iter()
+            .map(|origin| OriginPattern::parse(origin))
+            .collect();
+
+        // Reflecting arbitrary origins together with credentials would let any site
+        // act on behalf of a logged-in user
+        let allow_credentials = if config.allow_credentials && origins.contains(&OriginPattern::Any) {
+            tracing::warn!("CORS 允许任意来源时不能携带凭证，已禁用 allow_credentials");
+            false
+        } else {
+            config.allow_credentials
+        };
+
+        if origins.is_empty() {
+            tracing::warn!("未配置 CORS_ORIGINS，跨域请求将被拒绝");
+        }
+
+        Self {
+            origins: Arc::new(origins),
+            allow_credentials,
+            max_age_secs: config.max_age_secs,
+        }
+    }
+
+    pub fn is_allowed(&self, origin: &str) -> bool {
+        self.origins.iter().any(|pattern| pattern.matches(origin))
+    }
+
+    /// Add CORS headers to a response for an allowed `Origin`.
+    pub fn apply(&self, origin: Option<String>, reply: impl Reply) -> warp::reply::Response {
+        let mut response = reply.into_response();
+        let headers = response.headers_mut();
+        headers.append(header::VARY, HeaderValue::from_static("origin"));
+
+        if let Some(origin) = origin.filter(|origin| self.is_allowed(origin)) {
+            self.insert_origin_headers(headers, &origin);
+            headers.insert(
+                header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                HeaderValue::from_static(EXPOSED_HEADERS),
+            );
+        }
+
+        response
+    }
+
+    fn preflight_response(&self, origin: &str, request_headers: Option<&str>) -> warp::reply::Response {
+        if !self.is_allowed(origin) {
+            return StatusCode::FORBIDDEN.into_response();
+        }
+
+        let disallowed = request_headers
+            .map(|requested| {
+                requested
+                    .split(',')
+                    .map(|h| h.trim().to_ascii_lowercase())
+                    .any(|h| !h.is_empty() && !ALLOWED_HEADERS.contains(&h.as_str()))
+            })
+            .unwrap_or(false);
+        if disallowed {
+            return StatusCode::FORBIDDEN.into_response();
+        }
+
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        let headers = response.headers_mut();
+        self.insert_origin_headers(headers, origin);
+        headers.append(header::VARY, HeaderValue::from_static("origin"));
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static(ALLOWED_METHODS));
+        if let Ok(value) = ALLOWED_HEADERS.join(", ").parse() {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, value);
+        }
+        headers.insert(header::ACCESS_CONTROL_MAX_AGE, self.max_age_secs.into());
+
+        response
+    }
+
+    fn insert_origin_headers(&self, headers: &mut HeaderMap, origin: &str) {
+        if let Ok(value) = origin.parse() {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+        }
+        if self.allow_credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+}
+
+/// The request's `Origin` header, if present and valid.
+pub fn origin() -> impl Filter<Extract = (Option<String>,), Error = Infallible> + Clone {
+    warp::header::headers_cloned().map(|headers: HeaderMap| {
+        headers
+            .get(header::ORIGIN)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    })
+}
+
+/// Answer CORS preflight requests before they reach the routes.
+pub fn preflight(
+    policy: CorsPolicy,
+) -> impl Filter<Extract = (warp::reply::Response,), Error = Rejection> + Clone {
+    // `warp::options()` would reject other methods with 405, which outranks the
+    // 404 of an unknown route when rejections are combined
+    warp::method()
+        .and_then(|method: Method| async move {
+            if method == Method::OPTIONS {
+                Ok(())
+            } else {
+                Err(warp::reject::not_found())
+            }
+        })
+        .untuple_one()
+        .and(warp::header::<String>("origin"))
+        .and(warp::header::<String>("access-control-request-method"))
+        .and(warp::header::optional::<String>("access-control-request-headers"))
+        .map(move |origin: String, _method: String, request_headers: Option<String>| {
+            policy.preflight_response(&origin, request_headers.as_deref())
+        })
+}
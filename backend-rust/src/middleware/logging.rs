@@ -1,4 +1,3 @@
-use warp::Filter;
 use tracing::info;
 
 pub fn log() -> warp::log::Log<impl Fn(warp::log::Info) + Copy> {
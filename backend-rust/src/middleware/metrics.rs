@@ -0,0 +1,12 @@
+use crate::metrics;
+
+pub fn track() -> warp::log::Log<impl Fn(warp::log::Info) + Copy> {
+    warp::log::custom(|info| {
+        metrics::observe_http_request(
+            info.method().as_str(),
+            info.path(),
+            info.status().as_u16(),
+            info.elapsed(),
+        );
+    })
+}
@@ -1,4 +1,8 @@
+pub mod compression;
 pub mod cors;
 pub mod logging;
+pub mod metrics;
+pub mod rate_limit;
+pub mod request_id;
 pub mod security;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
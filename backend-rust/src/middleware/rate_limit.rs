@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use governor::{
+    clock::{Clock, DefaultClock},
+    middleware::StateInformationMiddleware,
+    state::keyed::DefaultKeyedStateStore,
+    Quota,
+};
+use redis::aio::Connection;
+use tokio::sync::Mutex;
+use warp::{Filter, Rejection, Reply, http::HeaderMap, path::FullPath};
+
+use crate::{
+    config::{Config, RateLimitBackend},
+    errors::AppError,
+    tls::PeerAddr,
+    utils::jwt::verify_jwt_token,
+};
+
+type KeyedLimiter = governor::RateLimiter<
+    String,
+    DefaultKeyedStateStore<String>,
+    DefaultClock,
+    StateInformationMiddleware,
+>;
+
+/// Routes are grouped into classes so login/registration can be held to a
+/// much stricter budget than content reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteClass {
+    Auth,
+    Api,
+}
+
+impl RouteClass {
+    pub fn from_path(path: &str) -> Self {
+        if path.starts_with("/api/auth/") {
+            RouteClass::Auth
+        } else {
+            RouteClass::Api
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteClass::Auth => "auth",
+            RouteClass::Api => "api",
+        }
+    }
+}
+
+/// Token bucket: `burst` requests at once, refilled at `per_minute`.
+#[derive(Debug, Clone, Copy)]
+pub struct Limit {
+    pub per_minute: u32,
+    pub burst: u32,
+}
+
+impl Limit {
+    fn replenish_interval(&self) -> Duration {
+        Duration::from_millis(60_000 / u64::from(self.per_minute.max(1)))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time until the bucket is full again
+    pub reset_after: Duration,
+    /// Time until the next request would be allowed (only when denied)
+    pub retry_after: Duration,
+}
+
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    async fn check(&self, key: &str, class: RouteClass, limit: Limit) -> anyhow::Result<RateLimitDecision>;
+}
+
+/// In-process token buckets; limits are per replica.
+pub struct MemoryStore {
+    limiters: HashMap<RouteClass, KeyedLimiter>,
+    clock: DefaultClock,
+}
+
+impl MemoryStore {
+    pub fn new(api: Limit, auth: Limit) -> Self {
+        let mut limiters = HashMap::new();
+        limiters.insert(RouteClass::Api, keyed_limiter(api));
+        limiters.insert(RouteClass::Auth, keyed_limiter(auth));
+
+        Self {
+            limiters,
+            clock: DefaultClock::default(),
+        }
+    }
+
+    /// Drop buckets that have refilled completely so the key map doesn't grow forever.
+    pub fn retain_recent(&self) {
+        for limiter in self.limiters.values() {
+            limiter.retain_recent();
+            limiter.shrink_to_fit();
+        }
+    }
+}
+
+fn keyed_limiter(limit: Limit) -> KeyedLimiter {
+    let per_minute = NonZeroU32::new(limit.per_minute).unwrap_or(NonZeroU32::MIN);
+    let burst = NonZeroU32::new(limit.burst).unwrap_or(NonZeroU32::MIN);
+
+    governor::RateLimiter::keyed(Quota::per_minute(per_minute).allow_burst(burst))
+        .with_middleware::<StateInformationMiddleware>()
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn check(&self, key: &str, class: RouteClass, limit: Limit) -> anyhow::Result<RateLimitDecision> {
+        let limiter = self
+            .limiters
+            .get(&class)
+            .ok_or_else(|| anyhow::anyhow!("no limiter for route class {}", class.as_str()))?;
+        let interval = limit.replenish_interval();
+
+        let decision = match limiter.check_key(&key.to_string()) {
+            Ok(snapshot) => {
+                let remaining = snapshot.remaining_burst_capacity();
+                RateLimitDecision {
+                    allowed: true,
+                    limit: limit.burst,
+                    remaining,
+                    reset_after: interval * limit.burst.saturating_sub(remaining),
+                    retry_after: Duration::ZERO,
+                }
+            }
+            Err(not_until) => RateLimitDecision {
+                allowed: false,
+                limit: limit.burst,
+                remaining: 0,
+                reset_after: interval * limit.burst,
+                retry_after: not_until.wait_time_from(self.clock.now()),
+            },
+        };
+
+        Ok(decision)
+    }
+}
+
+// GCRA over Redis so every replica draws from the same bucket.
+// Stores the theoretical arrival time (ms) per key and uses the server clock.
+const GCRA_SCRIPT: &str = r#"
+local interval = tonumber(ARGV[1])
+local burst = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local tolerance = interval * burst
+
+local tat = tonumber(redis.call('GET', KEYS[1]) or now)
+if tat < now then
+    tat = now
+end
+
+local new_tat = tat + interval
+local allow_at = new_tat - tolerance
+if allow_at > now then
+    return {0, 0, allow_at - now, tat - now}
+end
+
+redis.call('SET', KEYS[1], new_tat, 'PX', new_tat - now)
+return {1, math.floor((now - allow_at) / interval), 0, new_tat - now}
+"#;
+
+/// Redis-backed buckets shared by all replicas.
+pub struct RedisStore {
+    redis: Arc<Mutex<Connection>>,
+    script: redis::Script,
+}
+
+impl RedisStore {
+    pub fn new(redis: Arc<Mutex<Connection>>) -> Self {
+        Self {
+            redis,
+            script: redis::Script::new(GCRA_SCRIPT),
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisStore {
+    async fn check(&self, key: &str, _class: RouteClass, limit: Limit) -> anyhow::Result<RateLimitDecision> {
+        let interval_ms = limit.replenish_interval().as_millis() as u64;
+
+        let mut conn = self.redis.lock().await;
+        let result: Vec<i64> = self
+            .script
+            .key(format!("ratelimit:{}", key))
+            .arg(interval_ms)
+            .arg(limit.burst)
+            .invoke_async(&mut *conn)
+            .await?;
+
+        let [allowed, remaining, retry_after_ms, reset_after_ms] = result[..] else {
+            anyhow::bail!("unexpected rate limit script result: {:?}", result);
+        };
+
+        Ok(RateLimitDecision {
+            allowed: allowed == 1,
+            limit: limit.burst,
+            remaining: remaining.max(0) as u32,
+            reset_after: Duration::from_millis(reset_after_ms.max(0) as u64),
+            retry_after: Duration::from_millis(retry_after_ms.max(0) as u64),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    api: Limit,
+    auth: Limit,
+    jwt_secret: String,
+    trust_proxy: bool,
+}
+
+impl RateLimiter {
+    pub fn from_config(config: &Config, redis: Option<Arc<Mutex<Connection>>>) -> Option<Self> {
+        let settings = &config.rate_limit;
+        if !settings.enabled {
+            return None;
+        }
+
+        let api = Limit { per_minute: settings.per_minute, burst: settings.burst };
+        let auth = Limit { per_minute: settings.auth_per_minute, burst: settings.auth_burst };
+
+        let store: Arc<dyn RateLimitStore> = match (&settings.backend, redis) {
+            (RateLimitBackend::Redis, Some(redis)) => Arc::new(RedisStore::new(redis)),
+            (backend, _) => {
+                if *backend == RateLimitBackend::Redis {
+                    tracing::warn!("未连接 Redis，限流改用进程内存储");
+                }
+                let store = Arc::new(MemoryStore::new(api, auth));
+                let cleanup = store.clone();
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(Duration::from_secs(60));
+                    loop {
+                        interval.tick().await;
+                        cleanup.retain_recent();
+                    }
+                });
+                store
+            }
+        };
+
+        Some(Self {
+            store,
+            api,
+            auth,
+            jwt_secret: config.auth.jwt_secret.clone(),
+            trust_proxy: settings.trust_proxy,
+        })
+    }
+
+    async fn check(
+        &self,
+        path: &str,
+        remote: Option<SocketAddr>,
+        headers: &HeaderMap,
+    ) -> Result<Option<RateLimitDecision>, Rejection> {
+        let class = RouteClass::from_path(path);
+        let limit = match class {
+            RouteClass::Auth => self.auth,
+            RouteClass::Api => self.api,
+        };
+        let key = format!("{}:{}", class.as_str(), self.client_key(remote, headers));
+
+        match self.store.check(&key, class, limit).await {
+            Ok(decision) if decision.allowed => Ok(Some(decision)),
+            Ok(decision) => {
+                tracing::warn!(key = %key, "请求频率超限");
+                Err(warp::reject::custom(AppError::RateLimited(decision)))
+            }
+            Err(e) => {
+                // Fail open: an unavailable limiter backend must not take the API down
+                tracing::error!("限流检查失败: {}", e);
+                Ok(None)
+            }
+        }
+    }
+
+    // Authenticated users get their own bucket so a classroom behind one NAT
+    // address doesn't share a single per-IP budget.
+    fn client_key(&self, remote: Option<SocketAddr>, headers: &HeaderMap) -> String {
+        let user_id = headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| verify_jwt_token(token, &self.jwt_secret).ok())
+            .map(|claims| claims.sub);
+
+        if let Some(user_id) = user_id {
+            return format!("user:{}", user_id);
+        }
+
+        // The proxy appends the address it saw, so only the last entry is
+        // trustworthy; anything before it came from the client
+        let forwarded = if self.trust_proxy {
+            headers
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.rsplit(',').next())
+                .map(|ip| ip.trim().to_string())
+                .filter(|ip| !ip.is_empty())
+        } else {
+            None
+        };
+
+        let ip = forwarded
+            .or_else(|| remote.map(|addr| addr.ip().to_string()))
+            .unwrap_or_else(|| "unknown".to_string());
+
+        format!("ip:{}", ip)
+    }
+}
+
+pub fn rate_limit(
+    limiter: Option<RateLimiter>,
+) -> impl Filter<Extract = (Option<RateLimitDecision>,), Error = Rejection> + Clone {
+    warp::path::full()
+        .and(warp::addr::remote())
+        .and(warp::ext::optional::<PeerAddr>())
+        .and(warp::header::headers_cloned())
+        .and_then(move |path: FullPath, remote: Option<SocketAddr>, peer: Option<PeerAddr>, headers: HeaderMap| {
+            let limiter = limiter.clone();
+            let remote = remote.or(peer.map(|peer| peer.0));
+            async move {
+                match limiter {
+                    Some(limiter) => limiter.check(path.as_str(), remote, &headers).await,
+                    None => Ok(None),
+                }
+            }
+        })
+}
+
+/// Attach `RateLimit-*` headers describing the caller's bucket.
+pub fn with_headers(decision: Option<RateLimitDecision>, reply: impl Reply) -> warp::reply::Response {
+    let mut response = reply.into_response();
+    if let Some(decision) = decision {
+        insert_headers(response.headers_mut(), &decision);
+    }
+    response
+}
+
+pub fn insert_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert("ratelimit-limit", decision.limit.into());
+    headers.insert("ratelimit-remaining", decision.remaining.into());
+    headers.insert("ratelimit-reset", ceil_secs(decision.reset_after).into());
+
+    if !decision.allowed {
+        headers.insert("retry-after", ceil_secs(decision.retry_after).max(1).into());
+    }
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    (duration.as_millis() as u64).div_ceil(1000)
+}
@@ -0,0 +1,54 @@
+use std::convert::Infallible;
+use warp::{Filter, Reply, http::HeaderMap};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Root span for every request. `request_id` and `status` are recorded later
+/// by [`request_id`] and [`finish`], which run inside this span.
+pub fn trace() -> warp::trace::Trace<impl Fn(warp::trace::Info) -> tracing::Span + Clone> {
+    warp::trace(|info| {
+        tracing::info_span!(
+            "request",
+            method = %info.method(),
+            path = %info.path(),
+            request_id = tracing::field::Empty,
+            status = tracing::field::Empty,
+            otel.kind = "server",
+        )
+    })
+}
+
+/// Propagate the caller's `X-Request-Id` or generate a new one.
+pub fn request_id() -> impl Filter<Extract = (String,), Error = Infallible> + Clone {
+    warp::header::headers_cloned().map(|headers: HeaderMap| {
+        let id = headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| is_valid(value))
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        tracing::Span::current().record("request_id", id.as_str());
+        id
+    })
+}
+
+/// Echo the request ID back to the caller and record the final status on the span.
+pub fn finish(request_id: String, reply: impl Reply) -> warp::reply::Response {
+    let mut response = reply.into_response();
+    tracing::Span::current().record("status", response.status().as_u16());
+
+    if let Ok(value) = request_id.parse() {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+// Incoming IDs end up in logs and response headers, so only accept short, plain tokens
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
@@ -1,33 +1,56 @@
-use warp::{Filter, reply::Reply, http::HeaderMap};
-
-pub fn security_headers() -> impl Filter<Extract = impl Reply, Error = std::convert::Infallible> + Clone {
-    warp::any()
-        .map(|| {
-            let mut headers = HeaderMap::new();
-            
-            // Security headers
-            headers.insert("X-Content-Type-Options", "nosniff".parse().unwrap());
-            headers.insert("X-Frame-Options", "DENY".parse().unwrap());
-            headers.insert("X-XSS-Protection", "1; mode=block".parse().unwrap());
-            headers.insert("Referrer-Policy", "strict-origin-when-cross-origin".parse().unwrap());
-            headers.insert("Permissions-Policy", "geolocation=(), microphone=(), camera=()".parse().unwrap());
-            
-            // Content Security Policy
-            headers.insert(
-                "Content-Security-Policy",
-                "default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; img-src 'self' data: https:; font-src 'self' https:"
-                    .parse()
-                    .unwrap(),
-            );
-            
-            // HSTS (HTTP Strict Transport Security) - only in production
-            if std::env::var("NODE_ENV").unwrap_or_default() == "production" {
-                headers.insert(
-                    "Strict-Transport-Security",
-                    "max-age=31536000; includeSubDomains; preload".parse().unwrap(),
-                );
+use warp::{reply::Reply, http::{HeaderMap, HeaderValue}};
+
+use crate::config::Config;
+
+/// Security headers added to every response, including rejections.
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    headers: HeaderMap,
+}
+
+impl SecurityHeaders {
+    pub fn from_config(config: &Config) -> Self {
+        let mut headers = HeaderMap::new();
+
+        headers.insert("X-Content-Type-Options", HeaderValue::from_static("nosniff"));
+        headers.insert("X-Frame-Options", HeaderValue::from_static("DENY"));
+        headers.insert("X-XSS-Protection", HeaderValue::from_static("1; mode=block"));
+        headers.insert("Referrer-Policy", HeaderValue::from_static("strict-origin-when-cross-origin"));
+        headers.insert("Permissions-Policy", HeaderValue::from_static("geolocation=(), microphone=(), camera=()"));
+
+        // Content Security Policy
+        match config.security.content_security_policy.parse() {
+            Ok(csp) => {
+                headers.insert("Content-Security-Policy", csp);
+            }
+            Err(_) => tracing::warn!("无效的 Content-Security-Policy 配置，已忽略"),
+        }
+
+        // HSTS (HTTP Strict Transport Security)
+        if config.security.hsts_enabled {
+            let mut hsts = format!("max-age={}", config.security.hsts_max_age_secs);
+            if config.security.hsts_include_subdomains {
+                hsts.push_str("; includeSubDomains");
+            }
+            if config.security.hsts_preload {
+                hsts.push_str("; preload");
+            }
+            if let Ok(value) = hsts.parse() {
+                headers.insert("Strict-Transport-Security", value);
+            }
+        }
+
+        Self { headers }
+    }
+
+    pub fn apply(&self, reply: impl Reply) -> warp::reply::Response {
+        let mut response = reply.into_response();
+        for (name, value) in &self.headers {
+            // Handlers may set a stricter value themselves
+            if !response.headers().contains_key(name) {
+                response.headers_mut().insert(name.clone(), value.clone());
             }
-            
-            warp::reply::with_headers(warp::reply(), headers)
-        })
-}
\ No newline at end of file
+        }
+        response
+    }
+}
@@ -1,9 +1,14 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgExecutor, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
 use chrono::{DateTime, Utc};
 use anyhow::Result;
 
+use crate::models::PUBLIC_CLASSIC;
+use crate::utils::merge_patch;
+use crate::utils::pagination::{Page, PageRequest, Position, Sort, SortField};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Chapter {
     pub id: Uuid,
@@ -15,7 +20,7 @@ pub struct Chapter {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateChapterRequest {
     pub classic_id: Uuid,
     pub number: i32,
@@ -23,14 +28,27 @@ pub struct CreateChapterRequest {
     pub content: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// A JSON merge patch (RFC 7396): members left out are kept, `null` clears
+/// `content`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
 pub struct UpdateChapterRequest {
+    #[serde(default, deserialize_with = "merge_patch::non_null")]
+    #[schema(nullable = false)]
     pub number: Option<i32>,
+    #[serde(default, deserialize_with = "merge_patch::non_null")]
+    #[schema(nullable = false)]
     pub title: Option<String>,
-    pub content: Option<String>,
+    #[serde(default, deserialize_with = "merge_patch::nullable")]
+    #[schema(value_type = Option<String>)]
+    pub content: Option<Option<String>>,
+    /// Regenerate the chapter's sentences from its content once updated
+    #[serde(default)]
+    pub resegment: bool,
+    /// Why the change was made, kept with its revision
+    pub comment: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ChapterResponse {
     pub id: Uuid,
     pub classic_id: Uuid,
@@ -55,23 +73,119 @@ impl From<Chapter> for ChapterResponse {
     }
 }
 
+/// Query string of the chapter lists.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[serde(deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct ChapterListQuery {
+    /// 1-based page number
+    pub page: Option<i64>,
+    /// Items per page, 1–100 (default 20)
+    pub limit: Option<i64>,
+    /// `next_cursor` of the previous page; default order only
+    pub cursor: Option<String>,
+    /// `number` (default: by classic, then chapter number), `title` or `created_at`; prefix `-` for descending
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub sort: Sort<ChapterSortField>,
+    /// Only chapters of this classic
+    pub classic_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ChapterSortField {
+    #[default]
+    Number,
+    Title,
+    CreatedAt,
+}
+
+impl SortField for ChapterSortField {
+    const FIELDS: &'static [(&'static str, Self)] = &[
+        ("number", Self::Number),
+        ("title", Self::Title),
+        ("created_at", Self::CreatedAt),
+    ];
+
+    fn columns(self) -> &'static [&'static str] {
+        match self {
+            Self::Number => &["classic_id", "number"],
+            Self::Title => &["title"],
+            Self::CreatedAt => &["created_at"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChapterFilter {
+    pub classic_id: Option<Uuid>,
+    /// Leave out chapters of classics the public cannot see
+    pub published_only: bool,
+    pub sort: Sort<ChapterSortField>,
+}
+
+/// Keyset of the default order, unique per `UNIQUE(classic_id, number)`.
+pub type ChapterKey = (Uuid, i32);
+
+impl ChapterListQuery {
+    pub fn into_parts(self) -> Result<(ChapterFilter, PageRequest<ChapterKey>), String> {
+        let page = PageRequest::parse(self.page, self.limit, self.cursor.as_deref(), self.sort.is_default())?;
+        let filter = ChapterFilter {
+            classic_id: self.classic_id,
+            published_only: false,
+            sort: self.sort,
+        };
+        Ok((filter, page))
+    }
+}
+
+impl ChapterFilter {
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, Postgres>) {
+        if let Some(classic_id) = self.classic_id {
+            query.push(" AND classic_id = ").push_bind(classic_id);
+        }
+        if self.published_only {
+            query.push(" AND classic_id IN (SELECT id FROM classics WHERE ").push(PUBLIC_CLASSIC).push(")");
+        }
+    }
+}
+
 impl Chapter {
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<Chapter>> {
-        let chapters = sqlx::query_as::<_, Chapter>(
-            "SELECT id, classic_id, number, title, content, created_at, updated_at 
-             FROM chapters 
-             ORDER BY classic_id, number ASC"
-        )
-        .fetch_all(pool)
-        .await?;
-        Ok(chapters)
+    /// One page of chapters matching the filter.
+    pub async fn list(pool: &PgPool, filter: &ChapterFilter, page: &PageRequest<ChapterKey>) -> Result<Page<Chapter>> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, classic_id, number, title, content, created_at, updated_at
+             FROM chapters
+             WHERE deleted_at IS NULL"
+        );
+        filter.push_conditions(&mut query);
+        if let Position::After((classic_id, number)) = page.position {
+            query.push(" AND (classic_id, number) > (").push_bind(classic_id).push(", ").push_bind(number).push(")");
+        }
+        query.push(" ORDER BY ").push(filter.sort.order_by());
+        query.push(" LIMIT ").push_bind(page.fetch_limit());
+        if let Some(offset) = page.offset() {
+            query.push(" OFFSET ").push_bind(offset);
+        }
+        let chapters = query.build_query_as::<Chapter>().fetch_all(pool).await?;
+
+        let total = match page.offset() {
+            Some(_) => {
+                let mut count = QueryBuilder::new("SELECT COUNT(*) FROM chapters WHERE deleted_at IS NULL");
+                filter.push_conditions(&mut count);
+                Some(count.build_query_scalar::<i64>().fetch_one(pool).await?)
+            }
+            None => None,
+        };
+
+        Ok(page.finish(chapters, total, |c| (c.classic_id, c.number)))
     }
 
     pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Chapter>> {
         let chapter = sqlx::query_as::<_, Chapter>(
             "SELECT id, classic_id, number, title, content, created_at, updated_at 
              FROM chapters 
-             WHERE id = $1"
+             WHERE id = $1 AND deleted_at IS NULL"
         )
         .bind(id)
         .fetch_optional(pool)
@@ -83,7 +197,7 @@ impl Chapter {
         let chapters = sqlx::query_as::<_, Chapter>(
             "SELECT id, classic_id, number, title, content, created_at, updated_at 
              FROM chapters 
-             WHERE classic_id = $1 
+             WHERE classic_id = $1 AND deleted_at IS NULL
              ORDER BY number ASC"
         )
         .bind(classic_id)
@@ -92,7 +206,7 @@ impl Chapter {
         Ok(chapters)
     }
 
-    pub async fn create(pool: &PgPool, req: CreateChapterRequest) -> Result<Chapter> {
+    pub async fn create(executor: impl PgExecutor<'_>, req: CreateChapterRequest) -> Result<Chapter> {
         let id = Uuid::new_v4();
         let now = Utc::now();
         
@@ -108,40 +222,106 @@ impl Chapter {
         .bind(req.content)
         .bind(now)
         .bind(now)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
         Ok(chapter)
     }
 
-    pub async fn update(pool: &PgPool, id: Uuid, req: UpdateChapterRequest) -> Result<Option<Chapter>> {
+    /// Apply a merge patch; `content` is bound as whether it was sent and
+    /// what to set it to.
+    pub async fn update(executor: impl PgExecutor<'_>, id: Uuid, req: UpdateChapterRequest) -> Result<Option<Chapter>> {
         let now = Utc::now();
         
         let chapter = sqlx::query_as::<_, Chapter>(
             "UPDATE chapters 
              SET number = COALESCE($2, number),
                  title = COALESCE($3, title),
-                 content = COALESCE($4, content),
-                 updated_at = $5
-             WHERE id = $1 
+                 content = CASE WHEN $4 THEN $5 ELSE content END,
+                 updated_at = $6
+             WHERE id = $1 AND deleted_at IS NULL
              RETURNING id, classic_id, number, title, content, created_at, updated_at"
         )
         .bind(id)
         .bind(req.number)
         .bind(req.title)
-        .bind(req.content)
+        .bind(req.content.is_some())
+        .bind(req.content.flatten())
         .bind(now)
-        .fetch_optional(pool)
+        .fetch_optional(executor)
+        .await?;
+        Ok(chapter)
+    }
+
+    /// Insert a chapter, or update the one with the same number in the classic.
+    pub async fn upsert(executor: impl PgExecutor<'_>, req: &CreateChapterRequest) -> Result<Chapter> {
+        let chapter = sqlx::query_as::<_, Chapter>(
+            "INSERT INTO chapters (id, classic_id, number, title, content)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (classic_id, number) WHERE deleted_at IS NULL DO UPDATE
+             SET title = EXCLUDED.title,
+                 content = EXCLUDED.content,
+                 updated_at = NOW()
+             RETURNING id, classic_id, number, title, content, created_at, updated_at"
+        )
+        .bind(Uuid::new_v4())
+        .bind(req.classic_id)
+        .bind(req.number)
+        .bind(&req.title)
+        .bind(&req.content)
+        .fetch_one(executor)
+        .await?;
+        Ok(chapter)
+    }
+
+    /// Lock a chapter for the rest of the transaction and read it.
+    pub async fn lock(executor: impl PgExecutor<'_>, id: Uuid) -> Result<Option<Chapter>> {
+        let chapter = sqlx::query_as::<_, Chapter>(
+            "SELECT id, classic_id, number, title, content, created_at, updated_at
+             FROM chapters
+             WHERE id = $1 AND deleted_at IS NULL
+             FOR UPDATE"
+        )
+        .bind(id)
+        .fetch_optional(executor)
         .await?;
         Ok(chapter)
     }
 
-    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool> {
+    /// Set the title and content to those of an earlier state, nulls included.
+    /// Numbering is left alone.
+    pub async fn restore(executor: impl PgExecutor<'_>, id: Uuid, snapshot: &Chapter) -> Result<Option<Chapter>> {
+        let chapter = sqlx::query_as::<_, Chapter>(
+            "UPDATE chapters
+             SET title = $2,
+                 content = $3
+             WHERE id = $1 AND deleted_at IS NULL
+             RETURNING id, classic_id, number, title, content, created_at, updated_at"
+        )
+        .bind(id)
+        .bind(&snapshot.title)
+        .bind(&snapshot.content)
+        .fetch_optional(executor)
+        .await?;
+        Ok(chapter)
+    }
+
+    /// Move a chapter to the trash with its live sentences, stamped with the
+    /// transaction's time. Progress that refers to them is kept.
+    pub async fn delete(conn: &mut PgConnection, id: Uuid) -> Result<bool> {
         let result = sqlx::query(
-            "DELETE FROM chapters WHERE id = $1"
+            "UPDATE chapters SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL"
         )
         .bind(id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
-        Ok(result.rows_affected() > 0)
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query("UPDATE sentences SET deleted_at = NOW() WHERE chapter_id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        Ok(true)
     }
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor, PgPool};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use anyhow::Result;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Character {
+    pub id: Uuid,
+    pub character: String,
+    pub traditional: Option<String>,
+    pub simplified: Option<String>,
+    pub pinyin: Option<String>,
+    pub meaning: Option<String>,
+    pub etymology: Option<String>,
+    pub radical: Option<String>,
+    pub stroke_count: Option<i32>,
+    pub frequency_rank: Option<i32>,
+    pub hsk_level: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A character without database-assigned fields, as used by import/export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterData {
+    pub character: String,
+    pub traditional: Option<String>,
+    pub simplified: Option<String>,
+    pub pinyin: Option<String>,
+    pub meaning: Option<String>,
+    pub etymology: Option<String>,
+    pub radical: Option<String>,
+    pub stroke_count: Option<i32>,
+    pub frequency_rank: Option<i32>,
+    pub hsk_level: Option<i32>,
+}
+
+impl From<Character> for CharacterData {
+    fn from(character: Character) -> Self {
+        CharacterData {
+            character: character.character,
+            traditional: character.traditional,
+            simplified: character.simplified,
+            pinyin: character.pinyin,
+            meaning: character.meaning,
+            etymology: character.etymology,
+            radical: character.radical,
+            stroke_count: character.stroke_count,
+            frequency_rank: character.frequency_rank,
+            hsk_level: character.hsk_level,
+        }
+    }
+}
+
+impl Character {
+    pub async fn find_all(pool: &PgPool) -> Result<Vec<Character>> {
+        let characters = sqlx::query_as::<_, Character>(
+            "SELECT id, character, traditional, simplified, pinyin, meaning, etymology, radical,
+                    stroke_count, frequency_rank, hsk_level, created_at, updated_at
+             FROM characters
+             ORDER BY frequency_rank ASC NULLS LAST, character ASC"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(characters)
+    }
+
+    pub async fn find_by_character(pool: &PgPool, character: &str) -> Result<Option<Character>> {
+        let character = sqlx::query_as::<_, Character>(
+            "SELECT id, character, traditional, simplified, pinyin, meaning, etymology, radical,
+                    stroke_count, frequency_rank, hsk_level, created_at, updated_at
+             FROM characters
+             WHERE character = $1"
+        )
+        .bind(character)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(character)
+    }
+
+    /// Insert a character, or overwrite the existing row for the same character.
+    pub async fn upsert(executor: impl PgExecutor<'_>, data: &CharacterData) -> Result<Character> {
+        let character = sqlx::query_as::<_, Character>(
+            "INSERT INTO characters (id, character, traditional, simplified, pinyin, meaning, etymology,
+                                     radical, stroke_count, frequency_rank, hsk_level)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             ON CONFLICT (character) DO UPDATE
+             SET traditional = EXCLUDED.traditional,
+                 simplified = EXCLUDED.simplified,
+                 pinyin = EXCLUDED.pinyin,
+                 meaning = EXCLUDED.meaning,
+                 etymology = EXCLUDED.etymology,
+                 radical = EXCLUDED.radical,
+                 stroke_count = EXCLUDED.stroke_count,
+                 frequency_rank = EXCLUDED.frequency_rank,
+                 hsk_level = EXCLUDED.hsk_level,
+                 updated_at = NOW()
+             RETURNING id, character, traditional, simplified, pinyin, meaning, etymology, radical,
+                       stroke_count, frequency_rank, hsk_level, created_at, updated_at"
+        )
+        .bind(Uuid::new_v4())
+        .bind(&data.character)
+        .bind(&data.traditional)
+        .bind(&data.simplified)
+        .bind(&data.pinyin)
+        .bind(&data.meaning)
+        .bind(&data.etymology)
+        .bind(&data.radical)
+        .bind(data.stroke_count)
+        .bind(data.frequency_rank)
+        .bind(data.hsk_level)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(character)
+    }
+}
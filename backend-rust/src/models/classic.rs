@@ -1,10 +1,16 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgConnection, PgExecutor, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
 use chrono::{DateTime, Utc};
 use anyhow::Result;
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+use crate::errors::AppError;
+use crate::models::{PublicationStatus, PUBLIC_CLASSIC};
+use crate::utils::merge_patch;
+use crate::utils::pagination::{Page, PageRequest, Position, Sort, SortField};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Classic {
     pub id: Uuid,
     pub slug: String,
@@ -12,11 +18,16 @@ pub struct Classic {
     pub author: Option<String>,
     pub dynasty: Option<String>,
     pub description: Option<String>,
+    /// Defaulted so revisions recorded before the workflow existed still parse
+    #[serde(default)]
+    pub status: PublicationStatus,
+    pub publish_at: Option<DateTime<Utc>>,
+    pub unpublish_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateClassicRequest {
     pub slug: String,
     pub title: String,
@@ -25,19 +36,158 @@ pub struct CreateClassicRequest {
     pub description: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A JSON merge patch (RFC 7396): members left out are kept, `null` clears
+/// `author`, `dynasty` or `description`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
 pub struct UpdateClassicRequest {
+    #[serde(default, deserialize_with = "merge_patch::non_null")]
+    #[schema(nullable = false)]
     pub title: Option<String>,
+    #[serde(default, deserialize_with = "merge_patch::nullable")]
+    #[schema(value_type = Option<String>)]
+    pub author: Option<Option<String>>,
+    #[serde(default, deserialize_with = "merge_patch::nullable")]
+    #[schema(value_type = Option<String>)]
+    pub dynasty: Option<Option<String>>,
+    #[serde(default, deserialize_with = "merge_patch::nullable")]
+    #[schema(value_type = Option<String>)]
+    pub description: Option<Option<String>>,
+    /// Why the change was made, kept with its revision
+    pub comment: Option<String>,
+}
+
+/// Query string of `GET /api/classics`.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[serde(deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct ClassicListQuery {
+    /// 1-based page number
+    pub page: Option<i64>,
+    /// Items per page, 1–100 (default 20)
+    pub limit: Option<i64>,
+    /// `next_cursor` of the previous page; default order only
+    pub cursor: Option<String>,
+    /// `created_at` (default), `title`, `dynasty` or `author`; prefix `-` for descending
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub sort: Sort<ClassicSortField>,
+    /// Exact dynasty
+    pub dynasty: Option<String>,
+    /// Exact author
     pub author: Option<String>,
+    /// Workflow status, e.g. `in_review` for the review queue; admins only
+    pub status: Option<PublicationStatus>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ClassicSortField {
+    #[default]
+    CreatedAt,
+    Title,
+    Dynasty,
+    Author,
+}
+
+impl SortField for ClassicSortField {
+    const FIELDS: &'static [(&'static str, Self)] = &[
+        ("created_at", Self::CreatedAt),
+        ("title", Self::Title),
+        ("dynasty", Self::Dynasty),
+        ("author", Self::Author),
+    ];
+
+    fn columns(self) -> &'static [&'static str] {
+        match self {
+            Self::CreatedAt => &["created_at"],
+            Self::Title => &["title"],
+            Self::Dynasty => &["dynasty"],
+            Self::Author => &["author"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClassicFilter {
     pub dynasty: Option<String>,
-    pub description: Option<String>,
+    pub author: Option<String>,
+    pub status: Option<PublicationStatus>,
+    /// Leave out classics the public cannot see
+    pub published_only: bool,
+    pub sort: Sort<ClassicSortField>,
+}
+
+/// Keyset of the default order.
+pub type ClassicKey = (DateTime<Utc>, Uuid);
+
+impl ClassicListQuery {
+    pub fn into_parts(self) -> Result<(ClassicFilter, PageRequest<ClassicKey>), String> {
+        let page = PageRequest::parse(self.page, self.limit, self.cursor.as_deref(), self.sort.is_default())?;
+        let filter = ClassicFilter {
+            dynasty: self.dynasty,
+            author: self.author,
+            status: self.status,
+            published_only: false,
+            sort: self.sort,
+        };
+        Ok((filter, page))
+    }
+}
+
+impl ClassicFilter {
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, Postgres>) {
+        if let Some(dynasty) = &self.dynasty {
+            query.push(" AND dynasty = ").push_bind(dynasty.clone());
+        }
+        if let Some(author) = &self.author {
+            query.push(" AND author = ").push_bind(author.clone());
+        }
+        if let Some(status) = self.status {
+            query.push(" AND status = ").push_bind(status);
+        }
+        if self.published_only {
+            query.push(" AND ").push(PUBLIC_CLASSIC);
+        }
+    }
 }
 
 impl Classic {
+    /// One page of classics matching the filter.
+    pub async fn list(pool: &PgPool, filter: &ClassicFilter, page: &PageRequest<ClassicKey>) -> Result<Page<Classic>> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                    created_at, updated_at
+             FROM classics
+             WHERE deleted_at IS NULL"
+        );
+        filter.push_conditions(&mut query);
+        if let Position::After((created_at, id)) = page.position {
+            query.push(" AND (created_at, id) > (").push_bind(created_at).push(", ").push_bind(id).push(")");
+        }
+        query.push(" ORDER BY ").push(filter.sort.order_by());
+        query.push(" LIMIT ").push_bind(page.fetch_limit());
+        if let Some(offset) = page.offset() {
+            query.push(" OFFSET ").push_bind(offset);
+        }
+        let classics = query.build_query_as::<Classic>().fetch_all(pool).await?;
+
+        let total = match page.offset() {
+            Some(_) => {
+                let mut count = QueryBuilder::new("SELECT COUNT(*) FROM classics WHERE deleted_at IS NULL");
+                filter.push_conditions(&mut count);
+                Some(count.build_query_scalar::<i64>().fetch_one(pool).await?)
+            }
+            None => None,
+        };
+
+        Ok(page.finish(classics, total, |c| (c.created_at, c.id)))
+    }
+
     pub async fn find_all(pool: &PgPool) -> Result<Vec<Classic>> {
         let classics = sqlx::query_as::<_, Classic>(
-            "SELECT id, slug, title, author, dynasty, description, created_at, updated_at 
+            "SELECT id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                    created_at, updated_at 
              FROM classics 
+             WHERE deleted_at IS NULL
              ORDER BY created_at ASC"
         )
         .fetch_all(pool)
@@ -48,9 +198,10 @@ impl Classic {
 
     pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Classic>> {
         let classic = sqlx::query_as::<_, Classic>(
-            "SELECT id, slug, title, author, dynasty, description, created_at, updated_at 
+            "SELECT id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                    created_at, updated_at 
              FROM classics 
-             WHERE id = $1"
+             WHERE id = $1 AND deleted_at IS NULL"
         )
         .bind(id)
         .fetch_optional(pool)
@@ -61,9 +212,10 @@ impl Classic {
 
     pub async fn find_by_slug(pool: &PgPool, slug: &str) -> Result<Option<Classic>> {
         let classic = sqlx::query_as::<_, Classic>(
-            "SELECT id, slug, title, author, dynasty, description, created_at, updated_at 
+            "SELECT id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                    created_at, updated_at 
              FROM classics 
-             WHERE slug = $1"
+             WHERE slug = $1 AND deleted_at IS NULL"
         )
         .bind(slug)
         .fetch_optional(pool)
@@ -72,14 +224,24 @@ impl Classic {
         Ok(classic)
     }
 
-    pub async fn create(pool: &PgPool, req: CreateClassicRequest) -> Result<Classic> {
+    /// Whether any classic uses the slug; deleted ones keep theirs until purged.
+    pub async fn slug_exists(pool: &PgPool, slug: &str) -> Result<bool> {
+        let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM classics WHERE slug = $1)")
+            .bind(slug)
+            .fetch_one(pool)
+            .await?;
+        Ok(exists)
+    }
+
+    pub async fn create(executor: impl PgExecutor<'_>, req: CreateClassicRequest) -> Result<Classic> {
         let id = Uuid::new_v4();
         let now = Utc::now();
 
         let classic = sqlx::query_as::<_, Classic>(
             "INSERT INTO classics (id, slug, title, author, dynasty, description, created_at, updated_at)
              VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-             RETURNING id, slug, title, author, dynasty, description, created_at, updated_at"
+             RETURNING id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                       created_at, updated_at"
         )
         .bind(id)
         .bind(&req.slug)
@@ -89,43 +251,132 @@ impl Classic {
         .bind(&req.description)
         .bind(now)
         .bind(now)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
 
         Ok(classic)
     }
 
-    pub async fn update(pool: &PgPool, id: Uuid, req: UpdateClassicRequest) -> Result<Option<Classic>> {
+    /// Apply a merge patch; each nullable column is bound as whether it was
+    /// sent and what to set it to.
+    pub async fn update(executor: impl PgExecutor<'_>, id: Uuid, req: UpdateClassicRequest) -> Result<Option<Classic>> {
         let now = Utc::now();
 
         let classic = sqlx::query_as::<_, Classic>(
             "UPDATE classics 
              SET title = COALESCE($2, title),
-                 author = COALESCE($3, author),
-                 dynasty = COALESCE($4, dynasty),
-                 description = COALESCE($5, description),
-                 updated_at = $6
-             WHERE id = $1
-             RETURNING id, slug, title, author, dynasty, description, created_at, updated_at"
+                 author = CASE WHEN $3 THEN $4 ELSE author END,
+                 dynasty = CASE WHEN $5 THEN $6 ELSE dynasty END,
+                 description = CASE WHEN $7 THEN $8 ELSE description END,
+                 updated_at = $9
+             WHERE id = $1 AND deleted_at IS NULL
+             RETURNING id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                       created_at, updated_at"
         )
         .bind(id)
         .bind(&req.title)
+        .bind(req.author.is_some())
+        .bind(req.author.flatten())
+        .bind(req.dynasty.is_some())
+        .bind(req.dynasty.flatten())
+        .bind(req.description.is_some())
+        .bind(req.description.flatten())
+        .bind(now)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(classic)
+    }
+
+    /// Insert a classic, or update the existing one with the same slug. Fails if
+    /// that one is in the trash.
+    pub async fn upsert(executor: impl PgExecutor<'_>, req: &CreateClassicRequest) -> Result<Classic> {
+        let classic = sqlx::query_as::<_, Classic>(
+            "INSERT INTO classics (id, slug, title, author, dynasty, description)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (slug) DO UPDATE
+             SET title = EXCLUDED.title,
+                 author = EXCLUDED.author,
+                 dynasty = EXCLUDED.dynasty,
+                 description = EXCLUDED.description,
+                 updated_at = NOW()
+             WHERE classics.deleted_at IS NULL
+             RETURNING id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                       created_at, updated_at"
+        )
+        .bind(Uuid::new_v4())
+        .bind(&req.slug)
+        .bind(&req.title)
         .bind(&req.author)
         .bind(&req.dynasty)
         .bind(&req.description)
-        .bind(now)
-        .fetch_optional(pool)
+        .fetch_optional(executor)
         .await?;
 
+        classic.ok_or_else(|| AppError::Validation(format!("经典 {} 在回收站中，请先恢复或彻底删除", req.slug)).into())
+    }
+
+    /// Lock a classic for the rest of the transaction and read it.
+    pub async fn lock(executor: impl PgExecutor<'_>, id: Uuid) -> Result<Option<Classic>> {
+        let classic = sqlx::query_as::<_, Classic>(
+            "SELECT id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                    created_at, updated_at
+             FROM classics
+             WHERE id = $1 AND deleted_at IS NULL
+             FOR UPDATE"
+        )
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+        Ok(classic)
+    }
+
+    /// Set the title, author, dynasty and description to those of an earlier state, nulls included.
+    /// The slug is left alone, since links use it.
+    pub async fn restore(executor: impl PgExecutor<'_>, id: Uuid, snapshot: &Classic) -> Result<Option<Classic>> {
+        let classic = sqlx::query_as::<_, Classic>(
+            "UPDATE classics
+             SET title = $2,
+                 author = $3,
+                 dynasty = $4,
+                 description = $5
+             WHERE id = $1 AND deleted_at IS NULL
+             RETURNING id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                       created_at, updated_at"
+        )
+        .bind(id)
+        .bind(&snapshot.title)
+        .bind(&snapshot.author)
+        .bind(&snapshot.dynasty)
+        .bind(&snapshot.description)
+        .fetch_optional(executor)
+        .await?;
         Ok(classic)
     }
 
-    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM classics WHERE id = $1")
+    /// Move a classic to the trash with its live chapters and sentences, all
+    /// stamped with the transaction's time. Progress that refers to them is kept.
+    pub async fn delete(conn: &mut PgConnection, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("UPDATE classics SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL")
             .bind(id)
-            .execute(pool)
+            .execute(&mut *conn)
             .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
 
-        Ok(result.rows_affected() > 0)
+        sqlx::query(
+            "UPDATE sentences SET deleted_at = NOW()
+             WHERE deleted_at IS NULL
+               AND chapter_id IN (SELECT id FROM chapters WHERE classic_id = $1)"
+        )
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query("UPDATE chapters SET deleted_at = NOW() WHERE classic_id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        Ok(true)
     }
 }
\ No newline at end of file
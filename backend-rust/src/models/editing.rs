@@ -0,0 +1,675 @@
+//! Editor operations that change numbering: insert-at, move, renumber, split and
+//! merge. Each runs in one transaction with the parent row locked and leaves the
+//! parent's children numbered 1, 2, 3… with no gaps. Rows that are inserted,
+//! split, merged, moved, renumbered or regenerated get a revision in the same
+//! transaction; rows only shifted to make room do not.
+//!
+//! The unique indexes on `(chapter_id, number)` and `(classic_id, number)` are
+//! checked row by row, so shifting numbers in place would collide. Rows whose
+//! number changes are first parked on distinct negative numbers, then given their
+//! final ones. The indexes are partial (live rows only) and so cannot be deferred.
+//! Deleted rows are left out of the numbering altogether.
+
+use anyhow::Result;
+use serde::Deserialize;
+use sqlx::{Postgres, Transaction};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{Chapter, Edit, EntityType, Revision, RevisionAction, Sentence};
+
+/// Number a row is parked on while it moves into another parent.
+const PARKED: i32 = i32::MIN;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InsertSentenceRequest {
+    /// 1-based position; sentences from there on move down by one
+    pub position: i32,
+    pub text: String,
+    pub pinyin: Option<String>,
+    pub translation: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MoveSentenceRequest {
+    /// Chapter to move into; defaults to the sentence's own chapter
+    pub chapter_id: Option<Uuid>,
+    /// 1-based position in that chapter; defaults to the end
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SplitSentenceRequest {
+    /// Characters that stay in the first sentence
+    pub offset: usize,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InsertChapterRequest {
+    /// 1-based position; chapters from there on move down by one
+    pub position: i32,
+    pub title: String,
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MoveChapterRequest {
+    /// 1-based position in the classic; defaults to the end
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RenumberRequest {
+    /// Every id of the parent's children in the new order; omit to keep the
+    /// current order and only close gaps
+    pub order: Option<Vec<Uuid>>,
+}
+
+fn invalid(message: impl Into<String>) -> anyhow::Error {
+    AppError::Validation(message.into()).into()
+}
+
+/// The index at which to place a row among `len` others, from a 1-based position.
+/// `None` means the end.
+pub fn insert_index(position: Option<i32>, len: usize) -> Result<usize> {
+    match position {
+        None => Ok(len),
+        Some(position) if position >= 1 && position as usize <= len + 1 => Ok(position as usize - 1),
+        Some(_) => Err(invalid(format!("position must be between 1 and {}", len + 1))),
+    }
+}
+
+/// The new order for `current`, checking that `order` lists each of its ids once.
+pub fn requested_order(current: &[Uuid], order: Option<Vec<Uuid>>) -> Result<Vec<Uuid>> {
+    let Some(order) = order else {
+        return Ok(current.to_vec());
+    };
+    let mut sorted_order = order.clone();
+    sorted_order.sort();
+    let mut sorted_current = current.to_vec();
+    sorted_current.sort();
+    if sorted_order != sorted_current {
+        return Err(invalid("order must list every item exactly once"));
+    }
+    Ok(order)
+}
+
+/// The numbers that make `order` read 1, 2, 3…, for the rows in `current` whose
+/// number changes. Ids in `order` but not in `current` are rows yet to be inserted.
+pub fn renumbering(current: &[(Uuid, i32)], order: &[Uuid]) -> Vec<(Uuid, i32)> {
+    order
+        .iter()
+        .enumerate()
+        .filter_map(|(index, id)| {
+            let number = index as i32 + 1;
+            current
+                .iter()
+                .find(|(current_id, _)| current_id == id)
+                .filter(|(_, current_number)| *current_number != number)
+                .map(|_| (*id, number))
+        })
+        .collect()
+}
+
+/// Split text after `offset` characters; both parts must be non-empty.
+pub fn split_text(text: &str, offset: usize) -> Result<(String, String)> {
+    let len = text.chars().count();
+    if len < 2 {
+        return Err(invalid("A single character cannot be split"));
+    }
+    if offset == 0 || offset >= len {
+        return Err(invalid(format!("offset must be between 1 and {}", len.saturating_sub(1))));
+    }
+    let at = text.char_indices().nth(offset).map(|(at, _)| at).unwrap_or(text.len());
+    Ok((text[..at].to_string(), text[at..].to_string()))
+}
+
+/// Pinyin or translation of two merged sentences: both joined by a space, or
+/// whichever one exists.
+pub fn join_annotations(first: Option<String>, second: Option<String>) -> Option<String> {
+    match (first, second) {
+        (Some(first), Some(second)) => Some(format!("{} {}", first, second)),
+        (first, second) => first.or(second),
+    }
+}
+
+/// For each text, an existing sentence with exactly that text to keep, taken in
+/// order; each existing sentence is kept at most once.
+pub fn reuse_by_text(existing: &[(Uuid, String)], texts: &[String]) -> Vec<Option<Uuid>> {
+    let mut used = vec![false; existing.len()];
+    texts
+        .iter()
+        .map(|text| {
+            let index = existing
+                .iter()
+                .enumerate()
+                .position(|(index, (_, existing))| !used[index] && existing == text)?;
+            used[index] = true;
+            Some(existing[index].0)
+        })
+        .collect()
+}
+
+/// The ids of `ids` with `id` removed and put back at `index`.
+fn moved(ids: &[Uuid], id: Uuid, index: usize) -> Vec<Uuid> {
+    let mut ids: Vec<Uuid> = ids.iter().copied().filter(|other| *other != id).collect();
+    ids.insert(index.min(ids.len()), id);
+    ids
+}
+
+/// Apply `renumbering` output to `table`, parking the rows first.
+async fn apply_numbers(tx: &mut Transaction<'_, Postgres>, table: &str, changes: &[(Uuid, i32)]) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<Uuid> = changes.iter().map(|(id, _)| *id).collect();
+    let numbers: Vec<i32> = changes.iter().map(|(_, number)| *number).collect();
+
+    // Final numbers are positive and distinct, so their negations cannot collide
+    for sign in ["-", ""] {
+        sqlx::query(&format!(
+            "UPDATE {table} SET number = {sign}changes.number
+             FROM UNNEST($1::uuid[], $2::int[]) AS changes(id, number)
+             WHERE {table}.id = changes.id"
+        ))
+        .bind(&ids)
+        .bind(&numbers)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Lock a chapter against concurrent edits; `false` if it does not exist.
+async fn lock_chapter(tx: &mut Transaction<'_, Postgres>, id: Uuid) -> Result<bool> {
+    let found = sqlx::query_scalar::<_, i32>("SELECT 1 FROM chapters WHERE id = $1 AND deleted_at IS NULL FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    Ok(found.is_some())
+}
+
+async fn lock_classic(tx: &mut Transaction<'_, Postgres>, id: Uuid) -> Result<bool> {
+    let found = sqlx::query_scalar::<_, i32>("SELECT 1 FROM classics WHERE id = $1 AND deleted_at IS NULL FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    Ok(found.is_some())
+}
+
+async fn sentence_numbers(tx: &mut Transaction<'_, Postgres>, chapter_id: Uuid) -> Result<Vec<(Uuid, i32)>> {
+    let numbers = sqlx::query_as::<_, (Uuid, i32)>(
+        "SELECT id, number FROM sentences WHERE chapter_id = $1 AND deleted_at IS NULL ORDER BY number"
+    )
+    .bind(chapter_id)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(numbers)
+}
+
+async fn chapter_numbers(tx: &mut Transaction<'_, Postgres>, classic_id: Uuid) -> Result<Vec<(Uuid, i32)>> {
+    let numbers = sqlx::query_as::<_, (Uuid, i32)>(
+        "SELECT id, number FROM chapters WHERE classic_id = $1 AND deleted_at IS NULL ORDER BY number"
+    )
+    .bind(classic_id)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(numbers)
+}
+
+async fn live_sentences(tx: &mut Transaction<'_, Postgres>, chapter_id: Uuid) -> Result<Vec<Sentence>> {
+    let sentences = sqlx::query_as::<_, Sentence>(
+        "SELECT id, chapter_id, number, text, pinyin, translation, created_at, updated_at
+         FROM sentences
+         WHERE chapter_id = $1 AND deleted_at IS NULL
+         ORDER BY number"
+    )
+    .bind(chapter_id)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(sentences)
+}
+
+async fn live_chapters(tx: &mut Transaction<'_, Postgres>, classic_id: Uuid) -> Result<Vec<Chapter>> {
+    let chapters = sqlx::query_as::<_, Chapter>(
+        "SELECT id, classic_id, number, title, content, created_at, updated_at
+         FROM chapters
+         WHERE classic_id = $1 AND deleted_at IS NULL
+         ORDER BY number"
+    )
+    .bind(classic_id)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(chapters)
+}
+
+fn ids(numbers: &[(Uuid, i32)]) -> Vec<Uuid> {
+    numbers.iter().map(|(id, _)| *id).collect()
+}
+
+async fn find_sentence(tx: &mut Transaction<'_, Postgres>, id: Uuid) -> Result<Option<Sentence>> {
+    let sentence = sqlx::query_as::<_, Sentence>(
+        "SELECT id, chapter_id, number, text, pinyin, translation, created_at, updated_at
+         FROM sentences
+         WHERE id = $1 AND deleted_at IS NULL"
+    )
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    Ok(sentence)
+}
+
+/// Lock a sentence's chapter and then the sentence itself, in that order, so
+/// that every edit of a chapter's numbering queues on the same row. `None` if
+/// the sentence or its chapter does not exist.
+async fn lock_sentence(tx: &mut Transaction<'_, Postgres>, id: Uuid) -> Result<Option<Sentence>> {
+    lock_sentence_with(tx, id, None).await.map(|found| found.map(|(sentence, _)| sentence))
+}
+
+/// Like `lock_sentence`, also locking `target` in chapter id order so two moves
+/// between the same chapters cannot deadlock. The flag is `false` if `target`
+/// does not exist.
+async fn lock_sentence_with(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    target: Option<Uuid>,
+) -> Result<Option<(Sentence, bool)>> {
+    loop {
+        let chapter_id = sqlx::query_scalar::<_, Uuid>("SELECT chapter_id FROM sentences WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&mut **tx)
+            .await?;
+        let Some(chapter_id) = chapter_id else {
+            return Ok(None);
+        };
+
+        let mut chapters = vec![chapter_id, target.unwrap_or(chapter_id)];
+        chapters.sort();
+        chapters.dedup();
+        let mut target_found = true;
+        for other in chapters {
+            if !lock_chapter(tx, other).await? {
+                if other != chapter_id {
+                    target_found = false;
+                    continue;
+                }
+                return Ok(None);
+            }
+        }
+
+        let sentence = sqlx::query_as::<_, Sentence>(
+            "SELECT id, chapter_id, number, text, pinyin, translation, created_at, updated_at
+             FROM sentences
+             WHERE id = $1 AND deleted_at IS NULL
+             FOR UPDATE"
+        )
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?;
+        match sentence {
+            // Moved to another chapter before the lock was taken: lock that one instead
+            Some(sentence) if sentence.chapter_id != chapter_id => continue,
+            Some(sentence) => return Ok(Some((sentence, target_found))),
+            None => return Ok(None),
+        }
+    }
+}
+
+async fn insert_sentence(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+    chapter_id: Uuid,
+    number: i32,
+    text: &str,
+    pinyin: Option<&str>,
+    translation: Option<&str>,
+) -> Result<Sentence> {
+    let sentence = sqlx::query_as::<_, Sentence>(
+        "INSERT INTO sentences (id, chapter_id, number, text, pinyin, translation)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, chapter_id, number, text, pinyin, translation, created_at, updated_at"
+    )
+    .bind(id)
+    .bind(chapter_id)
+    .bind(number)
+    .bind(text)
+    .bind(pinyin)
+    .bind(translation)
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(sentence)
+}
+
+impl Sentence {
+    /// Insert a sentence at a position in its chapter. `None` if the chapter does not exist.
+    pub async fn insert_at(pool: &sqlx::PgPool, chapter_id: Uuid, req: InsertSentenceRequest, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tx = pool.begin().await?;
+        if !lock_chapter(&mut tx, chapter_id).await? {
+            return Ok(None);
+        }
+
+        let current = sentence_numbers(&mut tx, chapter_id).await?;
+        let index = insert_index(Some(req.position), current.len())?;
+        let id = Uuid::new_v4();
+        let mut order = ids(&current);
+        order.insert(index, id);
+        apply_numbers(&mut tx, "sentences", &renumbering(&current, &order)).await?;
+
+        let sentence = insert_sentence(
+            &mut tx,
+            id,
+            chapter_id,
+            index as i32 + 1,
+            &req.text,
+            req.pinyin.as_deref(),
+            req.translation.as_deref(),
+        )
+        .await?;
+        Revision::record(&mut tx, EntityType::Sentence, id, RevisionAction::Create, None, Some(&sentence), edit).await?;
+        tx.commit().await?;
+        Ok(Some(sentence))
+    }
+
+    /// Move a sentence within its chapter or into another one. `None` if the sentence does not exist.
+    pub async fn move_to(pool: &sqlx::PgPool, id: Uuid, req: MoveSentenceRequest, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tx = pool.begin().await?;
+        let Some((sentence, target_found)) = lock_sentence_with(&mut tx, id, req.chapter_id).await? else {
+            return Ok(None);
+        };
+        if !target_found {
+            return Err(invalid("Target chapter does not exist"));
+        }
+        let target = req.chapter_id.unwrap_or(sentence.chapter_id);
+
+        if target != sentence.chapter_id {
+            sqlx::query("UPDATE sentences SET chapter_id = $2, number = $3 WHERE id = $1")
+                .bind(id)
+                .bind(target)
+                .bind(PARKED)
+                .execute(&mut *tx)
+                .await?;
+            let source = sentence_numbers(&mut tx, sentence.chapter_id).await?;
+            apply_numbers(&mut tx, "sentences", &renumbering(&source, &ids(&source))).await?;
+        }
+
+        let current = sentence_numbers(&mut tx, target).await?;
+        let index = insert_index(req.position, current.len() - 1)?;
+        let order = moved(&ids(&current), id, index);
+        apply_numbers(&mut tx, "sentences", &renumbering(&current, &order)).await?;
+
+        let Some(after) = find_sentence(&mut tx, id).await? else {
+            return Ok(None);
+        };
+        if (after.chapter_id, after.number) != (sentence.chapter_id, sentence.number) {
+            Revision::record(&mut tx, EntityType::Sentence, id, RevisionAction::Update, Some(&sentence), Some(&after), edit).await?;
+        }
+        tx.commit().await?;
+        Ok(Some(after))
+    }
+
+    /// Number a chapter's sentences 1, 2, 3… in the given or current order.
+    /// `None` if the chapter does not exist.
+    pub async fn renumber(
+        pool: &sqlx::PgPool,
+        chapter_id: Uuid,
+        order: Option<Vec<Uuid>>,
+        edit: &Edit,
+    ) -> Result<Option<Vec<Sentence>>> {
+        let mut tx = pool.begin().await?;
+        if !lock_chapter(&mut tx, chapter_id).await? {
+            return Ok(None);
+        }
+
+        let before = live_sentences(&mut tx, chapter_id).await?;
+        let current: Vec<(Uuid, i32)> = before.iter().map(|s| (s.id, s.number)).collect();
+        let order = requested_order(&ids(&current), order)?;
+        apply_numbers(&mut tx, "sentences", &renumbering(&current, &order)).await?;
+
+        let sentences = live_sentences(&mut tx, chapter_id).await?;
+        for after in &sentences {
+            if let Some(before) = before.iter().find(|b| b.id == after.id && b.number != after.number) {
+                Revision::record(&mut tx, EntityType::Sentence, after.id, RevisionAction::Update, Some(before), Some(after), edit).await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(Some(sentences))
+    }
+
+    /// Split a sentence after `offset` characters. The second part becomes the
+    /// next sentence; pinyin and translation describe the whole sentence, so
+    /// both parts are left without them. `None` if the sentence does not exist.
+    pub async fn split(pool: &sqlx::PgPool, id: Uuid, offset: usize, edit: &Edit) -> Result<Option<(Sentence, Sentence)>> {
+        let mut tx = pool.begin().await?;
+        let Some(sentence) = lock_sentence(&mut tx, id).await? else {
+            return Ok(None);
+        };
+        let (first_text, second_text) = split_text(&sentence.text, offset)?;
+
+        let current = sentence_numbers(&mut tx, sentence.chapter_id).await?;
+        let second_id = Uuid::new_v4();
+        let mut order = ids(&current);
+        let index = order.iter().position(|other| *other == id).unwrap_or(order.len()) + 1;
+        order.insert(index, second_id);
+        apply_numbers(&mut tx, "sentences", &renumbering(&current, &order)).await?;
+
+        let first = sqlx::query_as::<_, Sentence>(
+            "UPDATE sentences SET text = $2, pinyin = NULL, translation = NULL
+             WHERE id = $1
+             RETURNING id, chapter_id, number, text, pinyin, translation, created_at, updated_at"
+        )
+        .bind(id)
+        .bind(&first_text)
+        .fetch_one(&mut *tx)
+        .await?;
+        let second = insert_sentence(&mut tx, second_id, sentence.chapter_id, index as i32 + 1, &second_text, None, None).await?;
+        Revision::record(&mut tx, EntityType::Sentence, id, RevisionAction::Update, Some(&sentence), Some(&first), edit).await?;
+        Revision::record(&mut tx, EntityType::Sentence, second_id, RevisionAction::Create, None, Some(&second), edit).await?;
+        tx.commit().await?;
+        Ok(Some((first, second)))
+    }
+
+    /// Replace a chapter's sentences with `texts`, numbered in order. Sentences
+    /// whose text is unchanged are kept with their id, pinyin and translation;
+    /// the rest go to the trash. `None` if the chapter does not exist.
+    pub async fn regenerate(
+        pool: &sqlx::PgPool,
+        chapter_id: Uuid,
+        texts: Vec<String>,
+        edit: &Edit,
+    ) -> Result<Option<Vec<Sentence>>> {
+        let mut tx = pool.begin().await?;
+        let sentences = Sentence::regenerate_in(&mut tx, chapter_id, texts, edit).await?;
+        tx.commit().await?;
+        Ok(sentences)
+    }
+
+    /// `regenerate` as part of a larger transaction, e.g. a chapter update.
+    pub(crate) async fn regenerate_in(
+        tx: &mut Transaction<'_, Postgres>,
+        chapter_id: Uuid,
+        texts: Vec<String>,
+        edit: &Edit,
+    ) -> Result<Option<Vec<Sentence>>> {
+        if !lock_chapter(tx, chapter_id).await? {
+            return Ok(None);
+        }
+
+        let existing = sqlx::query_as::<_, (Uuid, String)>(
+            "SELECT id, text FROM sentences WHERE chapter_id = $1 AND deleted_at IS NULL ORDER BY number"
+        )
+        .bind(chapter_id)
+        .fetch_all(&mut **tx)
+        .await?;
+        let reused = reuse_by_text(&existing, &texts);
+        let kept: Vec<Uuid> = reused.iter().flatten().copied().collect();
+        let removed = sqlx::query_as::<_, Sentence>(
+            "UPDATE sentences SET deleted_at = NOW()
+             WHERE chapter_id = $1 AND deleted_at IS NULL AND NOT (id = ANY($2))
+             RETURNING id, chapter_id, number, text, pinyin, translation, created_at, updated_at"
+        )
+        .bind(chapter_id)
+        .bind(&kept)
+        .fetch_all(&mut **tx)
+        .await?;
+        for sentence in &removed {
+            Revision::record(tx, EntityType::Sentence, sentence.id, RevisionAction::Delete, Some(sentence), None, edit).await?;
+        }
+
+        let current = sentence_numbers(tx, chapter_id).await?;
+        let order: Vec<Uuid> = reused.iter().map(|id| id.unwrap_or_else(Uuid::new_v4)).collect();
+        apply_numbers(tx, "sentences", &renumbering(&current, &order)).await?;
+        for (index, text) in texts.iter().enumerate() {
+            if reused[index].is_none() {
+                let created = insert_sentence(tx, order[index], chapter_id, index as i32 + 1, text, None, None).await?;
+                Revision::record(tx, EntityType::Sentence, created.id, RevisionAction::Create, None, Some(&created), edit).await?;
+            }
+        }
+
+        let sentences = sqlx::query_as::<_, Sentence>(
+            "SELECT id, chapter_id, number, text, pinyin, translation, created_at, updated_at
+             FROM sentences
+             WHERE chapter_id = $1 AND deleted_at IS NULL
+             ORDER BY number"
+        )
+        .bind(chapter_id)
+        .fetch_all(&mut **tx)
+        .await?;
+        Ok(Some(sentences))
+    }
+
+    /// Merge a sentence with the one after it in its chapter; the one after goes
+    /// to the trash. `None` if the sentence does not exist.
+    pub async fn merge_with_next(pool: &sqlx::PgPool, id: Uuid, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tx = pool.begin().await?;
+        let Some(sentence) = lock_sentence(&mut tx, id).await? else {
+            return Ok(None);
+        };
+
+        let current = sentence_numbers(&mut tx, sentence.chapter_id).await?;
+        let index = current.iter().position(|(other, _)| *other == id).unwrap_or(current.len());
+        let Some((next_id, _)) = current.get(index + 1).copied() else {
+            return Err(invalid("No following sentence in this chapter to merge with"));
+        };
+        let Some(next) = find_sentence(&mut tx, next_id).await? else {
+            return Err(invalid("No following sentence in this chapter to merge with"));
+        };
+
+        sqlx::query("UPDATE sentences SET deleted_at = NOW() WHERE id = $1")
+            .bind(next_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE sentences SET text = $2, pinyin = $3, translation = $4 WHERE id = $1")
+            .bind(id)
+            .bind(format!("{}{}", sentence.text, next.text))
+            .bind(join_annotations(sentence.pinyin.clone(), next.pinyin.clone()))
+            .bind(join_annotations(sentence.translation.clone(), next.translation.clone()))
+            .execute(&mut *tx)
+            .await?;
+
+        let remaining = sentence_numbers(&mut tx, sentence.chapter_id).await?;
+        apply_numbers(&mut tx, "sentences", &renumbering(&remaining, &ids(&remaining))).await?;
+
+        let Some(merged) = find_sentence(&mut tx, id).await? else {
+            return Ok(None);
+        };
+        Revision::record(&mut tx, EntityType::Sentence, next_id, RevisionAction::Delete, Some(&next), None, edit).await?;
+        Revision::record(&mut tx, EntityType::Sentence, id, RevisionAction::Update, Some(&sentence), Some(&merged), edit).await?;
+        tx.commit().await?;
+        Ok(Some(merged))
+    }
+}
+
+impl Chapter {
+    /// Insert a chapter at a position in its classic. `None` if the classic does not exist.
+    pub async fn insert_at(pool: &sqlx::PgPool, classic_id: Uuid, req: InsertChapterRequest, edit: &Edit) -> Result<Option<Chapter>> {
+        let mut tx = pool.begin().await?;
+        if !lock_classic(&mut tx, classic_id).await? {
+            return Ok(None);
+        }
+
+        let current = chapter_numbers(&mut tx, classic_id).await?;
+        let index = insert_index(Some(req.position), current.len())?;
+        let id = Uuid::new_v4();
+        let mut order = ids(&current);
+        order.insert(index, id);
+        apply_numbers(&mut tx, "chapters", &renumbering(&current, &order)).await?;
+
+        let chapter = sqlx::query_as::<_, Chapter>(
+            "INSERT INTO chapters (id, classic_id, number, title, content)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING id, classic_id, number, title, content, created_at, updated_at"
+        )
+        .bind(id)
+        .bind(classic_id)
+        .bind(index as i32 + 1)
+        .bind(&req.title)
+        .bind(&req.content)
+        .fetch_one(&mut *tx)
+        .await?;
+        Revision::record(&mut tx, EntityType::Chapter, id, RevisionAction::Create, None, Some(&chapter), edit).await?;
+        tx.commit().await?;
+        Ok(Some(chapter))
+    }
+
+    /// Move a chapter within its classic. `None` if the chapter does not exist.
+    pub async fn move_to(pool: &sqlx::PgPool, id: Uuid, position: Option<i32>, edit: &Edit) -> Result<Option<Chapter>> {
+        let mut tx = pool.begin().await?;
+        let classic_id = sqlx::query_scalar::<_, Uuid>("SELECT classic_id FROM chapters WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(classic_id) = classic_id else {
+            return Ok(None);
+        };
+        if !lock_classic(&mut tx, classic_id).await? {
+            return Ok(None);
+        }
+
+        let chapters = live_chapters(&mut tx, classic_id).await?;
+        let Some(before) = chapters.iter().find(|chapter| chapter.id == id) else {
+            return Ok(None);
+        };
+        let current: Vec<(Uuid, i32)> = chapters.iter().map(|c| (c.id, c.number)).collect();
+        let index = insert_index(position, current.len() - 1)?;
+        let order = moved(&ids(&current), id, index);
+        apply_numbers(&mut tx, "chapters", &renumbering(&current, &order)).await?;
+
+        let Some(after) = live_chapters(&mut tx, classic_id).await?.into_iter().find(|chapter| chapter.id == id) else {
+            return Ok(None);
+        };
+        if after.number != before.number {
+            Revision::record(&mut tx, EntityType::Chapter, id, RevisionAction::Update, Some(before), Some(&after), edit).await?;
+        }
+        tx.commit().await?;
+        Ok(Some(after))
+    }
+
+    /// Number a classic's chapters 1, 2, 3… in the given or current order.
+    /// `None` if the classic does not exist.
+    pub async fn renumber(
+        pool: &sqlx::PgPool,
+        classic_id: Uuid,
+        order: Option<Vec<Uuid>>,
+        edit: &Edit,
+    ) -> Result<Option<Vec<Chapter>>> {
+        let mut tx = pool.begin().await?;
+        if !lock_classic(&mut tx, classic_id).await? {
+            return Ok(None);
+        }
+
+        let before = live_chapters(&mut tx, classic_id).await?;
+        let current: Vec<(Uuid, i32)> = before.iter().map(|c| (c.id, c.number)).collect();
+        let order = requested_order(&ids(&current), order)?;
+        apply_numbers(&mut tx, "chapters", &renumbering(&current, &order)).await?;
+
+        let chapters = live_chapters(&mut tx, classic_id).await?;
+        for after in &chapters {
+            if let Some(before) = before.iter().find(|b| b.id == after.id && b.number != after.number) {
+                Revision::record(&mut tx, EntityType::Chapter, after.id, RevisionAction::Update, Some(before), Some(after), edit).await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(Some(chapters))
+    }
+}
@@ -3,13 +3,21 @@ pub mod chapter;
 pub mod sentence;
 pub mod character;
 pub mod user;
-pub mod session;
-pub mod stats;
+pub mod tree;
+pub mod editing;
+pub mod segmentation;
+pub mod revision;
+pub mod publishing;
+pub mod trash;
 
 pub use classic::*;
 pub use chapter::*;
 pub use sentence::*;
 pub use character::*;
 pub use user::*;
-pub use session::*;
-pub use stats::*;
\ No newline at end of file
+pub use tree::*;
+pub use editing::*;
+pub use segmentation::*;
+pub use revision::*;
+pub use publishing::*;
+pub use trash::*;
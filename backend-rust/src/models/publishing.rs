@@ -0,0 +1,174 @@
+//! Publication workflow of classics: a new classic is a draft, is submitted for
+//! review and goes public once an admin approves it, optionally inside a
+//! publish window. Chapters and sentences are visible exactly when their
+//! classic is.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Type};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{Classic, Edit, EntityType, Revision, RevisionAction};
+
+/// SQL condition, over `classics` columns, for a classic the public can see.
+pub const PUBLIC_CLASSIC: &str = "status = 'published' \
+     AND (publish_at IS NULL OR publish_at <= NOW()) \
+     AND (unpublish_at IS NULL OR unpublish_at > NOW())";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "publication_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PublicationStatus {
+    #[default]
+    Draft,
+    InReview,
+    Published,
+}
+
+impl PublicationStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::InReview => "in_review",
+            Self::Published => "published",
+        }
+    }
+}
+
+/// Who is reading: the public sees published content only, admins see everything.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Audience {
+    Public,
+    Admin,
+}
+
+impl Audience {
+    pub fn is_public(self) -> bool {
+        self == Self::Public
+    }
+}
+
+/// A signed-in caller who may change content. Outside the admin audience they
+/// change drafts and classics in review only, and are not sent them back.
+#[derive(Debug, Clone, Copy)]
+pub struct Editor {
+    pub user_id: Uuid,
+    pub audience: Audience,
+}
+
+impl Editor {
+    pub fn edit(&self, comment: Option<String>) -> Edit {
+        Edit { editor_id: Some(self.user_id), comment }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ChangeStatusRequest {
+    pub status: PublicationStatus,
+    /// Go live at this time rather than at once; only when publishing
+    pub publish_at: Option<DateTime<Utc>>,
+    /// Stop being public at this time; only when publishing
+    pub unpublish_at: Option<DateTime<Utc>>,
+    /// Kept with the revision, e.g. a reviewer's reason for sending a classic back
+    pub comment: Option<String>,
+}
+
+/// Check a status change against the workflow:
+///
+/// - anyone may submit a draft for review, or take it back to draft;
+/// - only admins approve (publish), unpublish or reschedule.
+pub fn check_transition(
+    current: PublicationStatus,
+    req: &ChangeStatusRequest,
+    audience: Audience,
+) -> Result<(), AppError> {
+    use PublicationStatus::*;
+
+    let scheduled = req.publish_at.is_some() || req.unpublish_at.is_some();
+    if scheduled && req.status != Published {
+        return Err(AppError::Validation("只有发布时可以设置上线和下线时间".to_string()));
+    }
+    if let (Some(publish_at), Some(unpublish_at)) = (req.publish_at, req.unpublish_at) {
+        if unpublish_at <= publish_at {
+            return Err(AppError::Validation("下线时间必须晚于上线时间".to_string()));
+        }
+    }
+
+    match (current, req.status) {
+        (Draft, InReview) | (InReview, Draft) => Ok(()),
+        (InReview, Published) | (Published, Published) | (Published, Draft) => match audience {
+            Audience::Admin => Ok(()),
+            Audience::Public => Err(AppError::Forbidden),
+        },
+        (from, to) => Err(AppError::Validation(format!(
+            "不能从 {} 变为 {}",
+            from.as_str(),
+            to.as_str()
+        ))),
+    }
+}
+
+impl Classic {
+    /// Whether the public can see the classic at `now`.
+    pub fn is_public(&self, now: DateTime<Utc>) -> bool {
+        self.status == PublicationStatus::Published
+            && self.publish_at.is_none_or(|at| at <= now)
+            && self.unpublish_at.is_none_or(|at| at > now)
+    }
+
+    /// Move a classic through the workflow, recording the change as a revision.
+    /// `None` if the classic does not exist.
+    pub async fn change_status(
+        pool: &PgPool,
+        id: Uuid,
+        req: &ChangeStatusRequest,
+        audience: Audience,
+        edit: &Edit,
+    ) -> Result<Option<Classic>> {
+        let mut tx = pool.begin().await?;
+        let Some(before) = Classic::lock(&mut *tx, id).await? else {
+            return Ok(None);
+        };
+        check_transition(before.status, req, audience)?;
+
+        let after = sqlx::query_as::<_, Classic>(
+            "UPDATE classics
+             SET status = $2,
+                 publish_at = $3,
+                 unpublish_at = $4
+             WHERE id = $1
+             RETURNING id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                       created_at, updated_at"
+        )
+        .bind(id)
+        .bind(req.status)
+        .bind(req.publish_at)
+        .bind(req.unpublish_at)
+        .fetch_one(&mut *tx)
+        .await?;
+        Revision::record(&mut tx, EntityType::Classic, id, RevisionAction::Update, Some(&before), Some(&after), edit).await?;
+        tx.commit().await?;
+        Ok(Some(after))
+    }
+
+    /// The earliest `publish_at` or `unpublish_at` still ahead among published
+    /// classics: when what the public sees next changes without a write.
+    pub async fn next_scheduled_change(pool: &PgPool) -> Result<Option<DateTime<Utc>>> {
+        let at = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+            "SELECT MIN(at)
+             FROM (
+                 SELECT publish_at AS at FROM classics WHERE status = 'published' AND deleted_at IS NULL
+                 UNION ALL
+                 SELECT unpublish_at FROM classics WHERE status = 'published' AND deleted_at IS NULL
+             ) scheduled
+             WHERE at > NOW()"
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(at)
+    }
+}
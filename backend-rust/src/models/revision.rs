@@ -0,0 +1,355 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, PgConnection, PgPool, Type};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::models::{
+    Chapter, Classic, CreateChapterRequest, CreateClassicRequest, CreateSentenceRequest, Sentence,
+    UpdateChapterRequest, UpdateClassicRequest, UpdateSentenceRequest,
+};
+use crate::services::segmentation;
+use crate::utils::conditional::{IfMatch, Stale, Validators};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "revision_entity", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum EntityType {
+    Classic,
+    Chapter,
+    Sentence,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "revision_action", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum RevisionAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+}
+
+/// One change to a classic, chapter or sentence. Revisions are never changed
+/// or removed, not even when the entity is deleted.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Revision {
+    pub id: Uuid,
+    pub entity_type: EntityType,
+    pub entity_id: Uuid,
+    /// 1, 2, 3… per entity; version 0 is the state before the first revision
+    pub version: i32,
+    pub action: RevisionAction,
+    /// The entity as it was
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<Value>,
+    /// The entity as it became; null for deletions
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<Value>,
+    pub editor_id: Option<Uuid>,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Who made a change and why, recorded with its revision.
+#[derive(Debug, Clone, Default)]
+pub struct Edit {
+    pub editor_id: Option<Uuid>,
+    pub comment: Option<String>,
+}
+
+/// Versions to compare; 0 is the state before the first revision.
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct RevisionDiffQuery {
+    pub from: i32,
+    pub to: i32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FieldChange {
+    pub field: String,
+    #[schema(value_type = Object)]
+    pub from: Value,
+    #[schema(value_type = Object)]
+    pub to: Value,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    /// Fields that differ, by name; `updated_at` is left out
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RestoreRevisionRequest {
+    pub comment: Option<String>,
+}
+
+/// The entity's state at `version`, from its revisions in version order: 0 is
+/// the state before the first one. `None` if there is no such version;
+/// `Some(None)` if the entity did not exist then.
+pub fn state_at(revisions: &[Revision], version: i32) -> Option<Option<&Value>> {
+    match version {
+        0 => revisions.first().map(|first| first.before.as_ref()),
+        version => revisions
+            .iter()
+            .find(|revision| revision.version == version)
+            .map(|revision| revision.after.as_ref()),
+    }
+}
+
+/// Field-by-field differences between two states. A missing state counts as
+/// every field being null.
+pub fn diff(from: Option<&Value>, to: Option<&Value>) -> Vec<FieldChange> {
+    let fields = |state: Option<&Value>| state.and_then(Value::as_object).cloned().unwrap_or_default();
+    let (from, to) = (fields(from), fields(to));
+
+    let mut names: Vec<&String> = from.keys().chain(to.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter(|name| name.as_str() != "updated_at")
+        .filter_map(|name| {
+            let before = from.get(name).cloned().unwrap_or(Value::Null);
+            let after = to.get(name).cloned().unwrap_or(Value::Null);
+            (before != after).then(|| FieldChange {
+                field: name.clone(),
+                from: before,
+                to: after,
+            })
+        })
+        .collect()
+}
+
+impl Revision {
+    /// Append a revision as the entity's next version. Callers hold the
+    /// entity's row lock, which keeps versions of one entity in sequence.
+    pub async fn record<T: Serialize>(
+        conn: &mut PgConnection,
+        entity_type: EntityType,
+        entity_id: Uuid,
+        action: RevisionAction,
+        before: Option<&T>,
+        after: Option<&T>,
+        edit: &Edit,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO revisions (id, entity_type, entity_id, version, action, before, after, editor_id, comment)
+             VALUES (
+                 $1, $2, $3,
+                 COALESCE((SELECT MAX(version) FROM revisions WHERE entity_type = $2 AND entity_id = $3), 0) + 1,
+                 $4, $5, $6, $7, $8
+             )"
+        )
+        .bind(Uuid::new_v4())
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(action)
+        .bind(before.map(serde_json::to_value).transpose()?)
+        .bind(after.map(serde_json::to_value).transpose()?)
+        .bind(edit.editor_id)
+        .bind(&edit.comment)
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    /// An entity's revisions, oldest first.
+    pub async fn list(pool: &PgPool, entity_type: EntityType, entity_id: Uuid) -> Result<Vec<Revision>> {
+        let revisions = sqlx::query_as::<_, Revision>(
+            "SELECT id, entity_type, entity_id, version, action, before, after, editor_id, comment, created_at
+             FROM revisions
+             WHERE entity_type = $1 AND entity_id = $2
+             ORDER BY version"
+        )
+        .bind(entity_type)
+        .bind(entity_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(revisions)
+    }
+}
+
+// Edits that leave a revision: the row is locked, changed and its revision
+// appended in one transaction. Updates check `If-Match` against the locked row,
+// so of two editors that read the same version only the first one's applies;
+// the second fails with the row as locked. Creates insert and record the same way.
+impl Classic {
+    pub async fn create_recorded(pool: &PgPool, req: CreateClassicRequest, edit: &Edit) -> Result<Classic> {
+        let mut tx = pool.begin().await?;
+        let created = Classic::create(&mut *tx, req).await?;
+        Revision::record(&mut tx, EntityType::Classic, created.id, RevisionAction::Create, None, Some(&created), edit).await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    pub async fn update_recorded(
+        pool: &PgPool,
+        id: Uuid,
+        req: UpdateClassicRequest,
+        if_match: &IfMatch,
+        edit: &Edit,
+    ) -> Result<Option<Classic>> {
+        let mut tx = pool.begin().await?;
+        let Some(before) = Classic::lock(&mut *tx, id).await? else {
+            return Ok(None);
+        };
+        if !if_match.allows(&Validators::single(before.id, before.updated_at)) {
+            return Err(Stale(before).into());
+        }
+        let after = Classic::update(&mut *tx, id, req).await?;
+        if let Some(after) = &after {
+            Revision::record(&mut tx, EntityType::Classic, id, RevisionAction::Update, Some(&before), Some(after), edit).await?;
+        }
+        tx.commit().await?;
+        Ok(after)
+    }
+
+    pub async fn delete_recorded(pool: &PgPool, id: Uuid, edit: &Edit) -> Result<bool> {
+        let mut tx = pool.begin().await?;
+        let Some(before) = Classic::lock(&mut *tx, id).await? else {
+            return Ok(false);
+        };
+        Classic::delete(&mut tx, id).await?;
+        Revision::record(&mut tx, EntityType::Classic, id, RevisionAction::Delete, Some(&before), None, edit).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn restore_recorded(pool: &PgPool, id: Uuid, snapshot: &Classic, edit: &Edit) -> Result<Option<Classic>> {
+        let mut tx = pool.begin().await?;
+        let Some(before) = Classic::lock(&mut *tx, id).await? else {
+            return Ok(None);
+        };
+        let after = Classic::restore(&mut *tx, id, snapshot).await?;
+        if let Some(after) = &after {
+            Revision::record(&mut tx, EntityType::Classic, id, RevisionAction::Restore, Some(&before), Some(after), edit).await?;
+        }
+        tx.commit().await?;
+        Ok(after)
+    }
+}
+
+impl Chapter {
+    pub async fn create_recorded(pool: &PgPool, req: CreateChapterRequest, edit: &Edit) -> Result<Chapter> {
+        let mut tx = pool.begin().await?;
+        let created = Chapter::create(&mut *tx, req).await?;
+        Revision::record(&mut tx, EntityType::Chapter, created.id, RevisionAction::Create, None, Some(&created), edit).await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    pub async fn update_recorded(
+        pool: &PgPool,
+        id: Uuid,
+        req: UpdateChapterRequest,
+        if_match: &IfMatch,
+        edit: &Edit,
+    ) -> Result<Option<Chapter>> {
+        let mut tx = pool.begin().await?;
+        let Some(before) = Chapter::lock(&mut *tx, id).await? else {
+            return Ok(None);
+        };
+        if !if_match.allows(&Validators::single(before.id, before.updated_at)) {
+            return Err(Stale(before).into());
+        }
+        let resegment = req.resegment;
+        let after = Chapter::update(&mut *tx, id, req).await?;
+        if let Some(after) = &after {
+            Revision::record(&mut tx, EntityType::Chapter, id, RevisionAction::Update, Some(&before), Some(after), edit).await?;
+            if resegment {
+                let texts = segmentation::segment_content(after.content.as_deref())?;
+                Sentence::regenerate_in(&mut tx, id, texts, edit).await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(after)
+    }
+
+    pub async fn delete_recorded(pool: &PgPool, id: Uuid, edit: &Edit) -> Result<bool> {
+        let mut tx = pool.begin().await?;
+        let Some(before) = Chapter::lock(&mut *tx, id).await? else {
+            return Ok(false);
+        };
+        Chapter::delete(&mut tx, id).await?;
+        Revision::record(&mut tx, EntityType::Chapter, id, RevisionAction::Delete, Some(&before), None, edit).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn restore_recorded(pool: &PgPool, id: Uuid, snapshot: &Chapter, edit: &Edit) -> Result<Option<Chapter>> {
+        let mut tx = pool.begin().await?;
+        let Some(before) = Chapter::lock(&mut *tx, id).await? else {
+            return Ok(None);
+        };
+        let after = Chapter::restore(&mut *tx, id, snapshot).await?;
+        if let Some(after) = &after {
+            Revision::record(&mut tx, EntityType::Chapter, id, RevisionAction::Restore, Some(&before), Some(after), edit).await?;
+        }
+        tx.commit().await?;
+        Ok(after)
+    }
+}
+
+impl Sentence {
+    pub async fn create_recorded(pool: &PgPool, req: CreateSentenceRequest, edit: &Edit) -> Result<Sentence> {
+        let mut tx = pool.begin().await?;
+        let created = Sentence::create(&mut *tx, req).await?;
+        Revision::record(&mut tx, EntityType::Sentence, created.id, RevisionAction::Create, None, Some(&created), edit).await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    pub async fn update_recorded(
+        pool: &PgPool,
+        id: Uuid,
+        req: UpdateSentenceRequest,
+        if_match: &IfMatch,
+        edit: &Edit,
+    ) -> Result<Option<Sentence>> {
+        let mut tx = pool.begin().await?;
+        let Some(before) = Sentence::lock(&mut *tx, id).await? else {
+            return Ok(None);
+        };
+        if !if_match.allows(&Validators::single(before.id, before.updated_at)) {
+            return Err(Stale(before).into());
+        }
+        let after = Sentence::update(&mut *tx, id, req).await?;
+        if let Some(after) = &after {
+            Revision::record(&mut tx, EntityType::Sentence, id, RevisionAction::Update, Some(&before), Some(after), edit).await?;
+        }
+        tx.commit().await?;
+        Ok(after)
+    }
+
+    pub async fn delete_recorded(pool: &PgPool, id: Uuid, edit: &Edit) -> Result<bool> {
+        let mut tx = pool.begin().await?;
+        let Some(before) = Sentence::lock(&mut *tx, id).await? else {
+            return Ok(false);
+        };
+        Sentence::delete(&mut *tx, id).await?;
+        Revision::record(&mut tx, EntityType::Sentence, id, RevisionAction::Delete, Some(&before), None, edit).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn restore_recorded(pool: &PgPool, id: Uuid, snapshot: &Sentence, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tx = pool.begin().await?;
+        let Some(before) = Sentence::lock(&mut *tx, id).await? else {
+            return Ok(None);
+        };
+        let after = Sentence::restore(&mut *tx, id, snapshot).await?;
+        if let Some(after) = &after {
+            Revision::record(&mut tx, EntityType::Sentence, id, RevisionAction::Restore, Some(&before), Some(after), edit).await?;
+        }
+        tx.commit().await?;
+        Ok(after)
+    }
+}
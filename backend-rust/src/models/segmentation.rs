@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// One sentence the chapter content would be split into.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SegmentedSentence {
+    pub number: i32,
+    pub text: String,
+    /// Existing sentence with the same text, kept with its pinyin and translation
+    pub existing_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SegmentationPreview {
+    pub chapter_id: Uuid,
+    pub sentences: Vec<SegmentedSentence>,
+    /// Existing sentences matching no segment, which confirming would delete
+    pub removed: usize,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct SegmentChapterRequest {
+    /// Regenerate the sentences of a chapter that already has some
+    #[serde(default)]
+    pub replace: bool,
+}
@@ -1,9 +1,14 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{FromRow, PgExecutor, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
 use chrono::{DateTime, Utc};
 use anyhow::Result;
 
+use crate::models::PUBLIC_CLASSIC;
+use crate::utils::merge_patch;
+use crate::utils::pagination::{Page, PageRequest, Position, Sort, SortField};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Sentence {
     pub id: Uuid,
@@ -16,7 +21,7 @@ pub struct Sentence {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateSentenceRequest {
     pub chapter_id: Uuid,
     pub number: i32,
@@ -25,15 +30,27 @@ pub struct CreateSentenceRequest {
     pub translation: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// A JSON merge patch (RFC 7396): members left out are kept, `null` clears
+/// `pinyin` or `translation`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
 pub struct UpdateSentenceRequest {
+    #[serde(default, deserialize_with = "merge_patch::non_null")]
+    #[schema(nullable = false)]
     pub number: Option<i32>,
+    #[serde(default, deserialize_with = "merge_patch::non_null")]
+    #[schema(nullable = false)]
     pub text: Option<String>,
-    pub pinyin: Option<String>,
-    pub translation: Option<String>,
+    #[serde(default, deserialize_with = "merge_patch::nullable")]
+    #[schema(value_type = Option<String>)]
+    pub pinyin: Option<Option<String>>,
+    #[serde(default, deserialize_with = "merge_patch::nullable")]
+    #[schema(value_type = Option<String>)]
+    pub translation: Option<Option<String>>,
+    /// Why the change was made, kept with its revision
+    pub comment: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SentenceResponse {
     pub id: Uuid,
     pub chapter_id: Uuid,
@@ -60,23 +77,170 @@ impl From<Sentence> for SentenceResponse {
     }
 }
 
+/// Where a sentence sits in its classic's reading order: chapter number, then
+/// sentence number.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct SentenceNeighbors {
+    pub previous_id: Option<Uuid>,
+    pub next_id: Option<Uuid>,
+    /// 1-based ordinal within the whole classic
+    pub position: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChapterSummary {
+    pub id: Uuid,
+    pub number: i32,
+    pub title: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClassicSummary {
+    pub id: Uuid,
+    pub slug: String,
+    pub title: String,
+}
+
+/// A sentence with what a reader needs to move on from it.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SentenceContext {
+    pub sentence: SentenceResponse,
+    pub chapter: ChapterSummary,
+    pub classic: ClassicSummary,
+    /// Previous sentence in reading order, possibly in the previous chapter
+    pub previous_id: Option<Uuid>,
+    /// Next sentence in reading order, possibly in the next chapter
+    pub next_id: Option<Uuid>,
+    /// 1-based ordinal within the whole classic
+    pub position: i64,
+    /// Sentences in the whole classic
+    pub total: i64,
+}
+
+/// Query string of the sentence lists.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[serde(deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct SentenceListQuery {
+    /// 1-based page number
+    pub page: Option<i64>,
+    /// Items per page, 1–100 (default 20)
+    pub limit: Option<i64>,
+    /// `next_cursor` of the previous page; default order only
+    pub cursor: Option<String>,
+    /// `number` (default: by chapter, then sentence number) or `created_at`; prefix `-` for descending
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub sort: Sort<SentenceSortField>,
+    /// Only sentences of this chapter
+    pub chapter_id: Option<Uuid>,
+    /// Only sentences of this classic's chapters
+    pub classic_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SentenceSortField {
+    #[default]
+    Number,
+    CreatedAt,
+}
+
+impl SortField for SentenceSortField {
+    const FIELDS: &'static [(&'static str, Self)] = &[
+        ("number", Self::Number),
+        ("created_at", Self::CreatedAt),
+    ];
+
+    fn columns(self) -> &'static [&'static str] {
+        match self {
+            Self::Number => &["chapter_id", "number"],
+            Self::CreatedAt => &["created_at"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SentenceFilter {
+    pub chapter_id: Option<Uuid>,
+    pub classic_id: Option<Uuid>,
+    /// Leave out sentences of classics the public cannot see
+    pub published_only: bool,
+    pub sort: Sort<SentenceSortField>,
+}
+
+/// Keyset of the default order, unique per `UNIQUE(chapter_id, number)`.
+pub type SentenceKey = (Uuid, i32);
+
+impl SentenceListQuery {
+    pub fn into_parts(self) -> Result<(SentenceFilter, PageRequest<SentenceKey>), String> {
+        let page = PageRequest::parse(self.page, self.limit, self.cursor.as_deref(), self.sort.is_default())?;
+        let filter = SentenceFilter {
+            chapter_id: self.chapter_id,
+            classic_id: self.classic_id,
+            published_only: false,
+            sort: self.sort,
+        };
+        Ok((filter, page))
+    }
+}
+
+impl SentenceFilter {
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, Postgres>) {
+        if let Some(chapter_id) = self.chapter_id {
+            query.push(" AND chapter_id = ").push_bind(chapter_id);
+        }
+        if let Some(classic_id) = self.classic_id {
+            query
+                .push(" AND chapter_id IN (SELECT id FROM chapters WHERE classic_id = ")
+                .push_bind(classic_id)
+                .push(")");
+        }
+        if self.published_only {
+            query
+                .push(" AND chapter_id IN (SELECT id FROM chapters WHERE classic_id IN (SELECT id FROM classics WHERE ")
+                .push(PUBLIC_CLASSIC)
+                .push("))");
+        }
+    }
+}
+
 impl Sentence {
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<Sentence>> {
-        let sentences = sqlx::query_as::<_, Sentence>(
-            "SELECT id, chapter_id, number, text, pinyin, translation, created_at, updated_at 
-             FROM sentences 
-             ORDER BY chapter_id, number ASC"
-        )
-        .fetch_all(pool)
-        .await?;
-        Ok(sentences)
+    /// One page of sentences matching the filter.
+    pub async fn list(pool: &PgPool, filter: &SentenceFilter, page: &PageRequest<SentenceKey>) -> Result<Page<Sentence>> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, chapter_id, number, text, pinyin, translation, created_at, updated_at
+             FROM sentences
+             WHERE deleted_at IS NULL"
+        );
+        filter.push_conditions(&mut query);
+        if let Position::After((chapter_id, number)) = page.position {
+            query.push(" AND (chapter_id, number) > (").push_bind(chapter_id).push(", ").push_bind(number).push(")");
+        }
+        query.push(" ORDER BY ").push(filter.sort.order_by());
+        query.push(" LIMIT ").push_bind(page.fetch_limit());
+        if let Some(offset) = page.offset() {
+            query.push(" OFFSET ").push_bind(offset);
+        }
+        let sentences = query.build_query_as::<Sentence>().fetch_all(pool).await?;
+
+        let total = match page.offset() {
+            Some(_) => {
+                let mut count = QueryBuilder::new("SELECT COUNT(*) FROM sentences WHERE deleted_at IS NULL");
+                filter.push_conditions(&mut count);
+                Some(count.build_query_scalar::<i64>().fetch_one(pool).await?)
+            }
+            None => None,
+        };
+
+        Ok(page.finish(sentences, total, |s| (s.chapter_id, s.number)))
     }
 
     pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Sentence>> {
         let sentence = sqlx::query_as::<_, Sentence>(
             "SELECT id, chapter_id, number, text, pinyin, translation, created_at, updated_at 
              FROM sentences 
-             WHERE id = $1"
+             WHERE id = $1 AND deleted_at IS NULL"
         )
         .bind(id)
         .fetch_optional(pool)
@@ -84,11 +248,39 @@ impl Sentence {
         Ok(sentence)
     }
 
+    pub async fn neighbors(pool: &PgPool, id: Uuid) -> Result<Option<SentenceNeighbors>> {
+        let neighbors = sqlx::query_as::<_, SentenceNeighbors>(
+            "SELECT previous_id, next_id, position, total
+             FROM (
+                 SELECT s.id,
+                        LAG(s.id) OVER w AS previous_id,
+                        LEAD(s.id) OVER w AS next_id,
+                        ROW_NUMBER() OVER w AS position,
+                        COUNT(*) OVER () AS total
+                 FROM sentences s
+                 JOIN chapters c ON c.id = s.chapter_id
+                 WHERE s.deleted_at IS NULL
+                   AND c.classic_id = (
+                     SELECT c.classic_id
+                     FROM sentences s
+                     JOIN chapters c ON c.id = s.chapter_id
+                     WHERE s.id = $1 AND s.deleted_at IS NULL
+                 )
+                 WINDOW w AS (ORDER BY c.number, s.number)
+             ) ordered
+             WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(neighbors)
+    }
+
     pub async fn find_by_chapter_id(pool: &PgPool, chapter_id: Uuid) -> Result<Vec<Sentence>> {
         let sentences = sqlx::query_as::<_, Sentence>(
             "SELECT id, chapter_id, number, text, pinyin, translation, created_at, updated_at 
              FROM sentences 
-             WHERE chapter_id = $1 
+             WHERE chapter_id = $1 AND deleted_at IS NULL
              ORDER BY number ASC"
         )
         .bind(chapter_id)
@@ -97,7 +289,37 @@ impl Sentence {
         Ok(sentences)
     }
 
-    pub async fn create(pool: &PgPool, req: CreateSentenceRequest) -> Result<Sentence> {
+    /// Every sentence of a classic, in chapter order.
+    pub async fn find_by_classic_id(pool: &PgPool, classic_id: Uuid) -> Result<Vec<Sentence>> {
+        let sentences = sqlx::query_as::<_, Sentence>(
+            "SELECT s.id, s.chapter_id, s.number, s.text, s.pinyin, s.translation, s.created_at, s.updated_at
+             FROM sentences s
+             JOIN chapters c ON c.id = s.chapter_id
+             WHERE c.classic_id = $1 AND s.deleted_at IS NULL
+             ORDER BY c.number, s.number"
+        )
+        .bind(classic_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(sentences)
+    }
+
+    /// Sentence count per chapter of a classic. Chapters without sentences are omitted.
+    pub async fn count_by_classic_id(pool: &PgPool, classic_id: Uuid) -> Result<Vec<(Uuid, i64)>> {
+        let counts = sqlx::query_as::<_, (Uuid, i64)>(
+            "SELECT s.chapter_id, COUNT(*)
+             FROM sentences s
+             JOIN chapters c ON c.id = s.chapter_id
+             WHERE c.classic_id = $1 AND s.deleted_at IS NULL
+             GROUP BY s.chapter_id"
+        )
+        .bind(classic_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(counts)
+    }
+
+    pub async fn create(executor: impl PgExecutor<'_>, req: CreateSentenceRequest) -> Result<Sentence> {
         let id = Uuid::new_v4();
         let now = Utc::now();
         
@@ -114,41 +336,103 @@ impl Sentence {
         .bind(req.translation)
         .bind(now)
         .bind(now)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
         Ok(sentence)
     }
 
-    pub async fn update(pool: &PgPool, id: Uuid, req: UpdateSentenceRequest) -> Result<Option<Sentence>> {
+    /// Apply a merge patch; each nullable column is bound as whether it was
+    /// sent and what to set it to.
+    pub async fn update(executor: impl PgExecutor<'_>, id: Uuid, req: UpdateSentenceRequest) -> Result<Option<Sentence>> {
         let now = Utc::now();
         
         let sentence = sqlx::query_as::<_, Sentence>(
             "UPDATE sentences 
              SET number = COALESCE($2, number),
                  text = COALESCE($3, text),
-                 pinyin = COALESCE($4, pinyin),
-                 translation = COALESCE($5, translation),
-                 updated_at = $6
-             WHERE id = $1 
+                 pinyin = CASE WHEN $4 THEN $5 ELSE pinyin END,
+                 translation = CASE WHEN $6 THEN $7 ELSE translation END,
+                 updated_at = $8
+             WHERE id = $1 AND deleted_at IS NULL
              RETURNING id, chapter_id, number, text, pinyin, translation, created_at, updated_at"
         )
         .bind(id)
         .bind(req.number)
         .bind(req.text)
-        .bind(req.pinyin)
-        .bind(req.translation)
+        .bind(req.pinyin.is_some())
+        .bind(req.pinyin.flatten())
+        .bind(req.translation.is_some())
+        .bind(req.translation.flatten())
         .bind(now)
-        .fetch_optional(pool)
+        .fetch_optional(executor)
+        .await?;
+        Ok(sentence)
+    }
+
+    /// Insert a sentence, or update the one with the same number in the chapter.
+    pub async fn upsert(executor: impl PgExecutor<'_>, req: &CreateSentenceRequest) -> Result<Sentence> {
+        let sentence = sqlx::query_as::<_, Sentence>(
+            "INSERT INTO sentences (id, chapter_id, number, text, pinyin, translation)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (chapter_id, number) WHERE deleted_at IS NULL DO UPDATE
+             SET text = EXCLUDED.text,
+                 pinyin = EXCLUDED.pinyin,
+                 translation = EXCLUDED.translation,
+                 updated_at = NOW()
+             RETURNING id, chapter_id, number, text, pinyin, translation, created_at, updated_at"
+        )
+        .bind(Uuid::new_v4())
+        .bind(req.chapter_id)
+        .bind(req.number)
+        .bind(&req.text)
+        .bind(&req.pinyin)
+        .bind(&req.translation)
+        .fetch_one(executor)
+        .await?;
+        Ok(sentence)
+    }
+
+    /// Lock a sentence for the rest of the transaction and read it.
+    pub async fn lock(executor: impl PgExecutor<'_>, id: Uuid) -> Result<Option<Sentence>> {
+        let sentence = sqlx::query_as::<_, Sentence>(
+            "SELECT id, chapter_id, number, text, pinyin, translation, created_at, updated_at
+             FROM sentences
+             WHERE id = $1 AND deleted_at IS NULL
+             FOR UPDATE"
+        )
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+        Ok(sentence)
+    }
+
+    /// Set the text, pinyin and translation to those of an earlier state, nulls included.
+    /// Numbering is left alone.
+    pub async fn restore(executor: impl PgExecutor<'_>, id: Uuid, snapshot: &Sentence) -> Result<Option<Sentence>> {
+        let sentence = sqlx::query_as::<_, Sentence>(
+            "UPDATE sentences
+             SET text = $2,
+                 pinyin = $3,
+                 translation = $4
+             WHERE id = $1 AND deleted_at IS NULL
+             RETURNING id, chapter_id, number, text, pinyin, translation, created_at, updated_at"
+        )
+        .bind(id)
+        .bind(&snapshot.text)
+        .bind(&snapshot.pinyin)
+        .bind(&snapshot.translation)
+        .fetch_optional(executor)
         .await?;
         Ok(sentence)
     }
 
-    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool> {
+    /// Move a sentence to the trash. Progress that refers to it is kept.
+    pub async fn delete(executor: impl PgExecutor<'_>, id: Uuid) -> Result<bool> {
         let result = sqlx::query(
-            "DELETE FROM sentences WHERE id = $1"
+            "UPDATE sentences SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL"
         )
         .bind(id)
-        .execute(pool)
+        .execute(executor)
         .await?;
         Ok(result.rows_affected() > 0)
     }
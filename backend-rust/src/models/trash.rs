@@ -0,0 +1,246 @@
+//! The trash: deleted classics, chapters and sentences keep their rows with a
+//! `deleted_at` stamp until restored or purged. A delete stamps the live
+//! children with the same time, so restoring brings back exactly what went
+//! with it, and not children deleted on their own before.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{Chapter, Classic, Edit, EntityType, Revision, RevisionAction, Sentence};
+
+/// Something deleted on its own, rather than along with its classic or chapter.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct TrashItem {
+    pub entity_type: EntityType,
+    pub id: Uuid,
+    /// The classic's or chapter's title, or the sentence's text
+    pub label: String,
+    /// The chapter's classic or the sentence's chapter
+    pub parent_id: Option<Uuid>,
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct TrashQuery {
+    /// Only items of this kind
+    pub entity_type: Option<EntityType>,
+}
+
+impl TrashItem {
+    /// Items in the trash, most recently deleted first.
+    pub async fn list(pool: &PgPool, entity_type: Option<EntityType>) -> Result<Vec<TrashItem>> {
+        let items = sqlx::query_as::<_, TrashItem>(
+            "SELECT entity_type, id, label, parent_id, deleted_at
+             FROM (
+                 SELECT 'classic'::revision_entity AS entity_type, id, title AS label,
+                        NULL::uuid AS parent_id, deleted_at
+                 FROM classics
+                 WHERE deleted_at IS NOT NULL
+                 UNION ALL
+                 SELECT 'chapter', ch.id, ch.title, ch.classic_id, ch.deleted_at
+                 FROM chapters ch
+                 JOIN classics c ON c.id = ch.classic_id
+                 WHERE ch.deleted_at IS NOT NULL AND c.deleted_at IS DISTINCT FROM ch.deleted_at
+                 UNION ALL
+                 SELECT 'sentence', s.id, s.text, s.chapter_id, s.deleted_at
+                 FROM sentences s
+                 JOIN chapters ch ON ch.id = s.chapter_id
+                 WHERE s.deleted_at IS NOT NULL AND ch.deleted_at IS DISTINCT FROM s.deleted_at
+             ) trash
+             WHERE $1::revision_entity IS NULL OR entity_type = $1
+             ORDER BY deleted_at DESC, id"
+        )
+        .bind(entity_type)
+        .fetch_all(pool)
+        .await?;
+        Ok(items)
+    }
+}
+
+// Restores bring a row back with the children stamped at the same time, and
+// record a restore revision. The parent must be live and the row's number
+// free, since the unique indexes only cover live rows. `None` means the row is
+// not in the trash.
+impl Classic {
+    pub async fn undelete(pool: &PgPool, id: Uuid, edit: &Edit) -> Result<Option<Classic>> {
+        let mut tx = pool.begin().await?;
+        let deleted_at = sqlx::query_scalar::<_, DateTime<Utc>>(
+            "SELECT deleted_at FROM classics WHERE id = $1 AND deleted_at IS NOT NULL FOR UPDATE"
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(deleted_at) = deleted_at else {
+            return Ok(None);
+        };
+
+        // Parents first: live rows cannot sit under a deleted one
+        let classic = sqlx::query_as::<_, Classic>(
+            "UPDATE classics SET deleted_at = NULL
+             WHERE id = $1
+             RETURNING id, slug, title, author, dynasty, description, status, publish_at, unpublish_at,
+                       created_at, updated_at"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE chapters SET deleted_at = NULL WHERE classic_id = $1 AND deleted_at = $2")
+            .bind(id)
+            .bind(deleted_at)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "UPDATE sentences SET deleted_at = NULL
+             WHERE deleted_at = $2
+               AND chapter_id IN (SELECT id FROM chapters WHERE classic_id = $1 AND deleted_at IS NULL)"
+        )
+        .bind(id)
+        .bind(deleted_at)
+        .execute(&mut *tx)
+        .await?;
+
+        Revision::record(&mut tx, EntityType::Classic, id, RevisionAction::Restore, None, Some(&classic), edit).await?;
+        tx.commit().await?;
+        Ok(Some(classic))
+    }
+
+    /// Remove a classic in the trash for good, with its chapters, sentences
+    /// and the progress that refers to them; practice sessions are kept
+    /// without the reference. `false` if it is not in the trash.
+    pub async fn purge(pool: &PgPool, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM classics WHERE id = $1 AND deleted_at IS NOT NULL")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+impl Chapter {
+    pub async fn undelete(pool: &PgPool, id: Uuid, edit: &Edit) -> Result<Option<Chapter>> {
+        let mut tx = pool.begin().await?;
+        let deleted = sqlx::query_as::<_, (Uuid, i32, DateTime<Utc>)>(
+            "SELECT classic_id, number, deleted_at FROM chapters WHERE id = $1 AND deleted_at IS NOT NULL FOR UPDATE"
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((classic_id, number, deleted_at)) = deleted else {
+            return Ok(None);
+        };
+
+        let classic_live = sqlx::query_scalar::<_, i32>(
+            "SELECT 1 FROM classics WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"
+        )
+        .bind(classic_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if classic_live.is_none() {
+            return Err(AppError::Validation("所属经典已删除，请先恢复经典".to_string()).into());
+        }
+        let taken = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM chapters WHERE classic_id = $1 AND number = $2 AND deleted_at IS NULL)"
+        )
+        .bind(classic_id)
+        .bind(number)
+        .fetch_one(&mut *tx)
+        .await?;
+        if taken {
+            return Err(AppError::Validation(format!("章节编号 {} 已被占用，请先调整编号", number)).into());
+        }
+
+        let chapter = sqlx::query_as::<_, Chapter>(
+            "UPDATE chapters SET deleted_at = NULL
+             WHERE id = $1
+             RETURNING id, classic_id, number, title, content, created_at, updated_at"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE sentences SET deleted_at = NULL WHERE chapter_id = $1 AND deleted_at = $2")
+            .bind(id)
+            .bind(deleted_at)
+            .execute(&mut *tx)
+            .await?;
+
+        Revision::record(&mut tx, EntityType::Chapter, id, RevisionAction::Restore, None, Some(&chapter), edit).await?;
+        tx.commit().await?;
+        Ok(Some(chapter))
+    }
+
+    /// Remove a chapter in the trash for good, with its sentences and the
+    /// progress that refers to them; practice sessions are kept without the
+    /// reference. `false` if it is not in the trash.
+    pub async fn purge(pool: &PgPool, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM chapters WHERE id = $1 AND deleted_at IS NOT NULL")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+impl Sentence {
+    pub async fn undelete(pool: &PgPool, id: Uuid, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tx = pool.begin().await?;
+        let deleted = sqlx::query_as::<_, (Uuid, i32)>(
+            "SELECT chapter_id, number FROM sentences WHERE id = $1 AND deleted_at IS NOT NULL FOR UPDATE"
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((chapter_id, number)) = deleted else {
+            return Ok(None);
+        };
+
+        let chapter_live = sqlx::query_scalar::<_, i32>(
+            "SELECT 1 FROM chapters WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"
+        )
+        .bind(chapter_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if chapter_live.is_none() {
+            return Err(AppError::Validation("所属章节已删除，请先恢复章节".to_string()).into());
+        }
+        let taken = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM sentences WHERE chapter_id = $1 AND number = $2 AND deleted_at IS NULL)"
+        )
+        .bind(chapter_id)
+        .bind(number)
+        .fetch_one(&mut *tx)
+        .await?;
+        if taken {
+            return Err(AppError::Validation(format!("句子编号 {} 已被占用，请先调整编号", number)).into());
+        }
+
+        let sentence = sqlx::query_as::<_, Sentence>(
+            "UPDATE sentences SET deleted_at = NULL
+             WHERE id = $1
+             RETURNING id, chapter_id, number, text, pinyin, translation, created_at, updated_at"
+        )
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Revision::record(&mut tx, EntityType::Sentence, id, RevisionAction::Restore, None, Some(&sentence), edit).await?;
+        tx.commit().await?;
+        Ok(Some(sentence))
+    }
+
+    /// Remove a sentence in the trash for good, with the progress that refers
+    /// to it. `false` if it is not in the trash.
+    pub async fn purge(pool: &PgPool, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM sentences WHERE id = $1 AND deleted_at IS NOT NULL")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
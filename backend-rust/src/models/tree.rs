@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::models::{Classic, SentenceResponse};
+
+/// Query string of `GET /api/classics/{slug}/tree`.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[serde(deny_unknown_fields)]
+#[into_params(parameter_in = Query)]
+pub struct ClassicTreeQuery {
+    /// Comma-separated parts to embed in each chapter: `content` (the default) and `sentences`
+    pub include: Option<String>,
+    /// `toc` for chapter titles and sentence counts only; cannot be combined with `include`
+    #[serde(default)]
+    pub mode: TreeMode,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TreeMode {
+    #[default]
+    Full,
+    Toc,
+}
+
+/// What each chapter node carries besides its title and sentence count.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TreeInclude {
+    pub content: bool,
+    pub sentences: bool,
+}
+
+impl TreeInclude {
+    pub const TOC: Self = Self { content: false, sentences: false };
+    const FIELDS: &'static [&'static str] = &["content", "sentences"];
+}
+
+impl Default for TreeInclude {
+    fn default() -> Self {
+        Self { content: true, sentences: false }
+    }
+}
+
+impl ClassicTreeQuery {
+    pub fn include(&self) -> Result<TreeInclude, String> {
+        let include = match (self.mode, &self.include) {
+            (TreeMode::Toc, Some(_)) => return Err("toc 模式不能与 include 同时使用".to_string()),
+            (TreeMode::Toc, None) => return Ok(TreeInclude::TOC),
+            (TreeMode::Full, None) => return Ok(TreeInclude::default()),
+            (TreeMode::Full, Some(include)) => include,
+        };
+
+        let mut parts = TreeInclude::TOC;
+        for part in include.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            match part {
+                "content" => parts.content = true,
+                "sentences" => parts.sentences = true,
+                other => {
+                    return Err(format!(
+                        "不支持的 include 值 `{}`，可选: {}",
+                        other,
+                        TreeInclude::FIELDS.join(", ")
+                    ))
+                }
+            }
+        }
+        Ok(parts)
+    }
+}
+
+/// A classic with its chapters in order.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClassicTree {
+    pub classic: Classic,
+    pub chapters: Vec<ChapterNode>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChapterNode {
+    pub id: Uuid,
+    pub number: i32,
+    pub title: String,
+    /// Present with `include=content`; null when the chapter has no text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub content: Option<Option<String>>,
+    pub sentence_count: i64,
+    /// Present with `include=sentences`, in order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sentences: Option<Vec<SentenceResponse>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
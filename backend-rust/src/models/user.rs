@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgPool, Type};
 use uuid::Uuid;
+use utoipa::ToSchema;
 use chrono::{DateTime, Utc};
 use anyhow::Result;
 use bcrypt::{hash, verify, DEFAULT_COST};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
 #[sqlx(type_name = "user_role", rename_all = "lowercase")]
 pub enum UserRole {
     Parent,
@@ -13,7 +14,7 @@ pub enum UserRole {
     Child,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct User {
     pub id: Uuid,
     pub username: String,
@@ -28,7 +29,7 @@ pub struct User {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateUserRequest {
     pub username: String,
     pub email: String,
@@ -37,12 +38,21 @@ pub struct CreateUserRequest {
     pub role: Option<UserRole>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub email: String,
     pub password: String,
 }
 
+/// Returned by register (without a token) and login.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuthResponse {
+    pub user: User,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub message: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateUserRequest {
     pub username: Option<String>,
@@ -163,7 +173,7 @@ impl User {
         .bind(&req.username)
         .bind(&req.email)
         .bind(&req.phone)
-        .bind(&req.is_active)
+        .bind(req.is_active)
         .bind(now)
         .fetch_optional(pool)
         .await?;
@@ -171,6 +181,19 @@ impl User {
         Ok(user)
     }
 
+    pub async fn set_password(pool: &PgPool, id: Uuid, password: &str) -> Result<bool> {
+        let hashed_password = hash(password, DEFAULT_COST)?;
+
+        let result = sqlx::query("UPDATE users SET password = $1, updated_at = $2 WHERE id = $3")
+            .bind(&hashed_password)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool> {
         let result = sqlx::query("DELETE FROM users WHERE id = $1")
             .bind(id)
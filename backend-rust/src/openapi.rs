@@ -0,0 +1,143 @@
+//! OpenAPI document generated from the handler annotations and the
+//! request/response types, plus the bundled Swagger UI that renders it.
+
+use std::sync::Arc;
+
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::Config;
+use warp::{
+    Filter, Rejection, Reply,
+    http::{StatusCode, Uri, header::CONTENT_TYPE},
+    path::{FullPath, Tail},
+};
+
+use crate::handlers;
+
+const SPEC_PATH: &str = "/api/openapi.json";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "小小读书郎 API", description = "经典、章节、句子与账户接口"),
+    paths(
+        handlers::health::health_check,
+        handlers::classics::list_classics_handler,
+        handlers::classics::get_classic_handler,
+        handlers::classics::get_classic_tree_handler,
+        handlers::classics::create_classic_handler,
+        handlers::classics::update_classic_handler,
+        handlers::classics::delete_classic_handler,
+        handlers::classics::change_status_handler,
+        handlers::chapters::get_all_chapters_handler,
+        handlers::chapters::get_chapter_by_id_handler,
+        handlers::chapters::get_chapters_by_classic_handler,
+        handlers::chapters::create_chapter_handler,
+        handlers::chapters::update_chapter_handler,
+        handlers::chapters::delete_chapter_handler,
+        handlers::chapters::insert_chapter_handler,
+        handlers::chapters::renumber_chapters_handler,
+        handlers::chapters::move_chapter_handler,
+        handlers::chapters::preview_segmentation_handler,
+        handlers::chapters::segment_chapter_handler,
+        handlers::sentences::get_all_sentences_handler,
+        handlers::sentences::get_sentence_by_id_handler,
+        handlers::sentences::get_sentence_context_handler,
+        handlers::sentences::get_sentences_by_chapter_handler,
+        handlers::sentences::create_sentence_handler,
+        handlers::sentences::update_sentence_handler,
+        handlers::sentences::delete_sentence_handler,
+        handlers::sentences::insert_sentence_handler,
+        handlers::sentences::renumber_sentences_handler,
+        handlers::sentences::move_sentence_handler,
+        handlers::sentences::split_sentence_handler,
+        handlers::sentences::merge_sentence_handler,
+        handlers::revisions::list_revisions_handler,
+        handlers::revisions::diff_revisions_handler,
+        handlers::revisions::restore_revision_handler,
+        handlers::trash::list_trash_handler,
+        handlers::trash::restore_handler,
+        handlers::trash::purge_handler,
+        handlers::auth::register_handler,
+        handlers::auth::login_handler,
+        handlers::auth::logout_handler,
+        handlers::auth::me_handler,
+        handlers::characters::characters_handler,
+    ),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "health"),
+        (name = "classics", description = "经典"),
+        (name = "chapters", description = "章节"),
+        (name = "sentences", description = "句子"),
+        (name = "revisions", description = "修订记录与恢复"),
+        (name = "trash", description = "回收站"),
+        (name = "auth", description = "注册、登录与会话"),
+        (name = "characters", description = "汉字"),
+    )
+)]
+pub struct ApiDoc;
+
+/// The JWT issued by `/api/auth/login`, sent as `Authorization: Bearer <token>`.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+// GET /api/openapi.json and GET /api/docs
+pub fn routes() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    // Generated once; the document only changes with the code
+    let spec = Arc::new(ApiDoc::openapi());
+    let openapi_json = warp::path!("api" / "openapi.json")
+        .and(warp::get())
+        .map(move || warp::reply::json(spec.as_ref()));
+
+    let config = Arc::new(Config::from(SPEC_PATH));
+    let docs = warp::path("api")
+        .and(warp::path("docs"))
+        .and(warp::get())
+        .and(warp::path::full())
+        .and(warp::path::tail())
+        .and(warp::any().map(move || config.clone()))
+        .and_then(serve_docs);
+
+    openapi_json.or(docs)
+}
+
+async fn serve_docs(
+    full_path: FullPath,
+    tail: Tail,
+    config: Arc<Config<'static>>,
+) -> Result<warp::reply::Response, Rejection> {
+    // Swagger UI loads its assets relative to the page, so the page needs a trailing slash
+    if full_path.as_str() == "/api/docs" {
+        return Ok(warp::redirect::found(Uri::from_static("/api/docs/")).into_response());
+    }
+
+    match utoipa_swagger_ui::serve(tail.as_str(), config) {
+        Ok(Some(file)) => Ok(warp::reply::with_header(
+            file.bytes.into_owned(),
+            CONTENT_TYPE,
+            file.content_type,
+        )
+        .into_response()),
+        Ok(None) => Err(warp::reject::not_found()),
+        Err(e) => {
+            tracing::error!("加载 Swagger UI 失败: {}", e);
+            Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+}
@@ -0,0 +1,1250 @@
+use std::sync::RwLock;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bcrypt::hash;
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    errors::AppError,
+    models::{
+        check_transition, Audience, ChangeStatusRequest, Chapter, ChapterFilter, ChapterKey,
+        ChapterSortField, Classic, ClassicFilter, ClassicKey, ClassicSortField,
+        CreateChapterRequest, CreateClassicRequest, CreateSentenceRequest, CreateUserRequest, Edit,
+        EntityType, InsertChapterRequest, InsertSentenceRequest, MoveSentenceRequest,
+        PublicationStatus, Revision, RevisionAction, Sentence, SentenceFilter, SentenceKey,
+        SentenceNeighbors, SentenceSortField, TrashItem, UpdateChapterRequest,
+        UpdateClassicRequest, UpdateSentenceRequest, User, UserRole,
+        editing::{
+            insert_index, join_annotations, renumbering, requested_order, reuse_by_text, split_text,
+        },
+    },
+    services::segmentation,
+    utils::{
+        conditional::{IfMatch, Stale, Validators},
+        pagination::{Page, PageRequest, Position},
+    },
+};
+
+use super::{ChapterRepo, ClassicRepo, RevisionRepo, SentenceRepo, TrashRepo, UserRepo};
+
+/// bcrypt's minimum cost; the store only backs tests, where the default cost is just slow.
+const PASSWORD_COST: u32 = 4;
+
+/// Repositories held in process memory, for running the routes without Postgres.
+/// Enforces the same unique keys, foreign keys and cascades as the migrations,
+/// and fails with the same messages Postgres would use.
+#[derive(Default)]
+pub struct InMemoryRepo {
+    tables: RwLock<Tables>,
+}
+
+#[derive(Default)]
+struct Tables {
+    classics: Vec<Classic>,
+    chapters: Vec<Chapter>,
+    sentences: Vec<Sentence>,
+    users: Vec<User>,
+    revisions: Vec<Revision>,
+    /// Rows in the trash with their deletion time, kept out of the live tables
+    /// the way `deleted_at IS NULL` keeps them out of every query.
+    deleted_classics: Vec<(Classic, DateTime<Utc>)>,
+    deleted_chapters: Vec<(Chapter, DateTime<Utc>)>,
+    deleted_sentences: Vec<(Sentence, DateTime<Utc>)>,
+}
+
+fn unique_violation(constraint: &str) -> anyhow::Error {
+    anyhow!("duplicate key value violates unique constraint \"{}\"", constraint)
+}
+
+fn check_if_match<T>(if_match: &IfMatch, row: &T, id: Uuid, updated_at: DateTime<Utc>) -> Result<()>
+where
+    T: Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+    if if_match.allows(&Validators::single(id, updated_at)) {
+        Ok(())
+    } else {
+        Err(Stale(row.clone()).into())
+    }
+}
+
+fn foreign_key_violation(table: &str, constraint: &str) -> anyhow::Error {
+    anyhow!(
+        "insert or update on table \"{}\" violates foreign key constraint \"{}\"",
+        table,
+        constraint
+    )
+}
+
+/// Apply keyset, offset and limit to rows already filtered and sorted, the way
+/// the SQL in the models does.
+fn paginate<T, K>(rows: Vec<T>, page: &PageRequest<K>, key: impl Fn(&T) -> K) -> Page<T>
+where
+    K: PartialOrd + Serialize + DeserializeOwned,
+{
+    let total = rows.len() as i64;
+    let rows: Vec<T> = match &page.position {
+        Position::After(after) => rows.into_iter().filter(|row| key(row) > *after).collect(),
+        Position::Page(_) => rows,
+    };
+    let offset = page.offset().unwrap_or(0) as usize;
+    let rows = rows.into_iter().skip(offset).take(page.fetch_limit() as usize).collect();
+    let total = page.offset().map(|_| total);
+    page.finish(rows, total, key)
+}
+
+impl Tables {
+    /// Deleted classics keep their slug until purged.
+    fn slug_taken(&self, slug: &str) -> bool {
+        self.classics.iter().any(|c| c.slug == slug) || self.deleted_classics.iter().any(|(c, _)| c.slug == slug)
+    }
+
+    fn is_public_classic(&self, classic_id: Uuid, now: DateTime<Utc>) -> bool {
+        self.classics.iter().any(|c| c.id == classic_id && c.is_public(now))
+    }
+
+    /// Append a revision as the entity's next version.
+    fn record<T: Serialize>(
+        &mut self,
+        entity_type: EntityType,
+        entity_id: Uuid,
+        action: RevisionAction,
+        before: Option<&T>,
+        after: Option<&T>,
+        edit: &Edit,
+    ) -> Result<()> {
+        let version = self
+            .revisions
+            .iter()
+            .filter(|r| r.entity_type == entity_type && r.entity_id == entity_id)
+            .map(|r| r.version)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        self.revisions.push(Revision {
+            id: Uuid::new_v4(),
+            entity_type,
+            entity_id,
+            version,
+            action,
+            before: before.map(serde_json::to_value).transpose()?,
+            after: after.map(serde_json::to_value).transpose()?,
+            editor_id: edit.editor_id,
+            comment: edit.comment.clone(),
+            created_at: Utc::now(),
+        });
+        Ok(())
+    }
+
+    /// Move the live chapters matching `filter` to the trash, with their sentences.
+    fn delete_chapters(&mut self, filter: impl Fn(&Chapter) -> bool, at: DateTime<Utc>) {
+        let (deleted, live): (Vec<Chapter>, Vec<Chapter>) =
+            std::mem::take(&mut self.chapters).into_iter().partition(|c| filter(c));
+        self.chapters = live;
+        let ids: Vec<Uuid> = deleted.iter().map(|c| c.id).collect();
+        self.delete_sentences(|s| ids.contains(&s.chapter_id), at);
+        self.deleted_chapters.extend(deleted.into_iter().map(|c| (c, at)));
+    }
+
+    fn delete_sentences(&mut self, filter: impl Fn(&Sentence) -> bool, at: DateTime<Utc>) {
+        let (deleted, live): (Vec<Sentence>, Vec<Sentence>) =
+            std::mem::take(&mut self.sentences).into_iter().partition(|s| filter(s));
+        self.sentences = live;
+        self.deleted_sentences.extend(deleted.into_iter().map(|s| (s, at)));
+    }
+
+    /// Bring back the deleted chapters matching `filter`, with the sentences
+    /// deleted along with them.
+    fn undelete_chapters(&mut self, filter: impl Fn(&Chapter, DateTime<Utc>) -> bool) {
+        let (restored, deleted): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.deleted_chapters).into_iter().partition(|(c, at)| filter(c, *at));
+        self.deleted_chapters = deleted;
+        for (chapter, at) in restored {
+            let id = chapter.id;
+            self.chapters.push(chapter);
+            self.undelete_sentences(|s, deleted_at| s.chapter_id == id && deleted_at == at);
+        }
+    }
+
+    fn undelete_sentences(&mut self, filter: impl Fn(&Sentence, DateTime<Utc>) -> bool) {
+        let (restored, deleted): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.deleted_sentences).into_iter().partition(|(s, at)| filter(s, *at));
+        self.deleted_sentences = deleted;
+        self.sentences.extend(restored.into_iter().map(|(s, _)| s));
+    }
+
+    /// A chapter's sentences as `(id, number)`, in order.
+    fn sentence_numbers(&self, chapter_id: Uuid) -> Vec<(Uuid, i32)> {
+        let mut numbers: Vec<(Uuid, i32)> = self
+            .sentences
+            .iter()
+            .filter(|s| s.chapter_id == chapter_id)
+            .map(|s| (s.id, s.number))
+            .collect();
+        numbers.sort_by_key(|(_, number)| *number);
+        numbers
+    }
+
+    /// A classic's chapters as `(id, number)`, in order.
+    fn chapter_numbers(&self, classic_id: Uuid) -> Vec<(Uuid, i32)> {
+        let mut numbers: Vec<(Uuid, i32)> = self
+            .chapters
+            .iter()
+            .filter(|c| c.classic_id == classic_id)
+            .map(|c| (c.id, c.number))
+            .collect();
+        numbers.sort_by_key(|(_, number)| *number);
+        numbers
+    }
+
+    /// Apply `renumbering` output; the lock makes the intermediate states unobservable.
+    fn apply_sentence_numbers(&mut self, changes: &[(Uuid, i32)]) {
+        let now = Utc::now();
+        for (id, number) in changes {
+            if let Some(sentence) = self.sentences.iter_mut().find(|s| s.id == *id) {
+                sentence.number = *number;
+                sentence.updated_at = now;
+            }
+        }
+    }
+
+    fn apply_chapter_numbers(&mut self, changes: &[(Uuid, i32)]) {
+        let now = Utc::now();
+        for (id, number) in changes {
+            if let Some(chapter) = self.chapters.iter_mut().find(|c| c.id == *id) {
+                chapter.number = *number;
+                chapter.updated_at = now;
+            }
+        }
+    }
+
+    fn ordered_sentences(&self, chapter_id: Uuid) -> Vec<Sentence> {
+        let mut sentences: Vec<Sentence> =
+            self.sentences.iter().filter(|s| s.chapter_id == chapter_id).cloned().collect();
+        sentences.sort_by_key(|s| s.number);
+        sentences
+    }
+
+    /// Replace a chapter's sentences with `texts`; see `SentenceRepo::regenerate`.
+    fn regenerate(&mut self, chapter_id: Uuid, texts: Vec<String>, edit: &Edit) -> Result<Option<Vec<Sentence>>> {
+        if !self.chapters.iter().any(|c| c.id == chapter_id) {
+            return Ok(None);
+        }
+
+        let existing: Vec<(Uuid, String)> = self
+            .ordered_sentences(chapter_id)
+            .into_iter()
+            .map(|s| (s.id, s.text))
+            .collect();
+        let reused = reuse_by_text(&existing, &texts);
+        let removed: Vec<Sentence> = self
+            .sentences
+            .iter()
+            .filter(|s| s.chapter_id == chapter_id && !reused.contains(&Some(s.id)))
+            .cloned()
+            .collect();
+        self.delete_sentences(|s| removed.iter().any(|r| r.id == s.id), Utc::now());
+        for sentence in &removed {
+            self.record(EntityType::Sentence, sentence.id, RevisionAction::Delete, Some(sentence), None, edit)?;
+        }
+
+        let current = self.sentence_numbers(chapter_id);
+        let order: Vec<Uuid> = reused.iter().map(|id| id.unwrap_or_else(Uuid::new_v4)).collect();
+        self.apply_sentence_numbers(&renumbering(&current, &order));
+        let now = Utc::now();
+        for (index, text) in texts.into_iter().enumerate() {
+            if reused[index].is_none() {
+                let created = Sentence {
+                    id: order[index],
+                    chapter_id,
+                    number: index as i32 + 1,
+                    text,
+                    pinyin: None,
+                    translation: None,
+                    created_at: now,
+                    updated_at: now,
+                };
+                self.record(EntityType::Sentence, created.id, RevisionAction::Create, None, Some(&created), edit)?;
+                self.sentences.push(created);
+            }
+        }
+        Ok(Some(self.ordered_sentences(chapter_id)))
+    }
+}
+
+fn ids(numbers: &[(Uuid, i32)]) -> Vec<Uuid> {
+    numbers.iter().map(|(id, _)| *id).collect()
+}
+
+impl InMemoryRepo {
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Tables> {
+        self.tables.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Tables> {
+        self.tables.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl ClassicRepo for InMemoryRepo {
+    async fn list(&self, filter: &ClassicFilter, page: &PageRequest<ClassicKey>) -> Result<Page<Classic>> {
+        let now = Utc::now();
+        let mut classics: Vec<Classic> = self
+            .read()
+            .classics
+            .iter()
+            .filter(|c| filter.dynasty.is_none() || c.dynasty == filter.dynasty)
+            .filter(|c| filter.author.is_none() || c.author == filter.author)
+            .filter(|c| filter.status.is_none_or(|status| c.status == status))
+            .filter(|c| !filter.published_only || c.is_public(now))
+            .cloned()
+            .collect();
+
+        let sort = filter.sort;
+        classics.sort_by(|a, b| {
+            let ordering = match sort.field {
+                ClassicSortField::CreatedAt => sort.direct(a.created_at.cmp(&b.created_at)),
+                ClassicSortField::Title => sort.direct(a.title.cmp(&b.title)),
+                ClassicSortField::Dynasty => sort.compare_nullable(&a.dynasty, &b.dynasty),
+                ClassicSortField::Author => sort.compare_nullable(&a.author, &b.author),
+            };
+            ordering.then_with(|| sort.direct(a.id.cmp(&b.id)))
+        });
+
+        Ok(paginate(classics, page, |c| (c.created_at, c.id)))
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Classic>> {
+        Ok(self.read().classics.iter().find(|c| c.id == id).cloned())
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<Classic>> {
+        Ok(self.read().classics.iter().find(|c| c.slug == slug).cloned())
+    }
+
+    async fn slug_exists(&self, slug: &str) -> Result<bool> {
+        Ok(self.read().slug_taken(slug))
+    }
+
+    async fn create(&self, req: CreateClassicRequest, edit: &Edit) -> Result<Classic> {
+        let mut tables = self.write();
+        if tables.slug_taken(&req.slug) {
+            return Err(unique_violation("classics_slug_key"));
+        }
+
+        let now = Utc::now();
+        let classic = Classic {
+            id: Uuid::new_v4(),
+            slug: req.slug,
+            title: req.title,
+            author: req.author,
+            dynasty: req.dynasty,
+            description: req.description,
+            status: PublicationStatus::default(),
+            publish_at: None,
+            unpublish_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        tables.classics.push(classic.clone());
+        tables.record(EntityType::Classic, classic.id, RevisionAction::Create, None, Some(&classic), edit)?;
+        Ok(classic)
+    }
+
+    async fn update(&self, id: Uuid, req: UpdateClassicRequest, if_match: &IfMatch, edit: &Edit) -> Result<Option<Classic>> {
+        let mut tables = self.write();
+        let Some(classic) = tables.classics.iter_mut().find(|c| c.id == id) else {
+            return Ok(None);
+        };
+        check_if_match(if_match, classic, classic.id, classic.updated_at)?;
+        let before = classic.clone();
+
+        if let Some(title) = req.title {
+            classic.title = title;
+        }
+        if let Some(author) = req.author {
+            classic.author = author;
+        }
+        if let Some(dynasty) = req.dynasty {
+            classic.dynasty = dynasty;
+        }
+        if let Some(description) = req.description {
+            classic.description = description;
+        }
+        classic.updated_at = Utc::now();
+        let after = classic.clone();
+        tables.record(EntityType::Classic, id, RevisionAction::Update, Some(&before), Some(&after), edit)?;
+        Ok(Some(after))
+    }
+
+    async fn restore(&self, id: Uuid, snapshot: &Classic, edit: &Edit) -> Result<Option<Classic>> {
+        let mut tables = self.write();
+        let Some(classic) = tables.classics.iter_mut().find(|c| c.id == id) else {
+            return Ok(None);
+        };
+        let before = classic.clone();
+        classic.title = snapshot.title.clone();
+        classic.author = snapshot.author.clone();
+        classic.dynasty = snapshot.dynasty.clone();
+        classic.description = snapshot.description.clone();
+        classic.updated_at = Utc::now();
+        let after = classic.clone();
+        tables.record(EntityType::Classic, id, RevisionAction::Restore, Some(&before), Some(&after), edit)?;
+        Ok(Some(after))
+    }
+
+    async fn change_status(
+        &self,
+        id: Uuid,
+        req: &ChangeStatusRequest,
+        audience: Audience,
+        edit: &Edit,
+    ) -> Result<Option<Classic>> {
+        let mut tables = self.write();
+        let Some(classic) = tables.classics.iter_mut().find(|c| c.id == id) else {
+            return Ok(None);
+        };
+        check_transition(classic.status, req, audience)?;
+
+        let before = classic.clone();
+        classic.status = req.status;
+        classic.publish_at = req.publish_at;
+        classic.unpublish_at = req.unpublish_at;
+        classic.updated_at = Utc::now();
+        let after = classic.clone();
+        tables.record(EntityType::Classic, id, RevisionAction::Update, Some(&before), Some(&after), edit)?;
+        Ok(Some(after))
+    }
+
+    async fn next_scheduled_change(&self) -> Result<Option<DateTime<Utc>>> {
+        let now = Utc::now();
+        Ok(self
+            .read()
+            .classics
+            .iter()
+            .filter(|c| c.status == PublicationStatus::Published)
+            .flat_map(|c| [c.publish_at, c.unpublish_at])
+            .flatten()
+            .filter(|at| *at > now)
+            .min())
+    }
+
+    async fn delete(&self, id: Uuid, edit: &Edit) -> Result<bool> {
+        let mut tables = self.write();
+        let Some(index) = tables.classics.iter().position(|c| c.id == id) else {
+            return Ok(false);
+        };
+        let before = tables.classics.remove(index);
+        tables.record(EntityType::Classic, id, RevisionAction::Delete, Some(&before), None, edit)?;
+
+        let now = Utc::now();
+        tables.delete_chapters(|c| c.classic_id == id, now);
+        tables.deleted_classics.push((before, now));
+        Ok(true)
+    }
+
+    async fn undelete(&self, id: Uuid, edit: &Edit) -> Result<Option<Classic>> {
+        let mut tables = self.write();
+        let Some(index) = tables.deleted_classics.iter().position(|(c, _)| c.id == id) else {
+            return Ok(None);
+        };
+        let (classic, at) = tables.deleted_classics.remove(index);
+        tables.classics.push(classic.clone());
+        tables.undelete_chapters(|c, deleted_at| c.classic_id == id && deleted_at == at);
+        tables.record(EntityType::Classic, id, RevisionAction::Restore, None, Some(&classic), edit)?;
+        Ok(Some(classic))
+    }
+
+    async fn purge(&self, id: Uuid) -> Result<bool> {
+        let mut tables = self.write();
+        let Some(index) = tables.deleted_classics.iter().position(|(c, _)| c.id == id) else {
+            return Ok(false);
+        };
+        tables.deleted_classics.remove(index);
+
+        // ON DELETE CASCADE down to sentences, all of them in the trash too
+        let chapter_ids: Vec<Uuid> = tables
+            .deleted_chapters
+            .iter()
+            .filter(|(c, _)| c.classic_id == id)
+            .map(|(c, _)| c.id)
+            .collect();
+        tables.deleted_chapters.retain(|(c, _)| c.classic_id != id);
+        tables.deleted_sentences.retain(|(s, _)| !chapter_ids.contains(&s.chapter_id));
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl ChapterRepo for InMemoryRepo {
+    async fn list(&self, filter: &ChapterFilter, page: &PageRequest<ChapterKey>) -> Result<Page<Chapter>> {
+        let tables = self.read();
+        let now = Utc::now();
+        let mut chapters: Vec<Chapter> = tables
+            .chapters
+            .iter()
+            .filter(|c| filter.classic_id.is_none_or(|id| c.classic_id == id))
+            .filter(|c| !filter.published_only || tables.is_public_classic(c.classic_id, now))
+            .cloned()
+            .collect();
+        drop(tables);
+
+        let sort = filter.sort;
+        chapters.sort_by(|a, b| {
+            let ordering = match sort.field {
+                ChapterSortField::Number => sort.direct((a.classic_id, a.number).cmp(&(b.classic_id, b.number))),
+                ChapterSortField::Title => sort.direct(a.title.cmp(&b.title)),
+                ChapterSortField::CreatedAt => sort.direct(a.created_at.cmp(&b.created_at)),
+            };
+            ordering.then_with(|| sort.direct(a.id.cmp(&b.id)))
+        });
+
+        Ok(paginate(chapters, page, |c| (c.classic_id, c.number)))
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Chapter>> {
+        Ok(self.read().chapters.iter().find(|c| c.id == id).cloned())
+    }
+
+    async fn find_by_classic_id(&self, classic_id: Uuid) -> Result<Vec<Chapter>> {
+        let mut chapters: Vec<Chapter> = self
+            .read()
+            .chapters
+            .iter()
+            .filter(|c| c.classic_id == classic_id)
+            .cloned()
+            .collect();
+        chapters.sort_by_key(|c| c.number);
+        Ok(chapters)
+    }
+
+    async fn create(&self, req: CreateChapterRequest, edit: &Edit) -> Result<Chapter> {
+        let mut tables = self.write();
+        if !tables.classics.iter().any(|c| c.id == req.classic_id) {
+            return Err(foreign_key_violation("chapters", "chapters_classic_id_fkey"));
+        }
+        if tables
+            .chapters
+            .iter()
+            .any(|c| c.classic_id == req.classic_id && c.number == req.number)
+        {
+            return Err(unique_violation("chapters_classic_id_number_key"));
+        }
+
+        let now = Utc::now();
+        let chapter = Chapter {
+            id: Uuid::new_v4(),
+            classic_id: req.classic_id,
+            number: req.number,
+            title: req.title,
+            content: req.content,
+            created_at: now,
+            updated_at: now,
+        };
+        tables.chapters.push(chapter.clone());
+        tables.record(EntityType::Chapter, chapter.id, RevisionAction::Create, None, Some(&chapter), edit)?;
+        Ok(chapter)
+    }
+
+    async fn update(&self, id: Uuid, req: UpdateChapterRequest, if_match: &IfMatch, edit: &Edit) -> Result<Option<Chapter>> {
+        let mut tables = self.write();
+        let Some(chapter) = tables.chapters.iter().find(|c| c.id == id) else {
+            return Ok(None);
+        };
+        check_if_match(if_match, chapter, chapter.id, chapter.updated_at)?;
+        let classic_id = chapter.classic_id;
+        if let Some(number) = req.number {
+            if tables
+                .chapters
+                .iter()
+                .any(|c| c.id != id && c.classic_id == classic_id && c.number == number)
+            {
+                return Err(unique_violation("chapters_classic_id_number_key"));
+            }
+        }
+
+        // Segment before changing anything, so a failure leaves the chapter as it was
+        let texts = if req.resegment {
+            let content = req.content.clone().unwrap_or_else(|| chapter.content.clone());
+            Some(segmentation::segment_content(content.as_deref())?)
+        } else {
+            None
+        };
+
+        let chapter = tables
+            .chapters
+            .iter_mut()
+            .find(|c| c.id == id)
+            .expect("chapter found above");
+        let before = chapter.clone();
+        if let Some(number) = req.number {
+            chapter.number = number;
+        }
+        if let Some(title) = req.title {
+            chapter.title = title;
+        }
+        if let Some(content) = req.content {
+            chapter.content = content;
+        }
+        chapter.updated_at = Utc::now();
+        let after = chapter.clone();
+        tables.record(EntityType::Chapter, id, RevisionAction::Update, Some(&before), Some(&after), edit)?;
+        if let Some(texts) = texts {
+            tables.regenerate(id, texts, edit)?;
+        }
+        Ok(Some(after))
+    }
+
+    async fn restore(&self, id: Uuid, snapshot: &Chapter, edit: &Edit) -> Result<Option<Chapter>> {
+        let mut tables = self.write();
+        let Some(chapter) = tables.chapters.iter_mut().find(|c| c.id == id) else {
+            return Ok(None);
+        };
+        let before = chapter.clone();
+        chapter.title = snapshot.title.clone();
+        chapter.content = snapshot.content.clone();
+        chapter.updated_at = Utc::now();
+        let after = chapter.clone();
+        tables.record(EntityType::Chapter, id, RevisionAction::Restore, Some(&before), Some(&after), edit)?;
+        Ok(Some(after))
+    }
+
+    async fn delete(&self, id: Uuid, edit: &Edit) -> Result<bool> {
+        let mut tables = self.write();
+        let Some(index) = tables.chapters.iter().position(|c| c.id == id) else {
+            return Ok(false);
+        };
+        let before = tables.chapters.remove(index);
+        tables.record(EntityType::Chapter, id, RevisionAction::Delete, Some(&before), None, edit)?;
+
+        let now = Utc::now();
+        tables.delete_sentences(|s| s.chapter_id == id, now);
+        tables.deleted_chapters.push((before, now));
+        Ok(true)
+    }
+
+    async fn undelete(&self, id: Uuid, edit: &Edit) -> Result<Option<Chapter>> {
+        let mut tables = self.write();
+        let Some((chapter, _)) = tables.deleted_chapters.iter().find(|(c, _)| c.id == id).cloned() else {
+            return Ok(None);
+        };
+        if !tables.classics.iter().any(|c| c.id == chapter.classic_id) {
+            return Err(AppError::Validation("所属经典已删除，请先恢复经典".to_string()).into());
+        }
+        if tables
+            .chapters
+            .iter()
+            .any(|c| c.classic_id == chapter.classic_id && c.number == chapter.number)
+        {
+            return Err(AppError::Validation(format!("章节编号 {} 已被占用，请先调整编号", chapter.number)).into());
+        }
+
+        tables.undelete_chapters(|c, _| c.id == id);
+        tables.record(EntityType::Chapter, id, RevisionAction::Restore, None, Some(&chapter), edit)?;
+        Ok(Some(chapter))
+    }
+
+    async fn purge(&self, id: Uuid) -> Result<bool> {
+        let mut tables = self.write();
+        let Some(index) = tables.deleted_chapters.iter().position(|(c, _)| c.id == id) else {
+            return Ok(false);
+        };
+        tables.deleted_chapters.remove(index);
+        tables.deleted_sentences.retain(|(s, _)| s.chapter_id != id);
+        Ok(true)
+    }
+
+    async fn insert_at(&self, classic_id: Uuid, req: InsertChapterRequest, edit: &Edit) -> Result<Option<Chapter>> {
+        let mut tables = self.write();
+        if !tables.classics.iter().any(|c| c.id == classic_id) {
+            return Ok(None);
+        }
+
+        let current = tables.chapter_numbers(classic_id);
+        let index = insert_index(Some(req.position), current.len())?;
+        let id = Uuid::new_v4();
+        let mut order = ids(&current);
+        order.insert(index, id);
+        tables.apply_chapter_numbers(&renumbering(&current, &order));
+
+        let now = Utc::now();
+        let chapter = Chapter {
+            id,
+            classic_id,
+            number: index as i32 + 1,
+            title: req.title,
+            content: req.content,
+            created_at: now,
+            updated_at: now,
+        };
+        tables.chapters.push(chapter.clone());
+        tables.record(EntityType::Chapter, id, RevisionAction::Create, None, Some(&chapter), edit)?;
+        Ok(Some(chapter))
+    }
+
+    async fn move_to(&self, id: Uuid, position: Option<i32>, edit: &Edit) -> Result<Option<Chapter>> {
+        let mut tables = self.write();
+        let Some(before) = tables.chapters.iter().find(|c| c.id == id).cloned() else {
+            return Ok(None);
+        };
+
+        let current = tables.chapter_numbers(before.classic_id);
+        let index = insert_index(position, current.len() - 1)?;
+        let mut order: Vec<Uuid> = ids(&current).into_iter().filter(|other| *other != id).collect();
+        order.insert(index, id);
+        tables.apply_chapter_numbers(&renumbering(&current, &order));
+        let Some(after) = tables.chapters.iter().find(|c| c.id == id).cloned() else {
+            return Ok(None);
+        };
+        if after.number != before.number {
+            tables.record(EntityType::Chapter, id, RevisionAction::Update, Some(&before), Some(&after), edit)?;
+        }
+        Ok(Some(after))
+    }
+
+    async fn renumber(&self, classic_id: Uuid, order: Option<Vec<Uuid>>, edit: &Edit) -> Result<Option<Vec<Chapter>>> {
+        let mut tables = self.write();
+        if !tables.classics.iter().any(|c| c.id == classic_id) {
+            return Ok(None);
+        }
+
+        let before: Vec<Chapter> = tables.chapters.iter().filter(|c| c.classic_id == classic_id).cloned().collect();
+        let current = tables.chapter_numbers(classic_id);
+        let order = requested_order(&ids(&current), order)?;
+        tables.apply_chapter_numbers(&renumbering(&current, &order));
+
+        let mut chapters: Vec<Chapter> =
+            tables.chapters.iter().filter(|c| c.classic_id == classic_id).cloned().collect();
+        chapters.sort_by_key(|c| c.number);
+        for after in &chapters {
+            if let Some(before) = before.iter().find(|b| b.id == after.id && b.number != after.number) {
+                tables.record(EntityType::Chapter, after.id, RevisionAction::Update, Some(before), Some(after), edit)?;
+            }
+        }
+        Ok(Some(chapters))
+    }
+}
+
+#[async_trait]
+impl SentenceRepo for InMemoryRepo {
+    async fn list(&self, filter: &SentenceFilter, page: &PageRequest<SentenceKey>) -> Result<Page<Sentence>> {
+        let tables = self.read();
+        let now = Utc::now();
+        let mut sentences: Vec<Sentence> = tables
+            .sentences
+            .iter()
+            .filter(|s| filter.chapter_id.is_none_or(|id| s.chapter_id == id))
+            .filter(|s| {
+                filter.classic_id.is_none_or(|classic_id| {
+                    tables.chapters.iter().any(|c| c.id == s.chapter_id && c.classic_id == classic_id)
+                })
+            })
+            .filter(|s| {
+                !filter.published_only
+                    || tables
+                        .chapters
+                        .iter()
+                        .any(|c| c.id == s.chapter_id && tables.is_public_classic(c.classic_id, now))
+            })
+            .cloned()
+            .collect();
+        drop(tables);
+
+        let sort = filter.sort;
+        sentences.sort_by(|a, b| {
+            let ordering = match sort.field {
+                SentenceSortField::Number => sort.direct((a.chapter_id, a.number).cmp(&(b.chapter_id, b.number))),
+                SentenceSortField::CreatedAt => sort.direct(a.created_at.cmp(&b.created_at)),
+            };
+            ordering.then_with(|| sort.direct(a.id.cmp(&b.id)))
+        });
+
+        Ok(paginate(sentences, page, |s| (s.chapter_id, s.number)))
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Sentence>> {
+        Ok(self.read().sentences.iter().find(|s| s.id == id).cloned())
+    }
+
+    async fn find_by_chapter_id(&self, chapter_id: Uuid) -> Result<Vec<Sentence>> {
+        let mut sentences: Vec<Sentence> = self
+            .read()
+            .sentences
+            .iter()
+            .filter(|s| s.chapter_id == chapter_id)
+            .cloned()
+            .collect();
+        sentences.sort_by_key(|s| s.number);
+        Ok(sentences)
+    }
+
+    async fn find_by_classic_id(&self, classic_id: Uuid) -> Result<Vec<Sentence>> {
+        let tables = self.read();
+        let mut sentences: Vec<(i32, Sentence)> = tables
+            .sentences
+            .iter()
+            .filter_map(|s| {
+                tables
+                    .chapters
+                    .iter()
+                    .find(|c| c.id == s.chapter_id && c.classic_id == classic_id)
+                    .map(|c| (c.number, s.clone()))
+            })
+            .collect();
+        sentences.sort_by_key(|(chapter_number, s)| (*chapter_number, s.number));
+        Ok(sentences.into_iter().map(|(_, s)| s).collect())
+    }
+
+    async fn count_by_classic_id(&self, classic_id: Uuid) -> Result<Vec<(Uuid, i64)>> {
+        let tables = self.read();
+        let counts = tables
+            .chapters
+            .iter()
+            .filter(|c| c.classic_id == classic_id)
+            .map(|c| (c.id, tables.sentences.iter().filter(|s| s.chapter_id == c.id).count() as i64))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        Ok(counts)
+    }
+
+    async fn neighbors(&self, id: Uuid) -> Result<Option<SentenceNeighbors>> {
+        let classic_id = {
+            let tables = self.read();
+            let chapter_id = tables.sentences.iter().find(|s| s.id == id).map(|s| s.chapter_id);
+            tables
+                .chapters
+                .iter()
+                .find(|c| Some(c.id) == chapter_id)
+                .map(|c| c.classic_id)
+        };
+        let Some(classic_id) = classic_id else {
+            return Ok(None);
+        };
+
+        let ordered: Vec<Uuid> = SentenceRepo::find_by_classic_id(self, classic_id)
+            .await?
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        Ok(ordered.iter().position(|s| *s == id).map(|index| SentenceNeighbors {
+            previous_id: index.checked_sub(1).map(|previous| ordered[previous]),
+            next_id: ordered.get(index + 1).copied(),
+            position: index as i64 + 1,
+            total: ordered.len() as i64,
+        }))
+    }
+
+    async fn create(&self, req: CreateSentenceRequest, edit: &Edit) -> Result<Sentence> {
+        let mut tables = self.write();
+        if !tables.chapters.iter().any(|c| c.id == req.chapter_id) {
+            return Err(foreign_key_violation("sentences", "sentences_chapter_id_fkey"));
+        }
+        if tables
+            .sentences
+            .iter()
+            .any(|s| s.chapter_id == req.chapter_id && s.number == req.number)
+        {
+            return Err(unique_violation("sentences_chapter_id_number_key"));
+        }
+
+        let now = Utc::now();
+        let sentence = Sentence {
+            id: Uuid::new_v4(),
+            chapter_id: req.chapter_id,
+            number: req.number,
+            text: req.text,
+            pinyin: req.pinyin,
+            translation: req.translation,
+            created_at: now,
+            updated_at: now,
+        };
+        tables.sentences.push(sentence.clone());
+        tables.record(EntityType::Sentence, sentence.id, RevisionAction::Create, None, Some(&sentence), edit)?;
+        Ok(sentence)
+    }
+
+    async fn update(&self, id: Uuid, req: UpdateSentenceRequest, if_match: &IfMatch, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tables = self.write();
+        let Some(sentence) = tables.sentences.iter().find(|s| s.id == id) else {
+            return Ok(None);
+        };
+        check_if_match(if_match, sentence, sentence.id, sentence.updated_at)?;
+        let chapter_id = sentence.chapter_id;
+        if let Some(number) = req.number {
+            if tables
+                .sentences
+                .iter()
+                .any(|s| s.id != id && s.chapter_id == chapter_id && s.number == number)
+            {
+                return Err(unique_violation("sentences_chapter_id_number_key"));
+            }
+        }
+
+        let sentence = tables
+            .sentences
+            .iter_mut()
+            .find(|s| s.id == id)
+            .expect("sentence found above");
+        let before = sentence.clone();
+        if let Some(number) = req.number {
+            sentence.number = number;
+        }
+        if let Some(text) = req.text {
+            sentence.text = text;
+        }
+        if let Some(pinyin) = req.pinyin {
+            sentence.pinyin = pinyin;
+        }
+        if let Some(translation) = req.translation {
+            sentence.translation = translation;
+        }
+        sentence.updated_at = Utc::now();
+        let after = sentence.clone();
+        tables.record(EntityType::Sentence, id, RevisionAction::Update, Some(&before), Some(&after), edit)?;
+        Ok(Some(after))
+    }
+
+    async fn restore(&self, id: Uuid, snapshot: &Sentence, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tables = self.write();
+        let Some(sentence) = tables.sentences.iter_mut().find(|s| s.id == id) else {
+            return Ok(None);
+        };
+        let before = sentence.clone();
+        sentence.text = snapshot.text.clone();
+        sentence.pinyin = snapshot.pinyin.clone();
+        sentence.translation = snapshot.translation.clone();
+        sentence.updated_at = Utc::now();
+        let after = sentence.clone();
+        tables.record(EntityType::Sentence, id, RevisionAction::Restore, Some(&before), Some(&after), edit)?;
+        Ok(Some(after))
+    }
+
+    async fn delete(&self, id: Uuid, edit: &Edit) -> Result<bool> {
+        let mut tables = self.write();
+        let Some(index) = tables.sentences.iter().position(|s| s.id == id) else {
+            return Ok(false);
+        };
+        let before = tables.sentences.remove(index);
+        tables.record(EntityType::Sentence, id, RevisionAction::Delete, Some(&before), None, edit)?;
+        tables.deleted_sentences.push((before, Utc::now()));
+        Ok(true)
+    }
+
+    async fn undelete(&self, id: Uuid, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tables = self.write();
+        let Some((sentence, _)) = tables.deleted_sentences.iter().find(|(s, _)| s.id == id).cloned() else {
+            return Ok(None);
+        };
+        if !tables.chapters.iter().any(|c| c.id == sentence.chapter_id) {
+            return Err(AppError::Validation("所属章节已删除，请先恢复章节".to_string()).into());
+        }
+        if tables
+            .sentences
+            .iter()
+            .any(|s| s.chapter_id == sentence.chapter_id && s.number == sentence.number)
+        {
+            return Err(AppError::Validation(format!("句子编号 {} 已被占用，请先调整编号", sentence.number)).into());
+        }
+
+        tables.undelete_sentences(|s, _| s.id == id);
+        tables.record(EntityType::Sentence, id, RevisionAction::Restore, None, Some(&sentence), edit)?;
+        Ok(Some(sentence))
+    }
+
+    async fn purge(&self, id: Uuid) -> Result<bool> {
+        let mut tables = self.write();
+        let before = tables.deleted_sentences.len();
+        tables.deleted_sentences.retain(|(s, _)| s.id != id);
+        Ok(tables.deleted_sentences.len() < before)
+    }
+
+    async fn insert_at(&self, chapter_id: Uuid, req: InsertSentenceRequest, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tables = self.write();
+        if !tables.chapters.iter().any(|c| c.id == chapter_id) {
+            return Ok(None);
+        }
+
+        let current = tables.sentence_numbers(chapter_id);
+        let index = insert_index(Some(req.position), current.len())?;
+        let id = Uuid::new_v4();
+        let mut order = ids(&current);
+        order.insert(index, id);
+        tables.apply_sentence_numbers(&renumbering(&current, &order));
+
+        let now = Utc::now();
+        let sentence = Sentence {
+            id,
+            chapter_id,
+            number: index as i32 + 1,
+            text: req.text,
+            pinyin: req.pinyin,
+            translation: req.translation,
+            created_at: now,
+            updated_at: now,
+        };
+        tables.sentences.push(sentence.clone());
+        tables.record(EntityType::Sentence, id, RevisionAction::Create, None, Some(&sentence), edit)?;
+        Ok(Some(sentence))
+    }
+
+    async fn move_to(&self, id: Uuid, req: MoveSentenceRequest, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tables = self.write();
+        let Some(before) = tables.sentences.iter().find(|s| s.id == id).cloned() else {
+            return Ok(None);
+        };
+        let source = before.chapter_id;
+        let target = req.chapter_id.unwrap_or(source);
+        if !tables.chapters.iter().any(|c| c.id == target) {
+            return Err(AppError::Validation("Target chapter does not exist".to_string()).into());
+        }
+
+        let current = tables.sentence_numbers(target);
+        let mut order: Vec<Uuid> = ids(&current).into_iter().filter(|other| *other != id).collect();
+        let index = insert_index(req.position, order.len())?;
+        order.insert(index, id);
+
+        if target != source {
+            if let Some(sentence) = tables.sentences.iter_mut().find(|s| s.id == id) {
+                sentence.chapter_id = target;
+                sentence.number = index as i32 + 1;
+                sentence.updated_at = Utc::now();
+            }
+            let remaining = tables.sentence_numbers(source);
+            tables.apply_sentence_numbers(&renumbering(&remaining, &ids(&remaining)));
+        }
+        let current = tables.sentence_numbers(target);
+        tables.apply_sentence_numbers(&renumbering(&current, &order));
+        let Some(after) = tables.sentences.iter().find(|s| s.id == id).cloned() else {
+            return Ok(None);
+        };
+        if (after.chapter_id, after.number) != (before.chapter_id, before.number) {
+            tables.record(EntityType::Sentence, id, RevisionAction::Update, Some(&before), Some(&after), edit)?;
+        }
+        Ok(Some(after))
+    }
+
+    async fn renumber(&self, chapter_id: Uuid, order: Option<Vec<Uuid>>, edit: &Edit) -> Result<Option<Vec<Sentence>>> {
+        let mut tables = self.write();
+        if !tables.chapters.iter().any(|c| c.id == chapter_id) {
+            return Ok(None);
+        }
+
+        let before = tables.ordered_sentences(chapter_id);
+        let current = tables.sentence_numbers(chapter_id);
+        let order = requested_order(&ids(&current), order)?;
+        tables.apply_sentence_numbers(&renumbering(&current, &order));
+
+        let sentences = tables.ordered_sentences(chapter_id);
+        for after in &sentences {
+            if let Some(before) = before.iter().find(|b| b.id == after.id && b.number != after.number) {
+                tables.record(EntityType::Sentence, after.id, RevisionAction::Update, Some(before), Some(after), edit)?;
+            }
+        }
+        Ok(Some(sentences))
+    }
+
+    async fn split(&self, id: Uuid, offset: usize, edit: &Edit) -> Result<Option<(Sentence, Sentence)>> {
+        let mut tables = self.write();
+        let Some(sentence) = tables.sentences.iter().find(|s| s.id == id).cloned() else {
+            return Ok(None);
+        };
+        let (first_text, second_text) = split_text(&sentence.text, offset)?;
+
+        let current = tables.sentence_numbers(sentence.chapter_id);
+        let second_id = Uuid::new_v4();
+        let mut order = ids(&current);
+        let index = order.iter().position(|other| *other == id).unwrap_or(order.len()) + 1;
+        order.insert(index, second_id);
+        tables.apply_sentence_numbers(&renumbering(&current, &order));
+
+        let now = Utc::now();
+        let Some(first) = tables.sentences.iter_mut().find(|s| s.id == id) else {
+            return Ok(None);
+        };
+        first.text = first_text;
+        first.pinyin = None;
+        first.translation = None;
+        first.updated_at = now;
+        let first = first.clone();
+
+        let second = Sentence {
+            id: second_id,
+            chapter_id: sentence.chapter_id,
+            number: index as i32 + 1,
+            text: second_text,
+            pinyin: None,
+            translation: None,
+            created_at: now,
+            updated_at: now,
+        };
+        tables.sentences.push(second.clone());
+        tables.record(EntityType::Sentence, id, RevisionAction::Update, Some(&sentence), Some(&first), edit)?;
+        tables.record(EntityType::Sentence, second_id, RevisionAction::Create, None, Some(&second), edit)?;
+        Ok(Some((first, second)))
+    }
+
+    async fn merge_with_next(&self, id: Uuid, edit: &Edit) -> Result<Option<Sentence>> {
+        let mut tables = self.write();
+        let Some(sentence) = tables.sentences.iter().find(|s| s.id == id).cloned() else {
+            return Ok(None);
+        };
+
+        let current = tables.sentence_numbers(sentence.chapter_id);
+        let index = current.iter().position(|(other, _)| *other == id).unwrap_or(current.len());
+        let Some(next) = current
+            .get(index + 1)
+            .and_then(|(next_id, _)| tables.sentences.iter().find(|s| s.id == *next_id))
+            .cloned()
+        else {
+            return Err(AppError::Validation("No following sentence in this chapter to merge with".to_string()).into());
+        };
+
+        tables.delete_sentences(|s| s.id == next.id, Utc::now());
+        if let Some(merged) = tables.sentences.iter_mut().find(|s| s.id == id) {
+            merged.text = format!("{}{}", sentence.text, next.text);
+            merged.pinyin = join_annotations(sentence.pinyin.clone(), next.pinyin.clone());
+            merged.translation = join_annotations(sentence.translation.clone(), next.translation.clone());
+            merged.updated_at = Utc::now();
+        }
+        let remaining = tables.sentence_numbers(sentence.chapter_id);
+        tables.apply_sentence_numbers(&renumbering(&remaining, &ids(&remaining)));
+        let Some(merged) = tables.sentences.iter().find(|s| s.id == id).cloned() else {
+            return Ok(None);
+        };
+        tables.record(EntityType::Sentence, next.id, RevisionAction::Delete, Some(&next), None, edit)?;
+        tables.record(EntityType::Sentence, id, RevisionAction::Update, Some(&sentence), Some(&merged), edit)?;
+        Ok(Some(merged))
+    }
+
+    async fn regenerate(&self, chapter_id: Uuid, texts: Vec<String>, edit: &Edit) -> Result<Option<Vec<Sentence>>> {
+        self.write().regenerate(chapter_id, texts, edit)
+    }
+}
+
+#[async_trait]
+impl RevisionRepo for InMemoryRepo {
+    async fn list(&self, entity_type: EntityType, entity_id: Uuid) -> Result<Vec<Revision>> {
+        let mut revisions: Vec<Revision> = self
+            .read()
+            .revisions
+            .iter()
+            .filter(|r| r.entity_type == entity_type && r.entity_id == entity_id)
+            .cloned()
+            .collect();
+        revisions.sort_by_key(|r| r.version);
+        Ok(revisions)
+    }
+}
+
+#[async_trait]
+impl TrashRepo for InMemoryRepo {
+    async fn list(&self, entity_type: Option<EntityType>) -> Result<Vec<TrashItem>> {
+        let tables = self.read();
+        let classics = tables.deleted_classics.iter().map(|(c, at)| TrashItem {
+            entity_type: EntityType::Classic,
+            id: c.id,
+            label: c.title.clone(),
+            parent_id: None,
+            deleted_at: *at,
+        });
+        // Children deleted along with their parent are listed under it only
+        let chapters = tables
+            .deleted_chapters
+            .iter()
+            .filter(|(c, at)| !tables.deleted_classics.iter().any(|(p, p_at)| p.id == c.classic_id && p_at == at))
+            .map(|(c, at)| TrashItem {
+                entity_type: EntityType::Chapter,
+                id: c.id,
+                label: c.title.clone(),
+                parent_id: Some(c.classic_id),
+                deleted_at: *at,
+            });
+        let sentences = tables
+            .deleted_sentences
+            .iter()
+            .filter(|(s, at)| !tables.deleted_chapters.iter().any(|(p, p_at)| p.id == s.chapter_id && p_at == at))
+            .map(|(s, at)| TrashItem {
+                entity_type: EntityType::Sentence,
+                id: s.id,
+                label: s.text.clone(),
+                parent_id: Some(s.chapter_id),
+                deleted_at: *at,
+            });
+
+        let mut items: Vec<TrashItem> = classics
+            .chain(chapters)
+            .chain(sentences)
+            .filter(|item| entity_type.is_none_or(|entity_type| item.entity_type == entity_type))
+            .collect();
+        items.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(items)
+    }
+}
+
+#[async_trait]
+impl UserRepo for InMemoryRepo {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>> {
+        Ok(self.read().users.iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
+        Ok(self.read().users.iter().find(|u| u.email == email).cloned())
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        Ok(self.read().users.iter().find(|u| u.username == username).cloned())
+    }
+
+    async fn create(&self, req: CreateUserRequest) -> Result<User> {
+        let hashed_password = hash(&req.password, PASSWORD_COST)?;
+
+        let mut tables = self.write();
+        if tables.users.iter().any(|u| u.email == req.email) {
+            return Err(unique_violation("users_email_key"));
+        }
+        if tables.users.iter().any(|u| u.username == req.username) {
+            return Err(unique_violation("users_username_key"));
+        }
+
+        let now = Utc::now();
+        let user = User {
+            id: Uuid::new_v4(),
+            username: req.username,
+            email: req.email,
+            password: hashed_password,
+            phone: req.phone,
+            role: req.role.unwrap_or(UserRole::Parent),
+            is_active: true,
+            last_login: None,
+            created_at: now,
+            updated_at: now,
+        };
+        tables.users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn update_last_login(&self, id: Uuid) -> Result<()> {
+        let mut tables = self.write();
+        if let Some(user) = tables.users.iter_mut().find(|u| u.id == id) {
+            let now = Utc::now();
+            user.last_login = Some(now);
+            user.updated_at = now;
+        }
+        Ok(())
+    }
+
+    async fn set_password(&self, id: Uuid, password: &str) -> Result<bool> {
+        let hashed_password = hash(password, PASSWORD_COST)?;
+
+        let mut tables = self.write();
+        let Some(user) = tables.users.iter_mut().find(|u| u.id == id) else {
+            return Ok(false);
+        };
+        user.password = hashed_password;
+        user.updated_at = Utc::now();
+        Ok(true)
+    }
+}
@@ -0,0 +1,189 @@
+//! Data access behind traits, so handlers can run against Postgres in production
+//! and against an in-memory store in tests.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    database::Database,
+    models::{
+        Audience, ChangeStatusRequest, Chapter, ChapterFilter, ChapterKey, Classic, ClassicFilter,
+        ClassicKey, CreateChapterRequest, CreateClassicRequest, CreateSentenceRequest,
+        CreateUserRequest, Edit, EntityType, InsertChapterRequest, InsertSentenceRequest,
+        MoveSentenceRequest, Revision, Sentence, SentenceFilter, SentenceKey, SentenceNeighbors,
+        TrashItem, UpdateChapterRequest, UpdateClassicRequest, UpdateSentenceRequest, User,
+    },
+    utils::{conditional::IfMatch, pagination::{Page, PageRequest}},
+};
+
+pub mod memory;
+pub mod postgres;
+
+pub use memory::InMemoryRepo;
+pub use postgres::PgRepo;
+
+#[async_trait]
+pub trait ClassicRepo: Send + Sync {
+    /// Must see the latest writes, since the result refills the list cache.
+    async fn list(&self, filter: &ClassicFilter, page: &PageRequest<ClassicKey>) -> Result<Page<Classic>>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Classic>>;
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<Classic>>;
+    /// Whether a classic already uses the slug, including one created a moment ago.
+    async fn slug_exists(&self, slug: &str) -> Result<bool>;
+    async fn create(&self, req: CreateClassicRequest, edit: &Edit) -> Result<Classic>;
+    // Creates, updates, restores and deletes each append a revision in the same transaction.
+    // Updates check `if_match` against the locked row and fail with `Stale`,
+    // carrying that row, when it is stale.
+    async fn update(&self, id: Uuid, req: UpdateClassicRequest, if_match: &IfMatch, edit: &Edit) -> Result<Option<Classic>>;
+    /// Set the content fields back to those of `snapshot`, an earlier revision's state.
+    async fn restore(&self, id: Uuid, snapshot: &Classic, edit: &Edit) -> Result<Option<Classic>>;
+    /// Move to the trash along with the chapters and sentences.
+    async fn delete(&self, id: Uuid, edit: &Edit) -> Result<bool>;
+    /// Bring back from the trash with what was deleted along with it. `None` if
+    /// it is not in the trash.
+    async fn undelete(&self, id: Uuid, edit: &Edit) -> Result<Option<Classic>>;
+    /// Remove from the trash for good. `false` if it is not in the trash.
+    async fn purge(&self, id: Uuid) -> Result<bool>;
+    /// Move through the publication workflow; disallowed changes fail with
+    /// `AppError::Validation` or `AppError::Forbidden`.
+    async fn change_status(
+        &self,
+        id: Uuid,
+        req: &ChangeStatusRequest,
+        audience: Audience,
+        edit: &Edit,
+    ) -> Result<Option<Classic>>;
+    /// The next scheduled publish or unpublish still ahead. Read from the
+    /// primary, since it bounds how long a list page may be cached.
+    async fn next_scheduled_change(&self) -> Result<Option<DateTime<Utc>>>;
+}
+
+#[async_trait]
+pub trait ChapterRepo: Send + Sync {
+    async fn list(&self, filter: &ChapterFilter, page: &PageRequest<ChapterKey>) -> Result<Page<Chapter>>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Chapter>>;
+    async fn find_by_classic_id(&self, classic_id: Uuid) -> Result<Vec<Chapter>>;
+    async fn create(&self, req: CreateChapterRequest, edit: &Edit) -> Result<Chapter>;
+    /// With `resegment`, the sentences are regenerated from the updated content
+    /// in the same transaction; a chapter without content fails with
+    /// `AppError::Validation` and is left unchanged.
+    async fn update(&self, id: Uuid, req: UpdateChapterRequest, if_match: &IfMatch, edit: &Edit) -> Result<Option<Chapter>>;
+    /// Set the content fields back to those of `snapshot`, an earlier revision's state.
+    async fn restore(&self, id: Uuid, snapshot: &Chapter, edit: &Edit) -> Result<Option<Chapter>>;
+    /// Move to the trash along with the sentences.
+    async fn delete(&self, id: Uuid, edit: &Edit) -> Result<bool>;
+    /// Bring back from the trash with what was deleted along with it; fails with
+    /// `AppError::Validation` while the classic is deleted or the number is taken.
+    async fn undelete(&self, id: Uuid, edit: &Edit) -> Result<Option<Chapter>>;
+    async fn purge(&self, id: Uuid) -> Result<bool>;
+
+    // Editor operations: each is atomic and leaves the classic's chapters numbered
+    // 1, 2, 3…; invalid positions and orders fail with `AppError::Validation`.
+    // `None` means the classic or chapter does not exist. Each records a revision
+    // for the chapter it creates and each one it renumbers or moves; numbers
+    // shifted to make room are not recorded.
+    async fn insert_at(&self, classic_id: Uuid, req: InsertChapterRequest, edit: &Edit) -> Result<Option<Chapter>>;
+    async fn move_to(&self, id: Uuid, position: Option<i32>, edit: &Edit) -> Result<Option<Chapter>>;
+    async fn renumber(&self, classic_id: Uuid, order: Option<Vec<Uuid>>, edit: &Edit) -> Result<Option<Vec<Chapter>>>;
+}
+
+#[async_trait]
+pub trait SentenceRepo: Send + Sync {
+    async fn list(&self, filter: &SentenceFilter, page: &PageRequest<SentenceKey>) -> Result<Page<Sentence>>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Sentence>>;
+    async fn find_by_chapter_id(&self, chapter_id: Uuid) -> Result<Vec<Sentence>>;
+    /// Every sentence of a classic, ordered by chapter number then sentence number.
+    async fn find_by_classic_id(&self, classic_id: Uuid) -> Result<Vec<Sentence>>;
+    /// `(chapter_id, count)` for the classic's chapters that have sentences.
+    async fn count_by_classic_id(&self, classic_id: Uuid) -> Result<Vec<(Uuid, i64)>>;
+    /// The sentence's place in its classic, or `None` if there is no such sentence.
+    async fn neighbors(&self, id: Uuid) -> Result<Option<SentenceNeighbors>>;
+    async fn create(&self, req: CreateSentenceRequest, edit: &Edit) -> Result<Sentence>;
+    async fn update(&self, id: Uuid, req: UpdateSentenceRequest, if_match: &IfMatch, edit: &Edit) -> Result<Option<Sentence>>;
+    /// Set the content fields back to those of `snapshot`, an earlier revision's state.
+    async fn restore(&self, id: Uuid, snapshot: &Sentence, edit: &Edit) -> Result<Option<Sentence>>;
+    async fn delete(&self, id: Uuid, edit: &Edit) -> Result<bool>;
+    /// Bring back from the trash; fails with `AppError::Validation` while the
+    /// chapter is deleted or the number is taken.
+    async fn undelete(&self, id: Uuid, edit: &Edit) -> Result<Option<Sentence>>;
+    async fn purge(&self, id: Uuid) -> Result<bool>;
+
+    // Editor operations: each is atomic and leaves the affected chapters' sentences
+    // numbered 1, 2, 3…; invalid input fails with `AppError::Validation`. `None`
+    // means the chapter or sentence does not exist. Each records a revision for
+    // every sentence it creates, changes, renumbers, moves or removes; numbers
+    // shifted to make room are not recorded.
+    async fn insert_at(&self, chapter_id: Uuid, req: InsertSentenceRequest, edit: &Edit) -> Result<Option<Sentence>>;
+    async fn move_to(&self, id: Uuid, req: MoveSentenceRequest, edit: &Edit) -> Result<Option<Sentence>>;
+    async fn renumber(&self, chapter_id: Uuid, order: Option<Vec<Uuid>>, edit: &Edit) -> Result<Option<Vec<Sentence>>>;
+    /// Split after `offset` characters into this sentence and a new next one.
+    async fn split(&self, id: Uuid, offset: usize, edit: &Edit) -> Result<Option<(Sentence, Sentence)>>;
+    /// Merge with the following sentence of the same chapter.
+    async fn merge_with_next(&self, id: Uuid, edit: &Edit) -> Result<Option<Sentence>>;
+    /// Replace the chapter's sentences with `texts`, keeping those whose text is unchanged.
+    async fn regenerate(&self, chapter_id: Uuid, texts: Vec<String>, edit: &Edit) -> Result<Option<Vec<Sentence>>>;
+}
+
+/// Revisions outlive their entity, so they can be listed after a delete.
+#[async_trait]
+pub trait RevisionRepo: Send + Sync {
+    /// An entity's revisions, oldest first.
+    async fn list(&self, entity_type: EntityType, entity_id: Uuid) -> Result<Vec<Revision>>;
+}
+
+#[async_trait]
+pub trait TrashRepo: Send + Sync {
+    /// Items deleted on their own, most recently deleted first.
+    async fn list(&self, entity_type: Option<EntityType>) -> Result<Vec<TrashItem>>;
+}
+
+/// Account lookups must see accounts registered a moment ago.
+#[async_trait]
+pub trait UserRepo: Send + Sync {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>>;
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>>;
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>>;
+    async fn create(&self, req: CreateUserRequest) -> Result<User>;
+    async fn update_last_login(&self, id: Uuid) -> Result<()>;
+    async fn set_password(&self, id: Uuid, password: &str) -> Result<bool>;
+}
+
+/// The repositories handlers use, shared through `AppState`.
+#[derive(Clone)]
+pub struct Repositories {
+    pub classics: Arc<dyn ClassicRepo>,
+    pub chapters: Arc<dyn ChapterRepo>,
+    pub sentences: Arc<dyn SentenceRepo>,
+    pub users: Arc<dyn UserRepo>,
+    pub revisions: Arc<dyn RevisionRepo>,
+    pub trash: Arc<dyn TrashRepo>,
+}
+
+impl Repositories {
+    pub fn postgres(db: Database) -> Self {
+        Self::from_store(Arc::new(PgRepo::new(db)))
+    }
+
+    /// An empty store that lives as long as the returned handles.
+    pub fn in_memory() -> Self {
+        Self::from_store(Arc::new(InMemoryRepo::default()))
+    }
+
+    fn from_store<R>(store: Arc<R>) -> Self
+    where
+        R: ClassicRepo + ChapterRepo + SentenceRepo + UserRepo + RevisionRepo + TrashRepo + 'static,
+    {
+        Self {
+            classics: store.clone(),
+            chapters: store.clone(),
+            sentences: store.clone(),
+            users: store.clone(),
+            revisions: store.clone(),
+            trash: store,
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    database::Database,
+    models::{
+        Audience, ChangeStatusRequest, Chapter, ChapterFilter, ChapterKey, Classic, ClassicFilter,
+        ClassicKey, CreateChapterRequest, CreateClassicRequest, CreateSentenceRequest,
+        CreateUserRequest, Edit, EntityType, InsertChapterRequest, InsertSentenceRequest,
+        MoveSentenceRequest, Revision, Sentence, SentenceFilter, SentenceKey, SentenceNeighbors,
+        TrashItem, UpdateChapterRequest, UpdateClassicRequest, UpdateSentenceRequest, User,
+    },
+    utils::{conditional::IfMatch, pagination::{Page, PageRequest}},
+};
+
+use super::{ChapterRepo, ClassicRepo, RevisionRepo, SentenceRepo, TrashRepo, UserRepo};
+
+/// Repositories backed by the model queries. Plain reads go to a replica;
+/// writes and reads that must see them go to the primary.
+#[derive(Clone)]
+pub struct PgRepo {
+    db: Database,
+}
+
+impl PgRepo {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl ClassicRepo for PgRepo {
+    async fn list(&self, filter: &ClassicFilter, page: &PageRequest<ClassicKey>) -> Result<Page<Classic>> {
+        // A lagging replica could repopulate the cache with data older than
+        // the write that just invalidated it
+        Classic::list(self.db.writer(), filter, page).await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Classic>> {
+        Classic::find_by_id(self.db.reader(), id).await
+    }
+
+    async fn find_by_slug(&self, slug: &str) -> Result<Option<Classic>> {
+        Classic::find_by_slug(self.db.reader(), slug).await
+    }
+
+    async fn slug_exists(&self, slug: &str) -> Result<bool> {
+        Classic::slug_exists(self.db.writer(), slug).await
+    }
+
+    async fn create(&self, req: CreateClassicRequest, edit: &Edit) -> Result<Classic> {
+        Classic::create_recorded(self.db.writer(), req, edit).await
+    }
+
+    async fn update(&self, id: Uuid, req: UpdateClassicRequest, if_match: &IfMatch, edit: &Edit) -> Result<Option<Classic>> {
+        Classic::update_recorded(self.db.writer(), id, req, if_match, edit).await
+    }
+
+    async fn restore(&self, id: Uuid, snapshot: &Classic, edit: &Edit) -> Result<Option<Classic>> {
+        Classic::restore_recorded(self.db.writer(), id, snapshot, edit).await
+    }
+
+    async fn delete(&self, id: Uuid, edit: &Edit) -> Result<bool> {
+        Classic::delete_recorded(self.db.writer(), id, edit).await
+    }
+
+    async fn undelete(&self, id: Uuid, edit: &Edit) -> Result<Option<Classic>> {
+        Classic::undelete(self.db.writer(), id, edit).await
+    }
+
+    async fn purge(&self, id: Uuid) -> Result<bool> {
+        Classic::purge(self.db.writer(), id).await
+    }
+
+    async fn change_status(
+        &self,
+        id: Uuid,
+        req: &ChangeStatusRequest,
+        audience: Audience,
+        edit: &Edit,
+    ) -> Result<Option<Classic>> {
+        Classic::change_status(self.db.writer(), id, req, audience, edit).await
+    }
+
+    async fn next_scheduled_change(&self) -> Result<Option<DateTime<Utc>>> {
+        Classic::next_scheduled_change(self.db.writer()).await
+    }
+}
+
+#[async_trait]
+impl ChapterRepo for PgRepo {
+    async fn list(&self, filter: &ChapterFilter, page: &PageRequest<ChapterKey>) -> Result<Page<Chapter>> {
+        Chapter::list(self.db.reader(), filter, page).await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Chapter>> {
+        Chapter::find_by_id(self.db.reader(), id).await
+    }
+
+    async fn find_by_classic_id(&self, classic_id: Uuid) -> Result<Vec<Chapter>> {
+        Chapter::find_by_classic_id(self.db.reader(), classic_id).await
+    }
+
+    async fn create(&self, req: CreateChapterRequest, edit: &Edit) -> Result<Chapter> {
+        Chapter::create_recorded(self.db.writer(), req, edit).await
+    }
+
+    async fn update(&self, id: Uuid, req: UpdateChapterRequest, if_match: &IfMatch, edit: &Edit) -> Result<Option<Chapter>> {
+        Chapter::update_recorded(self.db.writer(), id, req, if_match, edit).await
+    }
+
+    async fn restore(&self, id: Uuid, snapshot: &Chapter, edit: &Edit) -> Result<Option<Chapter>> {
+        Chapter::restore_recorded(self.db.writer(), id, snapshot, edit).await
+    }
+
+    async fn delete(&self, id: Uuid, edit: &Edit) -> Result<bool> {
+        Chapter::delete_recorded(self.db.writer(), id, edit).await
+    }
+
+    async fn undelete(&self, id: Uuid, edit: &Edit) -> Result<Option<Chapter>> {
+        Chapter::undelete(self.db.writer(), id, edit).await
+    }
+
+    async fn purge(&self, id: Uuid) -> Result<bool> {
+        Chapter::purge(self.db.writer(), id).await
+    }
+
+    async fn insert_at(&self, classic_id: Uuid, req: InsertChapterRequest, edit: &Edit) -> Result<Option<Chapter>> {
+        Chapter::insert_at(self.db.writer(), classic_id, req, edit).await
+    }
+
+    async fn move_to(&self, id: Uuid, position: Option<i32>, edit: &Edit) -> Result<Option<Chapter>> {
+        Chapter::move_to(self.db.writer(), id, position, edit).await
+    }
+
+    async fn renumber(&self, classic_id: Uuid, order: Option<Vec<Uuid>>, edit: &Edit) -> Result<Option<Vec<Chapter>>> {
+        Chapter::renumber(self.db.writer(), classic_id, order, edit).await
+    }
+}
+
+#[async_trait]
+impl SentenceRepo for PgRepo {
+    async fn list(&self, filter: &SentenceFilter, page: &PageRequest<SentenceKey>) -> Result<Page<Sentence>> {
+        Sentence::list(self.db.reader(), filter, page).await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<Sentence>> {
+        Sentence::find_by_id(self.db.reader(), id).await
+    }
+
+    async fn find_by_chapter_id(&self, chapter_id: Uuid) -> Result<Vec<Sentence>> {
+        Sentence::find_by_chapter_id(self.db.reader(), chapter_id).await
+    }
+
+    async fn find_by_classic_id(&self, classic_id: Uuid) -> Result<Vec<Sentence>> {
+        Sentence::find_by_classic_id(self.db.reader(), classic_id).await
+    }
+
+    async fn count_by_classic_id(&self, classic_id: Uuid) -> Result<Vec<(Uuid, i64)>> {
+        Sentence::count_by_classic_id(self.db.reader(), classic_id).await
+    }
+
+    async fn neighbors(&self, id: Uuid) -> Result<Option<SentenceNeighbors>> {
+        Sentence::neighbors(self.db.reader(), id).await
+    }
+
+    async fn create(&self, req: CreateSentenceRequest, edit: &Edit) -> Result<Sentence> {
+        Sentence::create_recorded(self.db.writer(), req, edit).await
+    }
+
+    async fn update(&self, id: Uuid, req: UpdateSentenceRequest, if_match: &IfMatch, edit: &Edit) -> Result<Option<Sentence>> {
+        Sentence::update_recorded(self.db.writer(), id, req, if_match, edit).await
+    }
+
+    async fn restore(&self, id: Uuid, snapshot: &Sentence, edit: &Edit) -> Result<Option<Sentence>> {
+        Sentence::restore_recorded(self.db.writer(), id, snapshot, edit).await
+    }
+
+    async fn delete(&self, id: Uuid, edit: &Edit) -> Result<bool> {
+        Sentence::delete_recorded(self.db.writer(), id, edit).await
+    }
+
+    async fn undelete(&self, id: Uuid, edit: &Edit) -> Result<Option<Sentence>> {
+        Sentence::undelete(self.db.writer(), id, edit).await
+    }
+
+    async fn purge(&self, id: Uuid) -> Result<bool> {
+        Sentence::purge(self.db.writer(), id).await
+    }
+
+    async fn insert_at(&self, chapter_id: Uuid, req: InsertSentenceRequest, edit: &Edit) -> Result<Option<Sentence>> {
+        Sentence::insert_at(self.db.writer(), chapter_id, req, edit).await
+    }
+
+    async fn move_to(&self, id: Uuid, req: MoveSentenceRequest, edit: &Edit) -> Result<Option<Sentence>> {
+        Sentence::move_to(self.db.writer(), id, req, edit).await
+    }
+
+    async fn renumber(&self, chapter_id: Uuid, order: Option<Vec<Uuid>>, edit: &Edit) -> Result<Option<Vec<Sentence>>> {
+        Sentence::renumber(self.db.writer(), chapter_id, order, edit).await
+    }
+
+    async fn split(&self, id: Uuid, offset: usize, edit: &Edit) -> Result<Option<(Sentence, Sentence)>> {
+        Sentence::split(self.db.writer(), id, offset, edit).await
+    }
+
+    async fn merge_with_next(&self, id: Uuid, edit: &Edit) -> Result<Option<Sentence>> {
+        Sentence::merge_with_next(self.db.writer(), id, edit).await
+    }
+
+    async fn regenerate(&self, chapter_id: Uuid, texts: Vec<String>, edit: &Edit) -> Result<Option<Vec<Sentence>>> {
+        Sentence::regenerate(self.db.writer(), chapter_id, texts, edit).await
+    }
+}
+
+#[async_trait]
+impl RevisionRepo for PgRepo {
+    async fn list(&self, entity_type: EntityType, entity_id: Uuid) -> Result<Vec<Revision>> {
+        // Read right after an edit or restore, so a lagging replica would miss it
+        Revision::list(self.db.writer(), entity_type, entity_id).await
+    }
+}
+
+#[async_trait]
+impl TrashRepo for PgRepo {
+    async fn list(&self, entity_type: Option<EntityType>) -> Result<Vec<TrashItem>> {
+        // Read right after a delete or restore, so a lagging replica would miss it
+        TrashItem::list(self.db.writer(), entity_type).await
+    }
+}
+
+#[async_trait]
+impl UserRepo for PgRepo {
+    async fn find_by_id(&self, id: Uuid) -> Result<Option<User>> {
+        User::find_by_id(self.db.writer(), id).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
+        User::find_by_email(self.db.writer(), email).await
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>> {
+        User::find_by_username(self.db.writer(), username).await
+    }
+
+    async fn create(&self, req: CreateUserRequest) -> Result<User> {
+        User::create(self.db.writer(), req).await
+    }
+
+    async fn update_last_login(&self, id: Uuid) -> Result<()> {
+        User::update_last_login(self.db.writer(), id).await
+    }
+
+    async fn set_password(&self, id: Uuid, password: &str) -> Result<bool> {
+        User::set_password(self.db.writer(), id, password).await
+    }
+}
@@ -0,0 +1,78 @@
+use warp::Filter;
+
+use crate::{errors, handlers::{self, AppState}, middleware, openapi};
+
+/// The full route tree with all middleware, as served by the server.
+pub fn build(
+    state: AppState
+) -> impl Filter<Extract = (impl warp::Reply,), Error = std::convert::Infallible> + Clone {
+    // Health check route
+    let health = warp::path("health")
+        .and(warp::get())
+        .and_then(handlers::health::health_check);
+
+    // Prometheus metrics route
+    let metrics = handlers::metrics::routes(state.clone());
+
+    // API document and Swagger UI
+    let docs = openapi::routes();
+
+    // Rate limiting applies to the API only, not health checks, metrics scrapes or docs
+    let rate_limiter = middleware::rate_limit::RateLimiter::from_config(&state.config, state.redis.clone());
+
+    // API routes
+    let api_routes = warp::path("api")
+        .and(middleware::rate_limit::rate_limit(rate_limiter))
+        .and(
+            // Classics routes
+            handlers::classics::routes(state.clone())
+                // Chapters routes
+                .or(handlers::chapters::routes(state.clone()))
+                // Sentences routes
+                .or(handlers::sentences::routes(state.clone()))
+                // Revision history routes
+                .or(handlers::revisions::routes(state.clone()))
+                // Trash routes
+                .or(handlers::trash::routes(state.clone()))
+                // Auth routes
+                .or(handlers::auth::routes(state.clone()))
+                // Character routes
+                .or(handlers::characters::routes(state.clone()))
+                // Boxed so the nested route futures live on the heap; unboxed
+                // they overflow the stack of a debug build
+                .boxed()
+        )
+        .map(middleware::rate_limit::with_headers);
+
+    let cors = middleware::cors::CorsPolicy::from_config(&state.config.cors);
+    let security_headers = middleware::security::SecurityHeaders::from_config(&state.config);
+
+    // Combine all routes
+    let routes = middleware::cors::preflight(cors.clone())
+        .or(health)
+        .or(metrics)
+        .or(docs)
+        .or(api_routes)
+        .with(middleware::logging::log())
+        .recover(errors::handle_rejection);
+
+    // CORS and security headers go on every response, including errors
+    let routes = middleware::cors::origin()
+        .and(routes)
+        .map(move |origin, reply| cors.apply(origin, reply))
+        .map(move |reply| security_headers.apply(reply));
+
+    // Negotiate gzip/brotli on the final response, including error bodies
+    let routes = middleware::compression::accept_encoding()
+        .and(middleware::compression::if_none_match())
+        .and(routes)
+        .and_then(middleware::compression::compress);
+
+    // Every request runs inside a span carrying its X-Request-Id
+    middleware::request_id::request_id()
+        .and(routes)
+        .map(middleware::request_id::finish)
+        // Recorded after recovery so rejected requests are counted with their final status
+        .with(middleware::metrics::track())
+        .with(middleware::request_id::trace())
+}
\ No newline at end of file
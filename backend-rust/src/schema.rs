@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use thiserror::Error;
+
+/// Columns the models read and write, with their Postgres `udt_name`.
+/// Keep in sync with the queries in `src/models/` when adding a migration.
+const EXPECTED_TABLES: &[(&str, &[(&str, &str)])] = &[
+    (
+        "users",
+        &[
+            ("id", "uuid"),
+            ("username", "varchar"),
+            ("email", "varchar"),
+            ("password", "varchar"),
+            ("phone", "varchar"),
+            ("role", "user_role"),
+            ("is_active", "bool"),
+            ("last_login", "timestamptz"),
+            ("created_at", "timestamptz"),
+            ("updated_at", "timestamptz"),
+        ],
+    ),
+    (
+        "classics",
+        &[
+            ("id", "uuid"),
+            ("slug", "varchar"),
+            ("title", "varchar"),
+            ("author", "varchar"),
+            ("dynasty", "varchar"),
+            ("description", "text"),
+            ("status", "publication_status"),
+            ("publish_at", "timestamptz"),
+            ("unpublish_at", "timestamptz"),
+            ("created_at", "timestamptz"),
+            ("updated_at", "timestamptz"),
+            ("deleted_at", "timestamptz"),
+        ],
+    ),
+    (
+        "chapters",
+        &[
+            ("id", "uuid"),
+            ("classic_id", "uuid"),
+            ("number", "int4"),
+            ("title", "varchar"),
+            ("content", "text"),
+            ("created_at", "timestamptz"),
+            ("updated_at", "timestamptz"),
+            ("deleted_at", "timestamptz"),
+        ],
+    ),
+    (
+        "sentences",
+        &[
+            ("id", "uuid"),
+            ("chapter_id", "uuid"),
+            ("number", "int4"),
+            ("text", "text"),
+            ("pinyin", "text"),
+            ("translation", "text"),
+            ("created_at", "timestamptz"),
+            ("updated_at", "timestamptz"),
+            ("deleted_at", "timestamptz"),
+        ],
+    ),
+    (
+        "revisions",
+        &[
+            ("id", "uuid"),
+            ("entity_type", "revision_entity"),
+            ("entity_id", "uuid"),
+            ("version", "int4"),
+            ("action", "revision_action"),
+            ("before", "jsonb"),
+            ("after", "jsonb"),
+            ("editor_id", "uuid"),
+            ("comment", "text"),
+            ("created_at", "timestamptz"),
+        ],
+    ),
+    (
+        "characters",
+        &[
+            ("id", "uuid"),
+            ("character", "varchar"),
+            ("traditional", "varchar"),
+            ("simplified", "varchar"),
+            ("pinyin", "varchar"),
+            ("meaning", "text"),
+            ("etymology", "text"),
+            ("radical", "varchar"),
+            ("stroke_count", "int4"),
+            ("frequency_rank", "int4"),
+            ("hsk_level", "int4"),
+            ("created_at", "timestamptz"),
+            ("updated_at", "timestamptz"),
+        ],
+    ),
+];
+
+/// Postgres enums mapped by `#[sqlx(type_name = ...)]`, with the labels the Rust enum uses.
+const EXPECTED_ENUMS: &[(&str, &[&str])] = &[("user_role", &["parent", "admin", "child"])];
+
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("数据库结构检查失败: {0}")]
+    Query(#[from] sqlx::Error),
+
+    #[error("数据库结构与模型不一致:\n  - {}", .0.join("\n  - "))]
+    Drift(Vec<String>),
+}
+
+/// Compare the live schema against what the models expect and list every mismatch.
+pub async fn check(pool: &PgPool) -> Result<(), SchemaError> {
+    let rows: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT table_name::text, column_name::text, udt_name::text
+         FROM information_schema.columns
+         WHERE table_schema = current_schema()",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut actual: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for (table, column, udt_name) in rows {
+        actual.entry(table).or_default().insert(column, udt_name);
+    }
+
+    let mut problems = Vec::new();
+
+    for (table, columns) in EXPECTED_TABLES {
+        let Some(actual_columns) = actual.get(*table) else {
+            problems.push(format!("缺少表 {}", table));
+            continue;
+        };
+
+        for (column, expected_type) in columns.iter() {
+            match actual_columns.get(*column) {
+                None => problems.push(format!("缺少列 {}.{}", table, column)),
+                Some(actual_type) if actual_type != expected_type => problems.push(format!(
+                    "列 {}.{} 类型为 {}，模型期望 {}",
+                    table, column, actual_type, expected_type
+                )),
+                Some(_) => {}
+            }
+        }
+    }
+
+    for (name, labels) in EXPECTED_ENUMS {
+        let actual_labels: Vec<String> = sqlx::query_scalar(
+            "SELECT e.enumlabel::text
+             FROM pg_enum e
+             JOIN pg_type t ON t.oid = e.enumtypid
+             WHERE t.typname = $1
+             ORDER BY e.enumsortorder",
+        )
+        .bind(name)
+        .fetch_all(pool)
+        .await?;
+
+        if actual_labels.is_empty() {
+            problems.push(format!("缺少枚举类型 {}", name));
+            continue;
+        }
+        for label in labels.iter() {
+            if !actual_labels.iter().any(|actual| actual == label) {
+                problems.push(format!("枚举 {} 缺少取值 '{}'", name, label));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaError::Drift(problems))
+    }
+}
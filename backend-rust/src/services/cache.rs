@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use tokio::sync::Mutex;
+use redis::{aio::Connection, AsyncCommands};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    metrics,
+    models::{ClassicFilter, ClassicKey},
+    utils::pagination::PageRequest,
+};
+
+/// Default TTL for cached API responses (1 hour)
+pub const DEFAULT_TTL_SECS: u64 = 3600;
+
+/// TTL for a page that must expire by `next`, the next scheduled publish or
+/// unpublish, rounded up to whole seconds.
+pub fn ttl_until(next: Option<DateTime<Utc>>, now: DateTime<Utc>) -> u64 {
+    match next {
+        Some(next) => {
+            let millis = (next - now).num_milliseconds().max(1) as u64;
+            millis.div_ceil(1000).min(DEFAULT_TTL_SECS)
+        }
+        None => DEFAULT_TTL_SECS,
+    }
+}
+
+/// Every page of the classics list is cached under this prefix.
+pub const CLASSICS_LIST_PREFIX: &str = "classics:list:";
+
+/// Key for one page of the classics list, unique per filter, sort and position.
+pub fn classics_list_key(filter: &ClassicFilter, page: &PageRequest<ClassicKey>) -> String {
+    let parts = serde_json::json!([
+        filter.sort.to_string(),
+        filter.dynasty,
+        filter.author,
+        filter.status,
+        filter.published_only,
+        page.to_string(),
+    ]);
+    format!("{}{}", CLASSICS_LIST_PREFIX, parts)
+}
+
+/// Look up a JSON value, recording a hit/miss for the key's namespace.
+/// Redis failures are treated as a miss so callers fall back to the database.
+#[tracing::instrument(level = "debug", skip(redis))]
+pub async fn get_json<T: DeserializeOwned>(redis: &Arc<Mutex<Connection>>, key: &str) -> Option<T> {
+    let cached: redis::RedisResult<Option<String>> = redis.lock().await.get(key).await;
+
+    match cached {
+        Ok(Some(data)) => match serde_json::from_str(&data) {
+            Ok(value) => {
+                metrics::observe_cache_lookup(key, "hit");
+                Some(value)
+            }
+            Err(e) => {
+                tracing::warn!("缓存数据反序列化失败 {}: {}", key, e);
+                metrics::observe_cache_lookup(key, "miss");
+                None
+            }
+        },
+        Ok(None) => {
+            metrics::observe_cache_lookup(key, "miss");
+            None
+        }
+        Err(e) => {
+            tracing::warn!("读取缓存失败 {}: {}", key, e);
+            metrics::observe_cache_lookup(key, "error");
+            None
+        }
+    }
+}
+
+#[tracing::instrument(level = "debug", skip(redis, value))]
+pub async fn set_json<T: Serialize>(redis: &Arc<Mutex<Connection>>, key: &str, value: &T, ttl_secs: u64) {
+    let data = match serde_json::to_string(value) {
+        Ok(data) => data,
+        Err(e) => {
+            tracing::warn!("缓存数据序列化失败 {}: {}", key, e);
+            return;
+        }
+    };
+
+    let result: redis::RedisResult<()> = redis.lock().await.set_ex(key, data, ttl_secs).await;
+    if let Err(e) = result {
+        tracing::warn!("写入缓存失败 {}: {}", key, e);
+    }
+}
+
+#[tracing::instrument(level = "debug", skip(redis))]
+pub async fn invalidate(redis: &Arc<Mutex<Connection>>, key: &str) {
+    let result: redis::RedisResult<()> = redis.lock().await.del(key).await;
+    if let Err(e) = result {
+        tracing::warn!("清除缓存失败 {}: {}", key, e);
+    }
+}
+
+/// Delete every key starting with `prefix`. SCAN rather than KEYS, so Redis
+/// is not blocked while it walks the keyspace.
+#[tracing::instrument(level = "debug", skip(redis))]
+pub async fn invalidate_prefix(redis: &Arc<Mutex<Connection>>, prefix: &str) {
+    let mut conn = redis.lock().await;
+
+    let mut keys: Vec<String> = Vec::new();
+    match conn.scan_match::<_, String>(format!("{}*", prefix)).await {
+        Ok(mut iter) => {
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+        }
+        Err(e) => {
+            tracing::warn!("扫描缓存失败 {}: {}", prefix, e);
+            return;
+        }
+    }
+
+    if keys.is_empty() {
+        return;
+    }
+    let result: redis::RedisResult<()> = conn.del(keys).await;
+    if let Err(e) = result {
+        tracing::warn!("清除缓存失败 {}: {}", prefix, e);
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use uuid::Uuid;
+
+use crate::{
+    models::{ChapterNode, ClassicTree, SentenceResponse, TreeInclude},
+    repositories::Repositories,
+    utils::conditional::Validators,
+};
+
+/// Load a classic with its chapters and, if asked, their sentences. Three
+/// queries however many chapters there are: the classic, its chapters, and
+/// either all of its sentences or just their counts.
+pub async fn load(repos: &Repositories, slug: &str, include: TreeInclude) -> Result<Option<ClassicTree>> {
+    let Some(classic) = repos.classics.find_by_slug(slug).await? else {
+        return Ok(None);
+    };
+    let chapters = repos.chapters.find_by_classic_id(classic.id).await?;
+
+    let mut sentences: HashMap<Uuid, Vec<SentenceResponse>> = HashMap::new();
+    let counts: HashMap<Uuid, i64> = if include.sentences {
+        for sentence in repos.sentences.find_by_classic_id(classic.id).await? {
+            sentences.entry(sentence.chapter_id).or_default().push(sentence.into());
+        }
+        sentences.iter().map(|(id, sentences)| (*id, sentences.len() as i64)).collect()
+    } else {
+        repos.sentences.count_by_classic_id(classic.id).await?.into_iter().collect()
+    };
+
+    let chapters = chapters
+        .into_iter()
+        .map(|chapter| ChapterNode {
+            id: chapter.id,
+            number: chapter.number,
+            title: chapter.title,
+            content: include.content.then_some(chapter.content),
+            sentence_count: counts.get(&chapter.id).copied().unwrap_or(0),
+            sentences: include
+                .sentences
+                .then(|| sentences.remove(&chapter.id).unwrap_or_default()),
+            created_at: chapter.created_at,
+            updated_at: chapter.updated_at,
+        })
+        .collect();
+
+    Ok(Some(ClassicTree { classic, chapters }))
+}
+
+/// Validators for a tree. Every row it shows contributes its version; the
+/// include set and the sentence counts cover what `updated_at` misses, like a
+/// sentence deleted from a chapter that is shown without its sentences.
+pub fn validators(tree: &ClassicTree, include: TreeInclude) -> Validators {
+    let versions = std::iter::once((tree.classic.id, tree.classic.updated_at))
+        .chain(tree.chapters.iter().flat_map(|chapter| {
+            std::iter::once((chapter.id, chapter.updated_at)).chain(
+                chapter
+                    .sentences
+                    .iter()
+                    .flatten()
+                    .map(|sentence| (sentence.id, sentence.updated_at)),
+            )
+        }));
+
+    let mut extra = vec![include.content as u8, include.sentences as u8];
+    for chapter in &tree.chapters {
+        extra.extend_from_slice(&chapter.sentence_count.to_be_bytes());
+    }
+    Validators::from_versions_with(versions, &extra)
+}
@@ -0,0 +1,157 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::models::{
+    Chapter, Character, CharacterData, Classic, CreateChapterRequest, CreateClassicRequest,
+    CreateSentenceRequest, Sentence,
+};
+
+/// A classic with its chapters and sentences, keyed by slug and numbers rather
+/// than ids so exports can be imported into another database.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClassicExport {
+    pub slug: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub dynasty: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub chapters: Vec<ChapterExport>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChapterExport {
+    pub number: i32,
+    pub title: String,
+    pub content: Option<String>,
+    #[serde(default)]
+    pub sentences: Vec<SentenceExport>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SentenceExport {
+    pub number: i32,
+    pub text: String,
+    pub pinyin: Option<String>,
+    pub translation: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub classics: usize,
+    pub chapters: usize,
+    pub sentences: usize,
+    pub characters: usize,
+}
+
+pub async fn export_classics(pool: &PgPool) -> Result<Vec<ClassicExport>> {
+    let mut exports = Vec::new();
+
+    for classic in Classic::find_all(pool).await? {
+        let mut chapters = Vec::new();
+        for chapter in Chapter::find_by_classic_id(pool, classic.id).await? {
+            let sentences = Sentence::find_by_chapter_id(pool, chapter.id)
+                .await?
+                .into_iter()
+                .map(|sentence| SentenceExport {
+                    number: sentence.number,
+                    text: sentence.text,
+                    pinyin: sentence.pinyin,
+                    translation: sentence.translation,
+                })
+                .collect();
+
+            chapters.push(ChapterExport {
+                number: chapter.number,
+                title: chapter.title,
+                content: chapter.content,
+                sentences,
+            });
+        }
+
+        exports.push(ClassicExport {
+            slug: classic.slug,
+            title: classic.title,
+            author: classic.author,
+            dynasty: classic.dynasty,
+            description: classic.description,
+            chapters,
+        });
+    }
+
+    Ok(exports)
+}
+
+/// Upsert classics by slug, chapters by number and sentences by number in one
+/// transaction. Rows that exist in the database but not in the file are kept.
+pub async fn import_classics(pool: &PgPool, classics: &[ClassicExport]) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let mut tx = pool.begin().await?;
+
+    for export in classics {
+        let classic = Classic::upsert(
+            &mut *tx,
+            &CreateClassicRequest {
+                slug: export.slug.clone(),
+                title: export.title.clone(),
+                author: export.author.clone(),
+                dynasty: export.dynasty.clone(),
+                description: export.description.clone(),
+            },
+        )
+        .await?;
+        summary.classics += 1;
+
+        for chapter_export in &export.chapters {
+            let chapter = Chapter::upsert(
+                &mut *tx,
+                &CreateChapterRequest {
+                    classic_id: classic.id,
+                    number: chapter_export.number,
+                    title: chapter_export.title.clone(),
+                    content: chapter_export.content.clone(),
+                },
+            )
+            .await?;
+            summary.chapters += 1;
+
+            for sentence in &chapter_export.sentences {
+                Sentence::upsert(
+                    &mut *tx,
+                    &CreateSentenceRequest {
+                        chapter_id: chapter.id,
+                        number: sentence.number,
+                        text: sentence.text.clone(),
+                        pinyin: sentence.pinyin.clone(),
+                        translation: sentence.translation.clone(),
+                    },
+                )
+                .await?;
+                summary.sentences += 1;
+            }
+        }
+    }
+
+    tx.commit().await?;
+    Ok(summary)
+}
+
+pub async fn export_characters(pool: &PgPool) -> Result<Vec<CharacterData>> {
+    let characters = Character::find_all(pool).await?;
+    Ok(characters.into_iter().map(CharacterData::from).collect())
+}
+
+/// Upsert characters by the character itself in one transaction.
+pub async fn import_characters(pool: &PgPool, characters: &[CharacterData]) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let mut tx = pool.begin().await?;
+
+    for character in characters {
+        Character::upsert(&mut *tx, character).await?;
+        summary.characters += 1;
+    }
+
+    tx.commit().await?;
+    Ok(summary)
+}
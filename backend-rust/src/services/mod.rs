@@ -1,3 +1,9 @@
 // Services module - for business logic
 pub mod cache;
-pub mod etymology;
\ No newline at end of file
+pub mod classic_tree;
+pub mod import_export;
+pub mod publishing;
+pub mod revisions;
+pub mod segmentation;
+pub mod sentence_context;
+pub mod trash;
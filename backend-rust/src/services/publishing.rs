@@ -0,0 +1,98 @@
+//! Who may see unpublished content, and lookups that hide chapters and
+//! sentences of classics the caller cannot see. Hidden content is reported as
+//! missing, not forbidden. Also who may change content, and where.
+
+use anyhow::Result;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    errors::AppError,
+    models::{Audience, Chapter, Editor, PublicationStatus, Sentence, UserRole},
+    repositories::Repositories,
+};
+
+/// Signed-in, active admins see everything; everyone else is the public.
+pub async fn audience(repos: &Repositories, user_id: Option<Uuid>) -> Result<Audience> {
+    let Some(user_id) = user_id else {
+        return Ok(Audience::Public);
+    };
+    let admin = repos
+        .users
+        .find_by_id(user_id)
+        .await?
+        .is_some_and(|user| user.is_active && matches!(user.role, UserRole::Admin));
+    Ok(if admin { Audience::Admin } else { Audience::Public })
+}
+
+/// Active users other than children may change content; `None` for anyone else.
+pub async fn editor(repos: &Repositories, user_id: Uuid) -> Result<Option<Editor>> {
+    let Some(user) = repos.users.find_by_id(user_id).await? else {
+        return Ok(None);
+    };
+    if !user.is_active {
+        return Ok(None);
+    }
+    let audience = match user.role {
+        UserRole::Admin => Audience::Admin,
+        UserRole::Parent => Audience::Public,
+        UserRole::Child => return Ok(None),
+    };
+    Ok(Some(Editor { user_id, audience }))
+}
+
+/// Fail with `Forbidden` unless `editor` may change the content of a classic:
+/// once published, only admins may. A missing classic is `NotFound`.
+pub async fn check_classic_editable(repos: &Repositories, classic_id: Uuid, editor: &Editor) -> Result<()> {
+    if !editor.audience.is_public() {
+        return Ok(());
+    }
+    match repos.classics.find_by_id(classic_id).await? {
+        Some(classic) if classic.status == PublicationStatus::Published => Err(AppError::Forbidden.into()),
+        Some(_) => Ok(()),
+        None => Err(AppError::NotFound.into()),
+    }
+}
+
+pub async fn check_chapter_editable(repos: &Repositories, chapter_id: Uuid, editor: &Editor) -> Result<()> {
+    if !editor.audience.is_public() {
+        return Ok(());
+    }
+    let Some(chapter) = repos.chapters.find_by_id(chapter_id).await? else {
+        return Err(AppError::NotFound.into());
+    };
+    check_classic_editable(repos, chapter.classic_id, editor).await
+}
+
+pub async fn check_sentence_editable(repos: &Repositories, sentence_id: Uuid, editor: &Editor) -> Result<()> {
+    if !editor.audience.is_public() {
+        return Ok(());
+    }
+    let Some(sentence) = repos.sentences.find_by_id(sentence_id).await? else {
+        return Err(AppError::NotFound.into());
+    };
+    check_chapter_editable(repos, sentence.chapter_id, editor).await
+}
+
+pub async fn find_chapter(repos: &Repositories, id: Uuid, audience: Audience) -> Result<Option<Chapter>> {
+    let Some(chapter) = repos.chapters.find_by_id(id).await? else {
+        return Ok(None);
+    };
+    Ok(classic_visible(repos, chapter.classic_id, audience).await?.then_some(chapter))
+}
+
+pub async fn find_sentence(repos: &Repositories, id: Uuid, audience: Audience) -> Result<Option<Sentence>> {
+    let Some(sentence) = repos.sentences.find_by_id(id).await? else {
+        return Ok(None);
+    };
+    let visible = find_chapter(repos, sentence.chapter_id, audience).await?.is_some();
+    Ok(visible.then_some(sentence))
+}
+
+async fn classic_visible(repos: &Repositories, classic_id: Uuid, audience: Audience) -> Result<bool> {
+    if !audience.is_public() {
+        return Ok(true);
+    }
+    let classic = repos.classics.find_by_id(classic_id).await?;
+    Ok(classic.is_some_and(|classic| classic.is_public(Utc::now())))
+}
@@ -0,0 +1,80 @@
+//! Revision history of classics, chapters and sentences: listing, comparing two
+//! versions and restoring one. Version 0 is the state before the first revision.
+
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    errors::AppError,
+    models::{revision, Edit, EntityType, Revision, RevisionDiff},
+    repositories::Repositories,
+};
+
+/// The entity's revisions, oldest first. `None` if it has none and does not exist.
+pub async fn list(repos: &Repositories, entity_type: EntityType, id: Uuid) -> Result<Option<Vec<Revision>>> {
+    let revisions = repos.revisions.list(entity_type, id).await?;
+    if revisions.is_empty() && !exists(repos, entity_type, id).await? {
+        return Ok(None);
+    }
+    Ok(Some(revisions))
+}
+
+/// Field changes from version `from` to version `to`.
+pub fn diff(revisions: &[Revision], from: i32, to: i32) -> Result<RevisionDiff> {
+    let changes = revision::diff(state(revisions, from)?, state(revisions, to)?);
+    Ok(RevisionDiff { from, to, changes })
+}
+
+/// Put the entity's content back to how it was at `version`, recording a
+/// restore revision. Returns the entity as restored, or `None` if it no longer
+/// exists.
+pub async fn restore(
+    repos: &Repositories,
+    entity_type: EntityType,
+    id: Uuid,
+    version: i32,
+    edit: &Edit,
+) -> Result<Option<Value>> {
+    let revisions = repos.revisions.list(entity_type, id).await?;
+    let Some(snapshot) = state(&revisions, version)? else {
+        return Err(AppError::Validation(format!("版本 {} 中内容已删除，无法恢复", version)).into());
+    };
+
+    match entity_type {
+        EntityType::Classic => {
+            let snapshot = parse(snapshot)?;
+            to_value(repos.classics.restore(id, &snapshot, edit).await?)
+        }
+        EntityType::Chapter => {
+            let snapshot = parse(snapshot)?;
+            to_value(repos.chapters.restore(id, &snapshot, edit).await?)
+        }
+        EntityType::Sentence => {
+            let snapshot = parse(snapshot)?;
+            to_value(repos.sentences.restore(id, &snapshot, edit).await?)
+        }
+    }
+}
+
+fn state(revisions: &[Revision], version: i32) -> Result<Option<&Value>> {
+    revision::state_at(revisions, version)
+        .ok_or_else(|| AppError::Validation(format!("版本不存在: {}", version)).into())
+}
+
+fn parse<T: DeserializeOwned>(snapshot: &Value) -> Result<T> {
+    Ok(serde_json::from_value(snapshot.clone())?)
+}
+
+fn to_value<T: Serialize>(entity: Option<T>) -> Result<Option<Value>> {
+    Ok(entity.map(serde_json::to_value).transpose()?)
+}
+
+async fn exists(repos: &Repositories, entity_type: EntityType, id: Uuid) -> Result<bool> {
+    Ok(match entity_type {
+        EntityType::Classic => repos.classics.find_by_id(id).await?.is_some(),
+        EntityType::Chapter => repos.chapters.find_by_id(id).await?.is_some(),
+        EntityType::Sentence => repos.sentences.find_by_id(id).await?.is_some(),
+    })
+}
@@ -0,0 +1,126 @@
+//! Splitting `Chapter.content` into sentences.
+//!
+//! A sentence ends at 。！？ or ；, together with any closing quotes and further
+//! terminators right after it (`？”`, `！？`). Quoted speech is kept whole: a
+//! terminator inside paired quotes only ends a sentence once the quotes close.
+//! Line breaks outside quotes end a sentence too.
+
+use anyhow::Result;
+
+use crate::{
+    errors::AppError,
+    models::{editing::reuse_by_text, Chapter, Edit, SegmentationPreview, SegmentedSentence, Sentence},
+    repositories::Repositories,
+};
+
+fn is_terminator(c: char) -> bool {
+    matches!(c, '。' | '！' | '？' | '；')
+}
+
+fn is_opening_quote(c: char) -> bool {
+    matches!(c, '“' | '‘' | '「' | '『')
+}
+
+fn is_closing_quote(c: char) -> bool {
+    matches!(c, '”' | '’' | '」' | '』')
+}
+
+fn flush(sentences: &mut Vec<String>, current: &mut String) {
+    let sentence = current.trim();
+    if !sentence.is_empty() {
+        sentences.push(sentence.to_string());
+    }
+    current.clear();
+}
+
+/// Split text into sentences, dropping surrounding whitespace and empty ones.
+pub fn segment(content: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    // The sentence has ended but may still take closing quotes and terminators
+    let mut ended = false;
+
+    for c in content.chars() {
+        if ended && !is_terminator(c) && !is_closing_quote(c) {
+            flush(&mut sentences, &mut current);
+            ended = false;
+        }
+        if c == '\n' || c == '\r' {
+            if depth == 0 {
+                flush(&mut sentences, &mut current);
+            }
+            continue;
+        }
+
+        current.push(c);
+        if is_opening_quote(c) {
+            depth += 1;
+        } else if is_closing_quote(c) {
+            depth = depth.saturating_sub(1);
+            let before_quotes = current.chars().rev().find(|c| !is_closing_quote(*c));
+            if depth == 0 && before_quotes.is_some_and(is_terminator) {
+                ended = true;
+            }
+        } else if is_terminator(c) && depth == 0 {
+            ended = true;
+        }
+    }
+    flush(&mut sentences, &mut current);
+    sentences
+}
+
+/// What confirming the segmentation of a chapter would produce.
+pub async fn preview(repos: &Repositories, chapter: &Chapter) -> Result<SegmentationPreview> {
+    let texts = segment(chapter.content.as_deref().unwrap_or_default());
+    let existing: Vec<(uuid::Uuid, String)> = repos
+        .sentences
+        .find_by_chapter_id(chapter.id)
+        .await?
+        .into_iter()
+        .map(|s| (s.id, s.text))
+        .collect();
+    let reused = reuse_by_text(&existing, &texts);
+    let removed = existing.len() - reused.iter().flatten().count();
+
+    let sentences = texts
+        .into_iter()
+        .zip(reused)
+        .enumerate()
+        .map(|(index, (text, existing_id))| SegmentedSentence {
+            number: index as i32 + 1,
+            text,
+            existing_id,
+        })
+        .collect();
+    Ok(SegmentationPreview {
+        chapter_id: chapter.id,
+        sentences,
+        removed,
+    })
+}
+
+/// Segment a chapter's content; fails with `AppError::Validation` if there is
+/// nothing to segment.
+pub fn segment_content(content: Option<&str>) -> Result<Vec<String>> {
+    let texts = segment(content.unwrap_or_default());
+    if texts.is_empty() {
+        return Err(AppError::Validation("Chapter has no content to segment".to_string()).into());
+    }
+    Ok(texts)
+}
+
+/// Create the chapter's sentences from its content. A chapter that already has
+/// sentences is only regenerated with `replace`; sentences whose text is
+/// unchanged keep their id, pinyin and translation. `None` if the chapter was
+/// deleted meanwhile.
+pub async fn apply(repos: &Repositories, chapter: &Chapter, replace: bool, edit: &Edit) -> Result<Option<Vec<Sentence>>> {
+    let texts = segment_content(chapter.content.as_deref())?;
+    if !replace && !repos.sentences.find_by_chapter_id(chapter.id).await?.is_empty() {
+        return Err(AppError::Validation(
+            "Chapter already has sentences; set replace to regenerate them".to_string(),
+        )
+        .into());
+    }
+    repos.sentences.regenerate(chapter.id, texts, edit).await
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    models::{Audience, ChapterSummary, ClassicSummary, SentenceContext},
+    repositories::Repositories,
+    utils::conditional::Validators,
+};
+
+/// A sentence with its chapter, its classic and its neighbours in reading order.
+/// Returns `None` if the sentence does not exist or its classic is hidden from
+/// the audience.
+pub async fn load(repos: &Repositories, id: Uuid, audience: Audience) -> Result<Option<(SentenceContext, Validators)>> {
+    let Some(sentence) = repos.sentences.find_by_id(id).await? else {
+        return Ok(None);
+    };
+    let chapter = repos.chapters.find_by_id(sentence.chapter_id).await?;
+    let classic = match &chapter {
+        Some(chapter) => repos.classics.find_by_id(chapter.classic_id).await?,
+        None => None,
+    };
+    let neighbors = repos.sentences.neighbors(id).await?;
+    // Deleted between the queries
+    let (Some(chapter), Some(classic), Some(neighbors)) = (chapter, classic, neighbors) else {
+        return Ok(None);
+    };
+    if audience.is_public() && !classic.is_public(Utc::now()) {
+        return Ok(None);
+    }
+
+    // Neighbours move when sentences are added or removed elsewhere in the
+    // classic, which no shown row's `updated_at` records
+    let mut extra = Vec::new();
+    for id in [neighbors.previous_id, neighbors.next_id] {
+        extra.extend_from_slice(id.unwrap_or_default().as_bytes());
+    }
+    extra.extend_from_slice(&neighbors.position.to_be_bytes());
+    extra.extend_from_slice(&neighbors.total.to_be_bytes());
+    let validators = Validators::from_versions_with(
+        [
+            (sentence.id, sentence.updated_at),
+            (chapter.id, chapter.updated_at),
+            (classic.id, classic.updated_at),
+        ],
+        &extra,
+    );
+
+    let context = SentenceContext {
+        sentence: sentence.into(),
+        chapter: ChapterSummary {
+            id: chapter.id,
+            number: chapter.number,
+            title: chapter.title,
+        },
+        classic: ClassicSummary {
+            id: classic.id,
+            slug: classic.slug,
+            title: classic.title,
+        },
+        previous_id: neighbors.previous_id,
+        next_id: neighbors.next_id,
+        position: neighbors.position,
+        total: neighbors.total,
+    };
+    Ok(Some((context, validators)))
+}
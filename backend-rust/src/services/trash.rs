@@ -0,0 +1,34 @@
+//! Restoring and purging deleted classics, chapters and sentences by kind.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    models::{Edit, EntityType},
+    repositories::Repositories,
+};
+
+/// Bring an entity back from the trash with whatever was deleted along with it.
+/// Returns the entity as restored, or `None` if it is not in the trash.
+pub async fn restore(repos: &Repositories, entity_type: EntityType, id: Uuid, edit: &Edit) -> Result<Option<Value>> {
+    match entity_type {
+        EntityType::Classic => to_value(repos.classics.undelete(id, edit).await?),
+        EntityType::Chapter => to_value(repos.chapters.undelete(id, edit).await?),
+        EntityType::Sentence => to_value(repos.sentences.undelete(id, edit).await?),
+    }
+}
+
+/// Remove an entity in the trash for good. `false` if it is not in the trash.
+pub async fn purge(repos: &Repositories, entity_type: EntityType, id: Uuid) -> Result<bool> {
+    match entity_type {
+        EntityType::Classic => repos.classics.purge(id).await,
+        EntityType::Chapter => repos.chapters.purge(id).await,
+        EntityType::Sentence => repos.sentences.purge(id).await,
+    }
+}
+
+fn to_value<T: Serialize>(entity: Option<T>) -> Result<Option<Value>> {
+    Ok(entity.map(serde_json::to_value).transpose()?)
+}
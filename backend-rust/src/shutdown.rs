@@ -0,0 +1,87 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Shared shutdown flag; every listener stops accepting once it flips.
+#[derive(Clone)]
+pub struct Shutdown {
+    rx: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    /// Flip the flag on SIGTERM (rolling deploys, `docker stop`) or Ctrl-C.
+    pub fn on_signal() -> Self {
+        let (tx, rx) = watch::channel(false);
+
+        tokio::spawn(async move {
+            signal().await;
+            let _ = tx.send(true);
+        });
+
+        Self { rx }
+    }
+
+    /// Resolves once shutdown has been requested.
+    pub async fn requested(mut self) {
+        // An error means the sender is gone, which only happens after it fired
+        let _ = self.rx.wait_for(|stop| *stop).await;
+    }
+
+    /// Run a server until shutdown is requested, then give in-flight requests
+    /// up to `deadline` to finish. Returns how much of `deadline` is left for
+    /// the rest of shutdown, which is zero if the deadline was hit.
+    pub async fn drain(self, server: impl Future<Output = ()>, deadline: Duration) -> Duration {
+        tokio::pin!(server);
+
+        tokio::select! {
+            _ = &mut server => return deadline,
+            _ = self.requested() => {}
+        }
+        let started = Instant::now();
+
+        info!("收到关闭信号，停止接收新连接，等待进行中的请求完成 (最长 {}s)", deadline.as_secs());
+
+        match tokio::time::timeout(deadline, server).await {
+            Ok(()) => {
+                info!("进行中的请求已全部完成");
+                deadline.saturating_sub(started.elapsed())
+            }
+            Err(_) => {
+                warn!("等待请求完成超时，强制关闭剩余连接");
+                Duration::ZERO
+            }
+        }
+    }
+}
+
+async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("监听 Ctrl-C 失败: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("监听 SIGTERM 失败: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("收到 Ctrl-C"),
+        _ = terminate => info!("收到 SIGTERM"),
+    }
+}
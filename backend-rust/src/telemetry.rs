@@ -0,0 +1,50 @@
+use anyhow::Result;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+use crate::config::Config;
+
+/// Install the global tracing subscriber: formatted logs filtered by `RUST_LOG`,
+/// plus an OpenTelemetry layer when `telemetry.otlp_endpoint` is configured.
+pub fn init(config: &Config) -> Result<()> {
+    let env_filter = EnvFilter::try_new(&config.telemetry.log_level)
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let otel_layer = match &config.telemetry.otlp_endpoint {
+        Some(endpoint) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .http()
+                        .with_endpoint(endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", config.telemetry.service_name.clone()),
+                ])))
+                .install_batch(runtime::Tokio)?;
+
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel_layer)
+        .try_init()?;
+
+    if let Some(endpoint) = &config.telemetry.otlp_endpoint {
+        tracing::info!("OpenTelemetry 导出已启用: {}", endpoint);
+    }
+
+    Ok(())
+}
+
+/// Flush spans still buffered in the batch exporter.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
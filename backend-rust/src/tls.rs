@@ -0,0 +1,271 @@
+use std::convert::Infallible;
+use std::fs::File;
+use std::future::Future;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use arc_swap::ArcSwap;
+use rustls::{
+    ServerConfig,
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+};
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_rustls::TlsAcceptor;
+use tracing::{info, warn, error};
+use warp::{
+    Filter, Reply,
+    http::{StatusCode, Uri, header},
+    hyper::{server::conn::Http, service::{Service, service_fn}},
+};
+
+use crate::{config::TlsConfig, shutdown::Shutdown};
+
+/// How long a client gets to complete the TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after a failed accept. Errors such as EMFILE persist until a
+/// connection closes, and retrying at once would spin the accept loop.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Client address for connections served here; warp only tracks the remote
+/// address for its own listeners, so it is passed as a request extension.
+#[derive(Debug, Clone, Copy)]
+pub struct PeerAddr(pub SocketAddr);
+
+/// Serves the current certificate and swaps in a new one when the files change.
+#[derive(Debug)]
+struct CertResolver {
+    current: ArcSwap<CertifiedKey>,
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.load_full())
+    }
+}
+
+pub struct TlsServer {
+    acceptor: TlsAcceptor,
+}
+
+impl TlsServer {
+    /// Load the certificate and key, and start watching them for changes.
+    pub fn from_config(config: &TlsConfig) -> Result<Self> {
+        let cert_path = PathBuf::from(&config.cert_path);
+        let key_path = PathBuf::from(&config.key_path);
+
+        let resolver = Arc::new(CertResolver {
+            current: ArcSwap::from_pointee(load_certified_key(&cert_path, &key_path)?),
+        });
+        info!("TLS 证书已加载: {}", cert_path.display());
+
+        spawn_reloader(
+            resolver.clone(),
+            cert_path,
+            key_path,
+            Duration::from_secs(config.reload_interval_secs),
+        );
+
+        let mut server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(resolver);
+        // Prefer HTTP/2; hyper serves whichever protocol the client picked
+        server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        Ok(Self {
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+        })
+    }
+
+    /// Bind `addr` and serve `filter` over TLS. The returned future accepts
+    /// connections until shutdown is requested, then resolves once every open
+    /// connection has finished its in-flight requests.
+    pub async fn bind<F>(
+        self,
+        addr: SocketAddr,
+        filter: F,
+        shutdown: Shutdown,
+    ) -> Result<(SocketAddr, impl Future<Output = ()>)>
+    where
+        F: Filter<Error = Infallible> + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+        F::Future: Send,
+    {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let service = warp::service(filter);
+
+        let server = async move {
+            // Each connection task holds a sender; `recv` returns `None` once all have finished
+            let (active_tx, mut active_rx) = mpsc::channel::<()>(1);
+
+            loop {
+                let (stream, peer) = tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            warn!("接受 TCP 连接失败: {}", e);
+                            tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                            continue;
+                        }
+                    },
+                    _ = shutdown.clone().requested() => break,
+                };
+
+                let acceptor = self.acceptor.clone();
+                let service = service.clone();
+                let shutdown = shutdown.clone();
+                let active = active_tx.clone();
+
+                tokio::spawn(async move {
+                    let _active = active;
+
+                    let stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => stream,
+                        Ok(Err(e)) => {
+                            tracing::debug!("TLS 握手失败 ({}): {}", peer, e);
+                            return;
+                        }
+                        Err(_) => {
+                            tracing::debug!("TLS 握手超时 ({})", peer);
+                            return;
+                        }
+                    };
+
+                    let service = service_fn(move |mut req: warp::http::Request<warp::hyper::Body>| {
+                        req.extensions_mut().insert(PeerAddr(peer));
+                        service.clone().call(req)
+                    });
+
+                    let conn = Http::new().serve_connection(stream, service).with_upgrades();
+                    tokio::pin!(conn);
+
+                    let result = tokio::select! {
+                        result = conn.as_mut() => result,
+                        _ = shutdown.requested() => {
+                            conn.as_mut().graceful_shutdown();
+                            conn.await
+                        }
+                    };
+                    if let Err(e) = result {
+                        tracing::debug!("TLS 连接错误 ({}): {}", peer, e);
+                    }
+                });
+            }
+
+            drop(active_tx);
+            let _ = active_rx.recv().await;
+        };
+
+        Ok((local_addr, server))
+    }
+}
+
+/// Plain-HTTP listener that permanently redirects every request to HTTPS.
+pub fn redirect_to_https(
+    https_port: u16,
+) -> impl Filter<Extract = (warp::reply::Response,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("host")
+        .and(warp::path::full())
+        .and(
+            warp::query::raw()
+                .map(Some)
+                .or(warp::any().map(|| None))
+                .unify(),
+        )
+        .map(move |host: Option<String>, path: warp::path::FullPath, query: Option<String>| {
+            let Some(host) = host else {
+                return StatusCode::BAD_REQUEST.into_response();
+            };
+
+            // Drop any port from Host and use the HTTPS one instead
+            let hostname = match host.rsplit_once(':') {
+                Some((name, port)) if !name.ends_with(']') && port.chars().all(|c| c.is_ascii_digit()) => name,
+                _ => host.as_str(),
+            };
+            let authority = if https_port == 443 {
+                hostname.to_string()
+            } else {
+                format!("{}:{}", hostname, https_port)
+            };
+            let location = match query {
+                Some(query) => format!("https://{}{}?{}", authority, path.as_str(), query),
+                None => format!("https://{}{}", authority, path.as_str()),
+            };
+
+            match location.parse::<Uri>() {
+                // 308 keeps the method and body, unlike 301
+                Ok(uri) => warp::reply::with_header(
+                    StatusCode::PERMANENT_REDIRECT,
+                    header::LOCATION,
+                    uri.to_string(),
+                )
+                .into_response(),
+                Err(_) => StatusCode::BAD_REQUEST.into_response(),
+            }
+        })
+}
+
+fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<CertifiedKey> {
+    let mut cert_reader = BufReader::new(
+        File::open(cert_path).with_context(|| format!("无法打开证书文件 {}", cert_path.display()))?,
+    );
+    let certs = rustls_pemfile::certs(&mut cert_reader)
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("无法解析证书文件 {}", cert_path.display()))?;
+    if certs.is_empty() {
+        anyhow::bail!("证书文件 {} 中没有证书", cert_path.display());
+    }
+
+    let mut key_reader = BufReader::new(
+        File::open(key_path).with_context(|| format!("无法打开私钥文件 {}", key_path.display()))?,
+    );
+    let key = rustls_pemfile::private_key(&mut key_reader)
+        .with_context(|| format!("无法解析私钥文件 {}", key_path.display()))?
+        .with_context(|| format!("私钥文件 {} 中没有私钥", key_path.display()))?;
+
+    let signing_key = rustls::crypto::ring::sign::any_supported_type(&key)
+        .map_err(|e| anyhow::anyhow!("不支持的私钥类型: {}", e))?;
+
+    Ok(CertifiedKey::new(certs, signing_key))
+}
+
+// Polls modification times rather than using inotify so renewals through
+// symlink swaps (certbot, Kubernetes secrets) are picked up as well.
+fn spawn_reloader(resolver: Arc<CertResolver>, cert_path: PathBuf, key_path: PathBuf, interval: Duration) {
+    tokio::spawn(async move {
+        let mut last_seen = modified(&cert_path, &key_path);
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+
+            let current = modified(&cert_path, &key_path);
+            if current == last_seen {
+                continue;
+            }
+
+            // Renewals may write the files one at a time; a half-written file
+            // fails to parse and is retried on the next tick
+            match load_certified_key(&cert_path, &key_path) {
+                Ok(key) => {
+                    resolver.current.store(Arc::new(key));
+                    last_seen = current;
+                    info!("TLS 证书已重新加载: {}", cert_path.display());
+                }
+                Err(e) => error!("TLS 证书重新加载失败，继续使用旧证书: {:#}", e),
+            }
+        }
+    });
+}
+
+fn modified(cert_path: &Path, key_path: &Path) -> (Option<SystemTime>, Option<SystemTime>) {
+    let mtime = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    (mtime(cert_path), mtime(key_path))
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::utils::pagination::{Page, Pagination};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ApiResponse<T> {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -11,6 +14,21 @@ pub struct ApiResponse<T> {
     pub timestamp: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
+    /// Present on list responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+}
+
+/// Payload of endpoints that only report an outcome.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MessageResponse {
+    pub message: String,
+}
+
+impl MessageResponse {
+    pub fn new(message: &str) -> Self {
+        Self { message: message.to_string() }
+    }
 }
 
 pub fn success_response<T: Serialize>(data: T) -> ApiResponse<T> {
@@ -20,6 +38,14 @@ pub fn success_response<T: Serialize>(data: T) -> ApiResponse<T> {
         error: None,
         timestamp: Utc::now(),
         path: None,
+        pagination: None,
+    }
+}
+
+pub fn paginated_response<T: Serialize>(page: Page<T>) -> ApiResponse<Vec<T>> {
+    ApiResponse {
+        pagination: Some(page.pagination),
+        ..success_response(page.items)
     }
 }
 
@@ -30,6 +56,7 @@ pub fn error_response(message: &str) -> ApiResponse<()> {
         error: Some(message.to_string()),
         timestamp: Utc::now(),
         path: None,
+        pagination: None,
     }
 }
 
@@ -40,6 +67,7 @@ pub fn success_response_with_path<T: Serialize>(data: T, path: &str) -> ApiRespo
         error: None,
         timestamp: Utc::now(),
         path: Some(path.to_string()),
+        pagination: None,
     }
 }
 
@@ -50,5 +78,6 @@ pub fn error_response_with_path(message: &str, path: &str) -> ApiResponse<()> {
         error: Some(message.to_string()),
         timestamp: Utc::now(),
         path: Some(path.to_string()),
+        pagination: None,
     }
 }
\ No newline at end of file
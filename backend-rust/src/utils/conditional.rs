@@ -0,0 +1,266 @@
+use std::convert::Infallible;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+use warp::{Filter, Reply, http::{HeaderMap, StatusCode, header}};
+
+use crate::utils::{
+    api_response::{paginated_response, success_response, ApiResponse},
+    pagination::Page,
+};
+
+/// ETag and Last-Modified for a content representation, derived from the
+/// `(id, updated_at)` pairs of the rows it contains.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validators {
+    pub etag: String,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+impl Validators {
+    pub fn from_versions<I>(versions: I) -> Self
+    where
+        I: IntoIterator<Item = (Uuid, DateTime<Utc>)>,
+    {
+        Self::from_versions_with(versions, &[])
+    }
+
+    /// Validators for one page of a list. The pagination metadata is part of the
+    /// tag, so a row added on a later page still changes the total.
+    pub fn from_page<T, F>(page: &Page<T>, version: F) -> Self
+    where
+        F: Fn(&T) -> (Uuid, DateTime<Utc>),
+    {
+        let metadata = serde_json::to_vec(&page.pagination).unwrap_or_default();
+        Self::from_versions_with(page.items.iter().map(version), &metadata)
+    }
+
+    /// Like `from_versions`, for a representation that also depends on values
+    /// not covered by any row's `updated_at`, such as counts.
+    pub fn from_versions_with<I>(versions: I, extra: &[u8]) -> Self
+    where
+        I: IntoIterator<Item = (Uuid, DateTime<Utc>)>,
+    {
+        let mut hash = Fnv64::new();
+        let mut count: u64 = 0;
+        let mut last_modified: Option<DateTime<Utc>> = None;
+
+        for (id, updated_at) in versions {
+            hash.write(id.as_bytes());
+            hash.write(&updated_at.timestamp_micros().to_be_bytes());
+            count += 1;
+            last_modified = last_modified.max(Some(updated_at));
+        }
+        hash.write(extra);
+
+        // The count is part of the tag so removing a row always changes it
+        Validators {
+            etag: format!("\"{:x}-{:016x}\"", count, hash.finish()),
+            last_modified,
+        }
+    }
+
+    pub fn single(id: Uuid, updated_at: DateTime<Utc>) -> Self {
+        Self::from_versions([(id, updated_at)])
+    }
+}
+
+#[derive(Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Header)]
+pub struct ConditionalHeaders {
+    /// ETag of the cached copy; a match answers `304 Not Modified`
+    #[param(rename = "If-None-Match")]
+    pub if_none_match: Option<String>,
+    /// Last-Modified of the cached copy, as an HTTP date
+    #[param(rename = "If-Modified-Since")]
+    pub if_modified_since: Option<String>,
+}
+
+impl ConditionalHeaders {
+    /// RFC 7232 §6: If-None-Match takes precedence over If-Modified-Since.
+    pub fn is_fresh(&self, validators: &Validators) -> bool {
+        if let Some(if_none_match) = &self.if_none_match {
+            return if_none_match
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || strip_encoding_suffix(tag) == validators.etag);
+        }
+
+        match (&self.if_modified_since, validators.last_modified) {
+            (Some(since), Some(last_modified)) => DateTime::parse_from_rfc2822(since)
+                .map(|since| last_modified.timestamp() <= since.timestamp())
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+}
+
+/// `If-Match` of an update: the ETags the client last read. Updates that
+/// send one only apply while the row still has one of those tags.
+#[derive(Debug, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Header)]
+pub struct IfMatch {
+    /// ETag from the last read; a stale tag answers `412 Precondition Failed`
+    /// with the current representation
+    #[param(rename = "If-Match")]
+    pub if_match: Option<String>,
+}
+
+impl IfMatch {
+    /// RFC 7232 §3.1: strong comparison, so weak tags never match; `*` matches
+    /// any current representation.
+    pub fn allows(&self, validators: &Validators) -> bool {
+        let Some(if_match) = &self.if_match else {
+            return true;
+        };
+        if_match
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || (!tag.starts_with("W/") && strip_encoding_suffix(tag) == validators.etag))
+    }
+}
+
+/// Failure of an update whose `If-Match` no longer matches: the row as it is,
+/// read under the update's lock, to answer the `412` with.
+#[derive(Debug, thiserror::Error)]
+#[error("资源已被修改")]
+pub struct Stale<T: std::fmt::Debug>(pub T);
+
+pub fn if_match() -> impl Filter<Extract = (IfMatch,), Error = Infallible> + Clone {
+    warp::header::headers_cloned().map(|headers: HeaderMap| IfMatch {
+        if_match: headers
+            .get(header::IF_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+    })
+}
+
+pub fn headers() -> impl Filter<Extract = (ConditionalHeaders,), Error = Infallible> + Clone {
+    warp::header::headers_cloned().map(|headers: HeaderMap| {
+        let get = |name: header::HeaderName| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+
+        ConditionalHeaders {
+            if_none_match: get(header::IF_NONE_MATCH),
+            if_modified_since: get(header::IF_MODIFIED_SINCE),
+        }
+    })
+}
+
+/// Reply with the `ApiResponse` envelope, or `304 Not Modified` when the
+/// client's cached copy is still current.
+pub fn reply<T: Serialize>(
+    data: T,
+    validators: Validators,
+    conditional: &ConditionalHeaders,
+) -> warp::reply::Response {
+    reply_with(success_response(data), validators, conditional)
+}
+
+/// Like `reply`, for one page of a list.
+pub fn reply_page<T: Serialize>(
+    page: Page<T>,
+    validators: Validators,
+    conditional: &ConditionalHeaders,
+) -> warp::reply::Response {
+    reply_with(paginated_response(page), validators, conditional)
+}
+
+fn reply_with<T: Serialize>(
+    body: ApiResponse<T>,
+    validators: Validators,
+    conditional: &ConditionalHeaders,
+) -> warp::reply::Response {
+    let mut response = if conditional.is_fresh(&validators) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        warp::reply::json(&body).into_response()
+    };
+
+    let headers = response.headers_mut();
+    if let Ok(etag) = validators.etag.parse() {
+        headers.insert(header::ETAG, etag);
+    }
+    if let Some(last_modified) = validators.last_modified {
+        if let Ok(value) = http_date(last_modified).parse() {
+            headers.insert(header::LAST_MODIFIED, value);
+        }
+    }
+    // Cacheable, but always revalidated so edits show up immediately
+    headers.insert(header::CACHE_CONTROL, "no-cache".parse().unwrap());
+
+    response
+}
+
+/// Reply to an update the client may chain further updates on: the
+/// `ApiResponse` envelope with the new ETag. `data` is `None` for callers who
+/// may not read what they changed; they get the ETag alone.
+pub fn reply_updated<T: Serialize>(data: Option<T>, validators: Validators) -> warp::reply::Response {
+    let body = ApiResponse {
+        success: true,
+        data,
+        error: None,
+        timestamp: Utc::now(),
+        path: None,
+        pagination: None,
+    };
+    reply_with(body, validators, &ConditionalHeaders::default())
+}
+
+/// `412 Precondition Failed` for an update based on a stale read, with the
+/// current representation, unless the caller may not read it, and its ETag
+/// to retry from.
+pub fn reply_stale<T: Serialize>(current: Option<T>, validators: Validators, message: &str) -> warp::reply::Response {
+    let body = ApiResponse {
+        success: false,
+        data: current,
+        error: Some(message.to_string()),
+        timestamp: Utc::now(),
+        path: None,
+        pagination: None,
+    };
+    let mut response = reply_with(body, validators, &ConditionalHeaders::default());
+    *response.status_mut() = StatusCode::PRECONDITION_FAILED;
+    response
+}
+
+pub fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+// Compression appends `-gzip` / `-br` to the tag of the encoded representation
+fn strip_encoding_suffix(tag: &str) -> String {
+    let tag = tag.strip_prefix("W/").unwrap_or(tag);
+    for suffix in ["-gzip\"", "-br\""] {
+        if let Some(stripped) = tag.strip_suffix(suffix) {
+            return format!("{}\"", stripped);
+        }
+    }
+    tag.to_string()
+}
+
+// FNV-1a: stable across processes and releases, unlike `DefaultHasher`
+struct Fnv64(u64);
+
+impl Fnv64 {
+    fn new() -> Self {
+        Fnv64(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,64 @@
+//! Update bodies with JSON Merge Patch (RFC 7396) semantics: a member left
+//! out keeps its value, `null` clears it.
+//!
+//! Use the field deserializers with `#[serde(default, deserialize_with = "...")]`,
+//! so a missing member stays `None`.
+
+use serde::{de::{DeserializeOwned, Error}, Deserialize, Deserializer};
+use warp::{hyper::body::Bytes, Filter, Rejection};
+
+use crate::errors::AppError;
+
+/// Deserialize an update body sent as `application/merge-patch+json` or
+/// `application/json`. Malformed bodies, including `null` for a field that
+/// cannot be cleared, are rejected with a 400 saying what was wrong.
+pub fn body<T>() -> impl Filter<Extract = (T,), Error = Rejection> + Clone
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    warp::header::optional::<String>("content-type")
+        .and_then(|content_type: Option<String>| async move {
+            let essence = content_type.as_deref().map(|ct| ct.split(';').next().unwrap_or("").trim());
+            match essence {
+                None => Ok::<_, Rejection>(()),
+                Some(essence)
+                    if essence.eq_ignore_ascii_case("application/json")
+                        || essence.eq_ignore_ascii_case("application/merge-patch+json") =>
+                {
+                    Ok(())
+                }
+                Some(essence) => Err(warp::reject::custom(AppError::Validation(format!(
+                    "不支持的 Content-Type: {}",
+                    essence
+                )))),
+            }
+        })
+        .untuple_one()
+        .and(warp::body::bytes())
+        .and_then(|body: Bytes| async move {
+            serde_json::from_slice::<T>(&body).map_err(|e| {
+                warp::reject::custom(AppError::Validation(format!("请求体无效: {}", e)))
+            })
+        })
+}
+
+/// A nullable column: `None` when absent, `Some(None)` for `null`.
+pub fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// A column that cannot be cleared: `None` when absent, an error for `null`.
+pub fn non_null<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    match Option::<T>::deserialize(deserializer)? {
+        Some(value) => Ok(Some(value)),
+        None => Err(D::Error::custom("该字段不能为 null")),
+    }
+}
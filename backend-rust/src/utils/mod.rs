@@ -1,2 +1,5 @@
 pub mod api_response;
-pub mod jwt;
\ No newline at end of file
+pub mod conditional;
+pub mod jwt;
+pub mod merge_patch;
+pub mod pagination;
\ No newline at end of file
@@ -0,0 +1,278 @@
+//! Offset and keyset pagination and whitelisted sorting, shared by the list endpoints.
+//!
+//! Every list accepts `page` + `limit` (offset) or `cursor` + `limit` (keyset). Cursors
+//! are opaque; they encode the key of the last row of the previous page and are only
+//! issued while the list is in its natural order, which is the order the key follows.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use utoipa::ToSchema;
+use warp::{Filter, Rejection};
+
+use crate::errors::AppError;
+
+pub const DEFAULT_LIMIT: i64 = 20;
+pub const MAX_LIMIT: i64 = 100;
+
+/// Pagination metadata returned next to `data` in the `ApiResponse` envelope.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Pagination {
+    pub limit: i64,
+    /// Current page, in offset mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    /// Rows matching the filters, in offset mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<i64>,
+    pub has_more: bool,
+    /// Pass as `cursor` to fetch the next page; only issued in the default order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// One page of a list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub pagination: Pagination,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            pagination: self.pagination,
+        }
+    }
+}
+
+/// Where a page starts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Position<K> {
+    /// 1-based page number
+    Page(i64),
+    /// Rows strictly after this key
+    After(K),
+}
+
+/// A validated `page`/`cursor` + `limit` pair. `K` is the keyset the list is ordered by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRequest<K> {
+    pub limit: i64,
+    pub position: Position<K>,
+    /// Whether the list is in its natural order, so cursors can be issued
+    pub keyset: bool,
+}
+
+impl<K> Default for PageRequest<K> {
+    fn default() -> Self {
+        Self {
+            limit: DEFAULT_LIMIT,
+            position: Position::Page(1),
+            keyset: true,
+        }
+    }
+}
+
+impl<K: Serialize + DeserializeOwned> PageRequest<K> {
+    pub fn parse(
+        page: Option<i64>,
+        limit: Option<i64>,
+        cursor: Option<&str>,
+        keyset: bool,
+    ) -> Result<Self, String> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT);
+        if !(1..=MAX_LIMIT).contains(&limit) {
+            return Err(format!("limit 必须在 1 到 {} 之间", MAX_LIMIT));
+        }
+
+        let position = match (page, cursor) {
+            (Some(_), Some(_)) => return Err("page 和 cursor 不能同时使用".to_string()),
+            (None, Some(_)) if !keyset => return Err("cursor 只能用于默认排序".to_string()),
+            (None, Some(cursor)) => Position::After(decode_cursor(cursor)?),
+            (Some(page), None) if page < 1 || page.checked_mul(limit).is_none() => {
+                return Err("page 超出范围".to_string());
+            }
+            (page, None) => Position::Page(page.unwrap_or(1)),
+        };
+
+        Ok(Self { limit, position, keyset })
+    }
+
+    /// Rows to skip, in offset mode.
+    pub fn offset(&self) -> Option<i64> {
+        match self.position {
+            Position::Page(page) => Some((page - 1) * self.limit),
+            Position::After(_) => None,
+        }
+    }
+
+    /// Rows to fetch: one more than the limit, to learn whether another page follows.
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit + 1
+    }
+
+    /// Build the page from rows fetched with `fetch_limit`. `total` is only
+    /// counted in offset mode; keyset pages skip the count on purpose.
+    pub fn finish<T>(&self, mut rows: Vec<T>, total: Option<i64>, key: impl Fn(&T) -> K) -> Page<T> {
+        let has_more = rows.len() as i64 > self.limit;
+        rows.truncate(self.limit as usize);
+
+        let next_cursor = match rows.last() {
+            Some(last) if has_more && self.keyset => Some(encode_cursor(&key(last))),
+            _ => None,
+        };
+        let page = match self.position {
+            Position::Page(page) => Some(page),
+            Position::After(_) => None,
+        };
+
+        Page {
+            items: rows,
+            pagination: Pagination {
+                limit: self.limit,
+                page,
+                total,
+                total_pages: total.map(|total| (total + self.limit - 1) / self.limit),
+                has_more,
+                next_cursor,
+            },
+        }
+    }
+}
+
+impl<K: Serialize> fmt::Display for PageRequest<K> {
+    /// A canonical form, for cache keys.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.position {
+            Position::Page(page) => write!(f, "limit={}&page={}", self.limit, page),
+            Position::After(key) => write!(f, "limit={}&cursor={}", self.limit, encode_cursor(key)),
+        }
+    }
+}
+
+fn encode_cursor<K: Serialize>(key: &K) -> String {
+    let json = serde_json::to_vec(key).expect("cursor keys serialize");
+    URL_SAFE_NO_PAD.encode(json)
+}
+
+fn decode_cursor<K: DeserializeOwned>(cursor: &str) -> Result<K, String> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(|| "无效的 cursor".to_string())
+}
+
+/// Deserialize a list's query string. Unknown parameters and malformed values are
+/// rejected with a 400 saying what was wrong, which `warp::query` does not report.
+pub fn query<T>() -> impl Filter<Extract = (T,), Error = Rejection> + Clone
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    warp::query::raw()
+        .or(warp::any().map(String::new))
+        .unify()
+        .and_then(|raw: String| async move {
+            serde_urlencoded::from_str::<T>(&raw).map_err(|e| {
+                warp::reject::custom(AppError::Validation(format!("查询参数无效: {}", e)))
+            })
+        })
+}
+
+/// The fields a list may be sorted by.
+pub trait SortField: Copy + Default + PartialEq + 'static {
+    /// Accepted names for the `sort` parameter.
+    const FIELDS: &'static [(&'static str, Self)];
+
+    /// Columns to order by, most significant first. `id` is appended as a tiebreaker.
+    fn columns(self) -> &'static [&'static str];
+}
+
+/// A whitelisted `sort` parameter: a field name, prefixed with `-` for descending.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sort<F> {
+    pub field: F,
+    pub descending: bool,
+}
+
+impl<F: SortField> Sort<F> {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The ORDER BY clause. NULLs sort last in both directions.
+    pub fn order_by(&self) -> String {
+        let direction = if self.descending { "DESC" } else { "ASC" };
+        self.field
+            .columns()
+            .iter()
+            .chain(["id"].iter())
+            .map(|column| format!("{} {} NULLS LAST", column, direction))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Apply the direction to an ascending comparison.
+    pub fn direct(&self, ordering: Ordering) -> Ordering {
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    /// Compare nullable values the way `order_by` does: NULLs last in both directions.
+    pub fn compare_nullable<T: Ord>(&self, a: &Option<T>, b: &Option<T>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => self.direct(a.cmp(b)),
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+        }
+    }
+}
+
+impl<F: SortField> FromStr for Sort<F> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (descending, name) = match s.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, s),
+        };
+
+        F::FIELDS
+            .iter()
+            .find(|(field_name, _)| *field_name == name)
+            .map(|(_, field)| Sort { field: *field, descending })
+            .ok_or_else(|| {
+                let allowed: Vec<&str> = F::FIELDS.iter().map(|(name, _)| *name).collect();
+                format!("不支持的排序字段 `{}`，可选: {}", name, allowed.join(", "))
+            })
+    }
+}
+
+impl<F: SortField> fmt::Display for Sort<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = F::FIELDS
+            .iter()
+            .find(|(_, field)| *field == self.field)
+            .map(|(name, _)| *name)
+            .unwrap_or_default();
+        write!(f, "{}{}", if self.descending { "-" } else { "" }, name)
+    }
+}
+
+impl<'de, F: SortField> Deserialize<'de> for Sort<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,130 @@
+use serde_json::json;
+use warp::http::StatusCode;
+
+use crate::support::{TestApp, PASSWORD};
+
+#[tokio::test]
+async fn register_creates_a_parent_account_without_exposing_the_password() {
+    let app = TestApp::new();
+
+    let response = app
+        .post(
+            "/api/auth/register",
+            json!({"username": "xiaoming", "email": "xiaoming@example.com", "password": PASSWORD}),
+        )
+        .await;
+
+    let data = response.assert_success(StatusCode::CREATED);
+    assert!(data["user"].get("password").is_none());
+    assert_eq!(data["user"]["role"], "Parent");
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn register_requires_username_email_and_password() {
+    let app = TestApp::new();
+
+    let response = app
+        .post("/api/auth/register", json!({"username": "xiaoming", "email": "", "password": PASSWORD}))
+        .await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "用户名、邮箱和密码不能为空");
+}
+
+#[tokio::test]
+async fn register_rejects_a_taken_email() {
+    let app = TestApp::new();
+    app.seed_user("xiaoming", "xiaoming@example.com").await;
+
+    let response = app
+        .post(
+            "/api/auth/register",
+            json!({"username": "xiaohong", "email": "xiaoming@example.com", "password": PASSWORD}),
+        )
+        .await;
+
+    assert_eq!(response.assert_error(StatusCode::CONFLICT), "该邮箱已被注册");
+}
+
+#[tokio::test]
+async fn register_rejects_a_taken_username() {
+    let app = TestApp::new();
+    app.seed_user("xiaoming", "xiaoming@example.com").await;
+
+    let response = app
+        .post(
+            "/api/auth/register",
+            json!({"username": "xiaoming", "email": "other@example.com", "password": PASSWORD}),
+        )
+        .await;
+
+    assert_eq!(response.assert_error(StatusCode::CONFLICT), "该用户名已被使用");
+}
+
+#[tokio::test]
+async fn login_returns_a_token() {
+    let app = TestApp::new();
+    app.seed_user("xiaoming", "xiaoming@example.com").await;
+
+    let response = app
+        .post("/api/auth/login", json!({"email": "xiaoming@example.com", "password": PASSWORD}))
+        .await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert!(!data["token"].as_str().unwrap().is_empty());
+    assert!(data["user"].get("password").is_none());
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn login_with_a_wrong_password_is_unauthorized() {
+    let app = TestApp::new();
+    app.seed_user("xiaoming", "xiaoming@example.com").await;
+
+    let response = app
+        .post("/api/auth/login", json!({"email": "xiaoming@example.com", "password": "wrong-password"}))
+        .await;
+
+    assert_eq!(response.assert_error(StatusCode::UNAUTHORIZED), "用户名或密码错误");
+}
+
+#[tokio::test]
+async fn login_with_an_unknown_email_gives_the_same_answer() {
+    let app = TestApp::new();
+
+    let response = app
+        .post("/api/auth/login", json!({"email": "nobody@example.com", "password": PASSWORD}))
+        .await;
+
+    assert_eq!(response.assert_error(StatusCode::UNAUTHORIZED), "用户名或密码错误");
+}
+
+#[tokio::test]
+async fn login_requires_email_and_password() {
+    let app = TestApp::new();
+
+    let response = app
+        .post("/api/auth/login", json!({"email": "xiaoming@example.com", "password": ""}))
+        .await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "邮箱和密码不能为空");
+}
+
+#[tokio::test]
+async fn logout_succeeds() {
+    let app = TestApp::new();
+
+    let response = app.send(warp::test::request().method("POST").path("/api/auth/logout")).await;
+
+    response.assert_success(StatusCode::OK);
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn me_requires_authentication() {
+    let app = TestApp::new();
+
+    let response = app.get("/api/auth/me").await;
+
+    assert_eq!(response.assert_error(StatusCode::UNAUTHORIZED), "需要身份验证");
+}
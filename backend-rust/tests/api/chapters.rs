@@ -0,0 +1,185 @@
+use serde_json::json;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+use crate::support::TestApp;
+
+#[tokio::test]
+async fn list_returns_every_chapter() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    app.seed_chapter(&classic, 2, "第二章").await;
+    app.seed_chapter(&classic, 1, "第一章").await;
+
+    let response = app.get("/api/chapters").await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data[0]["number"], 1);
+    assert_eq!(data[1]["number"], 2);
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn list_by_classic_only_includes_its_chapters() {
+    let app = TestApp::new();
+    let sanzijing = app.seed_classic("sanzijing", "三字经").await;
+    let qianziwen = app.seed_classic("qianziwen", "千字文").await;
+    app.seed_chapter(&sanzijing, 1, "第一章").await;
+    app.seed_chapter(&qianziwen, 1, "天地玄黄").await;
+
+    let response = app.get(&format!("/api/classics/{}/chapters", qianziwen.id)).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data.as_array().unwrap().len(), 1);
+    assert_eq!(data[0]["title"], "天地玄黄");
+}
+
+#[tokio::test]
+async fn get_by_id() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+
+    let response = app.get(&format!("/api/chapters/{}", chapter.id)).await;
+
+    response.assert_success(StatusCode::OK);
+    assert!(response.header("etag").is_some());
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn get_unknown_chapter_is_not_found() {
+    let app = TestApp::new();
+
+    let response = app.get(&format!("/api/chapters/{}", Uuid::new_v4())).await;
+
+    response.assert_error(StatusCode::NOT_FOUND);
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn create_returns_the_chapter() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+
+    let response = app
+        .post_as(
+            &admin,
+            "/api/chapters",
+            json!({"classic_id": classic.id, "number": 1, "title": "第一章", "content": "人之初，性本善。"}),
+        )
+        .await;
+
+    let data = response.assert_success(StatusCode::CREATED);
+    assert_eq!(data["classic_id"], classic.id.to_string());
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn create_rejects_a_duplicate_number() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    app.seed_chapter(&classic, 1, "第一章").await;
+
+    let response = app
+        .post_as(&admin, "/api/chapters", json!({"classic_id": classic.id, "number": 1, "title": "重复"}))
+        .await;
+
+    assert_eq!(
+        response.assert_error(StatusCode::BAD_REQUEST),
+        "Chapter number already exists for this classic"
+    );
+}
+
+#[tokio::test]
+async fn create_for_unknown_classic_fails() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+
+    let response = app
+        .post_as(&admin, "/api/chapters", json!({"classic_id": Uuid::new_v4(), "number": 1, "title": "第一章"}))
+        .await;
+
+    response.assert_error(StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn create_rejects_a_body_missing_fields() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+
+    let response = app.post_as(&admin, "/api/chapters", json!({"title": "第一章"})).await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "请求体格式错误");
+}
+
+#[tokio::test]
+async fn update_changes_only_given_fields() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+
+    let response = app
+        .put_as(&admin, &format!("/api/chapters/{}", chapter.id), json!({"content": "人之初，性本善。"}))
+        .await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data["title"], "第一章");
+    assert_eq!(data["content"], "人之初，性本善。");
+}
+
+#[tokio::test]
+async fn patch_with_null_clears_the_content() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let path = format!("/api/chapters/{}", chapter.id);
+    app.put_as(&admin, &path, json!({"content": "人之初，性本善。"})).await.assert_success(StatusCode::OK);
+
+    let response = app.patch_as(&admin, &path, json!({"content": null})).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data["content"], json!(null));
+    assert_eq!(data["title"], "第一章");
+}
+
+#[tokio::test]
+async fn update_unknown_chapter_is_not_found() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+
+    let response = app
+        .put_as(&admin, &format!("/api/chapters/{}", Uuid::new_v4()), json!({"title": "新标题"}))
+        .await;
+
+    response.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_removes_the_chapter_and_its_sentences() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+
+    let response = app.delete_as(&admin, &format!("/api/chapters/{}", chapter.id)).await;
+
+    response.assert_success(StatusCode::OK);
+    assert_body_snapshot!(response);
+    app.get(&format!("/api/sentences/{}", sentence.id)).await.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_unknown_chapter_is_not_found() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+
+    let response = app.delete_as(&admin, &format!("/api/chapters/{}", Uuid::new_v4())).await;
+
+    response.assert_error(StatusCode::NOT_FOUND);
+}
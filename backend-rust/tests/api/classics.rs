@@ -0,0 +1,243 @@
+use serde_json::json;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+use crate::support::TestApp;
+
+#[tokio::test]
+async fn list_is_empty_without_classics() {
+    let app = TestApp::new();
+
+    let response = app.get("/api/classics").await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data, &json!([]));
+}
+
+#[tokio::test]
+async fn list_returns_classics_in_creation_order() {
+    let app = TestApp::new();
+    app.seed_classic("sanzijing", "三字经").await;
+    app.seed_classic("qianziwen", "千字文").await;
+
+    let response = app.get("/api/classics").await;
+
+    response.assert_success(StatusCode::OK);
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn list_revalidates_with_etag() {
+    let app = TestApp::new();
+    app.seed_classic("sanzijing", "三字经").await;
+
+    let first = app.get("/api/classics").await;
+    let etag = first.header("etag").expect("etag").to_string();
+
+    let revalidated = app
+        .send(
+            warp::test::request()
+                .path("/api/classics")
+                .header("if-none-match", &etag),
+        )
+        .await;
+    assert_eq!(revalidated.status, StatusCode::NOT_MODIFIED);
+    assert!(revalidated.text.is_empty());
+
+    // Any edit changes the validator
+    app.seed_classic("qianziwen", "千字文").await;
+    let changed = app
+        .send(
+            warp::test::request()
+                .path("/api/classics")
+                .header("if-none-match", &etag),
+        )
+        .await;
+    assert_eq!(changed.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn get_by_slug() {
+    let app = TestApp::new();
+    app.seed_classic("sanzijing", "三字经").await;
+
+    let response = app.get("/api/classics/sanzijing").await;
+
+    response.assert_success(StatusCode::OK);
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn get_unknown_slug_is_not_found() {
+    let app = TestApp::new();
+
+    let response = app.get("/api/classics/missing").await;
+
+    assert_eq!(response.assert_error(StatusCode::NOT_FOUND), "经典未找到");
+}
+
+#[tokio::test]
+async fn get_rejects_malformed_slug() {
+    let app = TestApp::new();
+
+    let response = app.get("/api/classics/bad_slug").await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "无效的经典标识符");
+}
+
+#[tokio::test]
+async fn create_returns_the_classic() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+
+    let response = app
+        .post_as(
+            &admin,
+            "/api/classics",
+            json!({
+                "slug": "dizigui",
+                "title": "弟子规",
+                "author": "李毓秀",
+                "dynasty": "清",
+                "description": "儿童启蒙读物"
+            }),
+        )
+        .await;
+
+    response.assert_success(StatusCode::CREATED);
+    assert_body_snapshot!(response);
+
+    // New classics start as drafts, hidden until published
+    assert_eq!(response.body["data"]["status"], "draft");
+    app.get("/api/classics/dizigui").await.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn create_requires_title_and_slug() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+
+    let response = app.post_as(&admin, "/api/classics", json!({"slug": "", "title": "弟子规"})).await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "标题和标识符不能为空");
+}
+
+#[tokio::test]
+async fn create_rejects_a_taken_slug() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    app.seed_classic("sanzijing", "三字经").await;
+
+    let response = app.post_as(&admin, "/api/classics", json!({"slug": "sanzijing", "title": "三字经"})).await;
+
+    assert_eq!(response.assert_error(StatusCode::CONFLICT), "该标识符已存在");
+}
+
+#[tokio::test]
+async fn update_changes_only_given_fields() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+
+    let response = app
+        .put_as(&admin, &format!("/api/classics/{}", classic.id), json!({"description": "蒙学经典"}))
+        .await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data["title"], "三字经");
+    assert_eq!(data["description"], "蒙学经典");
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn patch_with_null_clears_a_nullable_field() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+
+    let response = app.patch_as(&admin, &format!("/api/classics/{}", classic.id), json!({"author": null})).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data["author"], json!(null));
+    assert_eq!(data["dynasty"], "宋");
+    assert_eq!(data["title"], "三字经");
+}
+
+#[tokio::test]
+async fn patch_cannot_clear_the_title() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let path = format!("/api/classics/{}", classic.id);
+
+    let response = app.patch_as(&admin, &path, json!({"title": null, "author": null})).await;
+
+    let error = response.assert_error(StatusCode::BAD_REQUEST);
+    assert!(error.contains("不能为 null"), "{}", error);
+    let data = app.get("/api/classics/sanzijing").await;
+    assert_eq!(data.assert_success(StatusCode::OK)["author"], "王应麟");
+}
+
+#[tokio::test]
+async fn update_with_a_stale_if_match_returns_the_current_classic() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let path = format!("/api/classics/{}", classic.id);
+    let read = app.get("/api/classics/sanzijing").await.header("etag").expect("etag").to_string();
+    app.put_as(&admin, &path, json!({"description": "蒙学经典"})).await.assert_success(StatusCode::OK);
+
+    let response = app
+        .send(
+            warp::test::request()
+                .method("PUT")
+                .path(&path)
+                .header("authorization", format!("Bearer {}", admin))
+                .header("if-match", &read)
+                .json(&json!({"description": "覆盖"})),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::PRECONDITION_FAILED, "body: {}", response.text);
+    assert_eq!(response.body["error"], "经典已被他人修改，请基于当前版本重新提交");
+    assert_eq!(response.body["data"]["description"], "蒙学经典");
+    let current = app.get("/api/classics/sanzijing").await;
+    assert_eq!(response.header("etag"), current.header("etag"));
+}
+
+#[tokio::test]
+async fn update_unknown_classic_is_not_found() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+
+    let response = app
+        .put_as(&admin, &format!("/api/classics/{}", Uuid::new_v4()), json!({"title": "新标题"}))
+        .await;
+
+    assert_eq!(response.assert_error(StatusCode::NOT_FOUND), "经典未找到");
+}
+
+#[tokio::test]
+async fn delete_removes_the_classic_and_its_chapters() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("qianziwen", "千字文").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+
+    let response = app.delete_as(&admin, &format!("/api/classics/{}", classic.id)).await;
+
+    response.assert_success(StatusCode::OK);
+    assert_body_snapshot!(response);
+    app.get("/api/classics/qianziwen").await.assert_error(StatusCode::NOT_FOUND);
+    app.get(&format!("/api/chapters/{}", chapter.id)).await.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_unknown_classic_is_not_found() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+
+    let response = app.delete_as(&admin, &format!("/api/classics/{}", Uuid::new_v4())).await;
+
+    assert_eq!(response.assert_error(StatusCode::NOT_FOUND), "经典未找到");
+}
@@ -0,0 +1,112 @@
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use serde_json::{json, Value};
+use warp::http::StatusCode;
+
+use xiaoxiao_dushulang_backend::middleware::compression::{negotiate, Encoding};
+
+use crate::support::{TestApp, TestResponse};
+
+/// A chapter whose JSON is well past the size worth compressing.
+async fn seed_long_chapter(app: &TestApp) -> String {
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let path = format!("/api/chapters/{}", chapter.id);
+    app.put_as(&admin, &path, json!({ "content": "人之初，性本善。性相近，习相远。".repeat(40) }))
+        .await
+        .assert_success(StatusCode::OK);
+    path
+}
+
+async fn get_encoded(app: &TestApp, path: &str, accept_encoding: &str, if_none_match: Option<&str>) -> TestResponse {
+    let mut request = warp::test::request().path(path).header("accept-encoding", accept_encoding);
+    if let Some(etag) = if_none_match {
+        request = request.header("if-none-match", etag);
+    }
+    app.send(request).await
+}
+
+fn varies_on_encoding(response: &TestResponse) -> bool {
+    response.headers.get_all("vary").iter().any(|value| value == "accept-encoding")
+}
+
+#[test]
+fn negotiation_honours_q_values_and_prefers_brotli_on_ties() {
+    assert_eq!(negotiate("gzip, br"), Some(Encoding::Brotli));
+    assert_eq!(negotiate("gzip;q=1.0, br;q=0.5"), Some(Encoding::Gzip));
+    assert_eq!(negotiate("br;q=0, gzip"), Some(Encoding::Gzip));
+    assert_eq!(negotiate("x-gzip"), Some(Encoding::Gzip));
+    assert_eq!(negotiate("*"), Some(Encoding::Brotli));
+    assert_eq!(negotiate("identity"), None);
+    assert_eq!(negotiate("gzip;q=0, br;q=0"), None);
+}
+
+#[tokio::test]
+async fn large_bodies_are_compressed_with_the_negotiated_encoding() {
+    let app = TestApp::new();
+    let path = seed_long_chapter(&app).await;
+    let plain = app.get(&path).await;
+
+    let response = get_encoded(&app, &path, "gzip", None).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.header("content-encoding"), Some("gzip"));
+    assert!(varies_on_encoding(&response));
+    let mut decoded = String::new();
+    GzDecoder::new(response.bytes.as_slice()).read_to_string(&mut decoded).unwrap();
+    let decoded: Value = serde_json::from_str(&decoded).unwrap();
+    assert_eq!(decoded["data"], plain.body["data"]);
+    assert!(response.bytes.len() < plain.bytes.len());
+
+    let response = get_encoded(&app, &path, "br;q=0.8, gzip;q=0.5", None).await;
+    assert_eq!(response.header("content-encoding"), Some("br"));
+}
+
+#[tokio::test]
+async fn small_bodies_are_sent_as_is_but_still_vary() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+
+    let response = get_encoded(&app, &format!("/api/classics/{}", classic.slug), "gzip, br", None).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.text.len() < 860, "fixture grew past the minimum: {}", response.text.len());
+    assert_eq!(response.header("content-encoding"), None);
+    assert!(varies_on_encoding(&response));
+    assert_eq!(response.body["success"], true);
+}
+
+#[tokio::test]
+async fn encoded_bodies_carry_their_own_etag() {
+    let app = TestApp::new();
+    let path = seed_long_chapter(&app).await;
+    let plain = app.get(&path).await.header("etag").expect("etag").to_string();
+
+    let gzip = get_encoded(&app, &path, "gzip", None).await;
+    let brotli = get_encoded(&app, &path, "br", None).await;
+
+    assert_eq!(gzip.header("etag"), Some(format!("{}-gzip\"", plain.trim_end_matches('"')).as_str()));
+    assert_eq!(brotli.header("etag"), Some(format!("{}-br\"", plain.trim_end_matches('"')).as_str()));
+}
+
+#[tokio::test]
+async fn not_modified_answers_with_the_encoded_etag_the_client_holds() {
+    let app = TestApp::new();
+    let path = seed_long_chapter(&app).await;
+    let etag = get_encoded(&app, &path, "gzip", None).await.header("etag").expect("etag").to_string();
+    assert!(etag.ends_with("-gzip\""));
+
+    let response = get_encoded(&app, &path, "gzip", Some(&etag)).await;
+
+    assert_eq!(response.status, StatusCode::NOT_MODIFIED);
+    assert!(response.bytes.is_empty());
+    assert_eq!(response.header("etag"), Some(etag.as_str()));
+    assert!(varies_on_encoding(&response));
+
+    let plain = app.get(&path).await.header("etag").expect("etag").to_string();
+    let response = get_encoded(&app, &path, "gzip", Some(&plain)).await;
+    assert_eq!(response.status, StatusCode::NOT_MODIFIED);
+    assert_eq!(response.header("etag"), Some(plain.as_str()));
+}
@@ -0,0 +1,101 @@
+use xiaoxiao_dushulang_backend::config::{Config, ConfigError, CorsConfig, Environment, DEFAULT_JWT_SECRET};
+
+/// A production configuration that passes validation.
+fn production() -> Config {
+    let mut config = Config::defaults(Environment::Production);
+    config.auth.jwt_secret = "j".repeat(32);
+    config.auth.session_secret = "s".repeat(32);
+    config.database.url = "postgres://app:s3cret@db:5432/xiaoxiao_dushulang".to_string();
+    config.cors.allowed_origins = vec!["https://xiaoxiao.example.com".to_string()];
+    config
+}
+
+fn problems(config: &Config) -> Vec<String> {
+    match config.validate() {
+        Ok(()) => Vec::new(),
+        Err(ConfigError::Invalid(problems)) => problems,
+        Err(e) => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn a_complete_production_config_is_accepted() {
+    assert_eq!(problems(&production()), Vec::<String>::new());
+}
+
+#[test]
+fn production_refuses_default_and_short_secrets() {
+    let mut config = production();
+    config.auth.jwt_secret = DEFAULT_JWT_SECRET.to_string();
+    config.auth.session_secret = "short".to_string();
+
+    assert_eq!(
+        problems(&config),
+        ["生产环境不能使用默认的 auth.jwt_secret", "生产环境的 auth.session_secret 至少需要 32 个字符"]
+    );
+    assert_eq!(problems(&Config { environment: Environment::Development, ..config }), Vec::<String>::new());
+}
+
+#[test]
+fn production_refuses_a_database_without_password() {
+    let mut config = production();
+    config.database.url = "postgres://app@db:5432/xiaoxiao_dushulang".to_string();
+
+    assert_eq!(problems(&config), ["生产环境的数据库密码不能为空"]);
+}
+
+#[test]
+fn production_refuses_a_cors_wildcard() {
+    let mut config = production();
+    config.cors.allowed_origins.push("*".to_string());
+
+    assert_eq!(problems(&config), ["生产环境的 cors.allowed_origins 不能包含 *，请列出允许的来源"]);
+    let subdomains = Config {
+        cors: CorsConfig {
+            allowed_origins: vec!["https://*.example.com".to_string()],
+            ..config.cors.clone()
+        },
+        ..config
+    };
+    assert_eq!(problems(&subdomains), Vec::<String>::new());
+}
+
+#[test]
+fn tls_needs_a_certificate_and_a_separate_redirect_port() {
+    let mut config = production();
+    config.tls.enabled = true;
+    config.tls.redirect_http_port = Some(config.server.port);
+
+    assert_eq!(
+        problems(&config),
+        ["启用 TLS 时必须配置 tls.cert_path 和 tls.key_path", "tls.redirect_http_port 不能与 server.port 相同"]
+    );
+}
+
+#[test]
+fn production_terminating_tls_does_not_trust_proxy_headers() {
+    let mut config = production();
+    config.tls.enabled = true;
+    config.tls.cert_path = "/etc/tls/cert.pem".to_string();
+    config.tls.key_path = "/etc/tls/key.pem".to_string();
+    config.rate_limit.trust_proxy = true;
+
+    assert_eq!(problems(&config), ["直接提供 TLS 时不能启用 rate_limit.trust_proxy"]);
+    config.rate_limit.trust_proxy = false;
+    assert_eq!(problems(&config), Vec::<String>::new());
+}
+
+#[test]
+fn production_file_keeps_the_port_and_does_not_trust_proxies() {
+    let config: Config = config::Config::builder()
+        .add_source(config::Config::try_from(&production()).unwrap())
+        .add_source(config::File::with_name(concat!(env!("CARGO_MANIFEST_DIR"), "/config/production")))
+        .build()
+        .unwrap()
+        .try_deserialize()
+        .unwrap();
+
+    assert_eq!(config.server.port, 9005);
+    assert!(!config.rate_limit.trust_proxy);
+    assert_eq!(problems(&config), Vec::<String>::new());
+}
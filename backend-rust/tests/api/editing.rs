@@ -0,0 +1,303 @@
+use serde_json::{json, Value};
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+use xiaoxiao_dushulang_backend::models::{Chapter, EntityType, Revision, RevisionAction};
+use xiaoxiao_dushulang_backend::utils::jwt::create_jwt_token;
+
+use crate::support::TestApp;
+
+/// `(number, text)` of a chapter's sentences, in order.
+async fn sentences_of(app: &TestApp, chapter: &Chapter) -> Vec<(i64, String)> {
+    let response = app.get(&format!("/api/chapters/{}/sentences", chapter.id)).await;
+    let data = response.assert_success(StatusCode::OK);
+    data.as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["number"].as_i64().unwrap(), s["text"].as_str().unwrap().to_string()))
+        .collect()
+}
+
+/// A sentence's revisions, oldest first.
+async fn revisions_of(app: &TestApp, id: Uuid) -> Vec<Revision> {
+    app.state.repos.revisions.list(EntityType::Sentence, id).await.unwrap()
+}
+
+/// A chapter's revisions, oldest first.
+async fn chapter_revisions(app: &TestApp, id: Uuid) -> Vec<Revision> {
+    app.state.repos.revisions.list(EntityType::Chapter, id).await.unwrap()
+}
+
+fn numbered(texts: &[&str]) -> Vec<(i64, String)> {
+    texts.iter().enumerate().map(|(i, text)| (i as i64 + 1, text.to_string())).collect()
+}
+
+async fn seed_chapter_with(app: &TestApp, texts: &[&str]) -> Chapter {
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    for (i, text) in texts.iter().enumerate() {
+        app.seed_sentence(&chapter, i as i32 + 1, text).await;
+    }
+    chapter
+}
+
+#[tokio::test]
+async fn insert_at_shifts_later_sentences() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let chapter = seed_chapter_with(&app, &["人之初", "性相近", "习相远"]).await;
+
+    let response = app
+        .post_as(
+            &admin,
+            &format!("/api/chapters/{}/sentences/insert", chapter.id),
+            json!({ "position": 2, "text": "性本善" }),
+        )
+        .await;
+
+    let data = response.assert_success(StatusCode::CREATED);
+    assert_eq!(data["number"], 2);
+    assert_eq!(sentences_of(&app, &chapter).await, numbered(&["人之初", "性本善", "性相近", "习相远"]));
+
+    let inserted = revisions_of(&app, data["id"].as_str().unwrap().parse().unwrap()).await;
+    assert_eq!(inserted.len(), 1);
+    assert_eq!(inserted[0].action, RevisionAction::Create);
+    assert_eq!(inserted[0].after.as_ref().unwrap()["text"], "性本善");
+    let shifted = &app.state.repos.sentences.find_by_chapter_id(chapter.id).await.unwrap()[2];
+    assert_eq!(revisions_of(&app, shifted.id).await.len(), 1, "shifting to make room is not recorded");
+}
+
+#[tokio::test]
+async fn insert_at_rejects_positions_past_the_end() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let chapter = seed_chapter_with(&app, &["人之初"]).await;
+
+    let response = app
+        .post_as(
+            &admin,
+            &format!("/api/chapters/{}/sentences/insert", chapter.id),
+            json!({ "position": 3, "text": "性本善" }),
+        )
+        .await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "position must be between 1 and 2");
+}
+
+#[tokio::test]
+async fn move_within_a_chapter() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let chapter = seed_chapter_with(&app, &["甲", "乙", "丙"]).await;
+    let last = &app.state.repos.sentences.find_by_chapter_id(chapter.id).await.unwrap()[2];
+
+    let response = app.post_as(&admin, &format!("/api/sentences/{}/move", last.id), json!({ "position": 1 })).await;
+
+    assert_eq!(response.assert_success(StatusCode::OK)["number"], 1);
+    assert_eq!(sentences_of(&app, &chapter).await, numbered(&["丙", "甲", "乙"]));
+}
+
+#[tokio::test]
+async fn move_into_another_chapter_renumbers_both() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let chapter = seed_chapter_with(&app, &["甲", "乙", "丙"]).await;
+    let classic = app.state.repos.classics.find_by_id(chapter.classic_id).await.unwrap().unwrap();
+    let other = app.seed_chapter(&classic, 2, "第二章").await;
+    app.seed_sentence(&other, 1, "丁").await;
+    let first = &app.state.repos.sentences.find_by_chapter_id(chapter.id).await.unwrap()[0];
+
+    let response = app
+        .post_as(
+            &admin,
+            &format!("/api/sentences/{}/move", first.id),
+            json!({ "chapter_id": other.id, "position": 1 }),
+        )
+        .await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data["chapter_id"], other.id.to_string());
+    assert_eq!(sentences_of(&app, &chapter).await, numbered(&["乙", "丙"]));
+    assert_eq!(sentences_of(&app, &other).await, numbered(&["甲", "丁"]));
+
+    let response = app
+        .post_as(&admin, &format!("/api/sentences/{}/move", first.id), json!({ "chapter_id": Uuid::new_v4() }))
+        .await;
+    response.assert_error(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn renumber_closes_gaps_and_applies_an_order() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let a = app.seed_sentence(&chapter, 3, "甲").await;
+    let b = app.seed_sentence(&chapter, 7, "乙").await;
+    let c = app.seed_sentence(&chapter, 10, "丙").await;
+    let path = format!("/api/chapters/{}/sentences/renumber", chapter.id);
+
+    app.post_as(&admin, &path, json!({})).await.assert_success(StatusCode::OK);
+    assert_eq!(sentences_of(&app, &chapter).await, numbered(&["甲", "乙", "丙"]));
+
+    let response = app.post_as(&admin, &path, json!({ "order": [c.id, a.id, b.id] })).await;
+    let texts: Vec<&Value> = response.assert_success(StatusCode::OK).as_array().unwrap().iter().map(|s| &s["text"]).collect();
+    assert_eq!(texts, ["丙", "甲", "乙"]);
+    let renumbered = revisions_of(&app, c.id).await;
+    assert_eq!(renumbered.len(), 3);
+    assert_eq!(renumbered[1].before.as_ref().unwrap()["number"], 10);
+    assert_eq!(renumbered[2].before.as_ref().unwrap()["number"], 3);
+    assert_eq!(renumbered[2].after.as_ref().unwrap()["number"], 1);
+
+    let response = app.post_as(&admin, &path, json!({ "order": [c.id, a.id] })).await;
+    response.assert_error(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn split_at_a_character_offset() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let chapter = seed_chapter_with(&app, &["人之初性本善", "性相近"]).await;
+    let first = &app.state.repos.sentences.find_by_chapter_id(chapter.id).await.unwrap()[0];
+    let path = format!("/api/sentences/{}/split", first.id);
+
+    let response = app.post_as(&admin, &path, json!({ "offset": 3 })).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data[0]["text"], "人之初");
+    assert_eq!(data[1]["text"], "性本善");
+    assert_eq!(sentences_of(&app, &chapter).await, numbered(&["人之初", "性本善", "性相近"]));
+
+    let response = app.post_as(&admin, &path, json!({ "offset": 3 })).await;
+    response.assert_error(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn merge_with_the_next_sentence() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let chapter = seed_chapter_with(&app, &["人之初", "性本善", "性相近"]).await;
+    let sentences = app.state.repos.sentences.find_by_chapter_id(chapter.id).await.unwrap();
+
+    let response = app.post_as(&admin, &format!("/api/sentences/{}/merge", sentences[0].id), json!({})).await;
+
+    assert_eq!(response.assert_success(StatusCode::OK)["text"], "人之初性本善");
+    assert_eq!(sentences_of(&app, &chapter).await, numbered(&["人之初性本善", "性相近"]));
+    let trashed = app.state.repos.trash.list(Some(EntityType::Sentence)).await.unwrap();
+    assert_eq!(trashed.len(), 1);
+    assert_eq!(trashed[0].id, sentences[1].id);
+
+    let response = app.post_as(&admin, &format!("/api/sentences/{}/merge", sentences[2].id), json!({})).await;
+    response.assert_error(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn split_merge_and_move_record_revisions() {
+    let app = TestApp::new();
+    let chapter = seed_chapter_with(&app, &["人之初性本善", "性相近"]).await;
+    let sentences = app.state.repos.sentences.find_by_chapter_id(chapter.id).await.unwrap();
+    let (first, last) = (sentences[0].id, sentences[1].id);
+    let editor = app.seed_admin().await;
+    let admin = create_jwt_token(editor.id, &app.state.config.auth.jwt_secret).unwrap();
+
+    let response = app.post_as(&admin, &format!("/api/sentences/{}/split", first), json!({ "offset": 3 })).await;
+    let second: Uuid = response.assert_success(StatusCode::OK)[1]["id"].as_str().unwrap().parse().unwrap();
+    let split = &revisions_of(&app, first).await[1];
+    assert_eq!(split.action, RevisionAction::Update);
+    assert_eq!(split.editor_id, Some(editor.id));
+    assert_eq!(split.before.as_ref().unwrap()["text"], "人之初性本善");
+    assert_eq!(split.after.as_ref().unwrap()["text"], "人之初");
+    let created = &revisions_of(&app, second).await[0];
+    assert_eq!(created.action, RevisionAction::Create);
+    assert_eq!(created.before, None);
+    assert_eq!(created.after.as_ref().unwrap()["text"], "性本善");
+
+    app.post_as(&admin, &format!("/api/sentences/{}/merge", first), json!({})).await.assert_success(StatusCode::OK);
+    let merged = &revisions_of(&app, first).await[2];
+    assert_eq!(merged.after.as_ref().unwrap()["text"], "人之初性本善");
+    let removed = &revisions_of(&app, second).await[1];
+    assert_eq!(removed.action, RevisionAction::Delete);
+    assert_eq!(removed.after, None);
+
+    app.post_as(&admin, &format!("/api/sentences/{}/move", last), json!({ "position": 1 })).await.assert_success(StatusCode::OK);
+    let revisions = revisions_of(&app, last).await;
+    assert_eq!(revisions.len(), 2, "only the moved sentence is recorded, not those shifted");
+    assert_eq!(revisions[1].before.as_ref().unwrap()["number"], 2);
+    assert_eq!(revisions[1].after.as_ref().unwrap()["number"], 1);
+    assert_eq!(revisions_of(&app, first).await.len(), 3);
+}
+
+#[tokio::test]
+async fn chapters_insert_move_and_renumber() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let first = app.seed_chapter(&classic, 1, "甲").await;
+    let second = app.seed_chapter(&classic, 2, "乙").await;
+    let titles = |response: &crate::support::TestResponse| -> Vec<String> {
+        response.body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| format!("{}{}", c["number"], c["title"].as_str().unwrap()))
+            .collect()
+    };
+
+    let response = app
+        .post_as(&admin, &format!("/api/classics/{}/chapters/insert", classic.id), json!({ "position": 1, "title": "序" }))
+        .await;
+    let data = response.assert_success(StatusCode::CREATED);
+    assert_eq!(data["number"], 1);
+    let inserted = chapter_revisions(&app, data["id"].as_str().unwrap().parse().unwrap()).await;
+    assert_eq!(inserted.len(), 1);
+    assert_eq!(inserted[0].action, RevisionAction::Create);
+
+    let response = app.post_as(&admin, &format!("/api/chapters/{}/move", first.id), json!({})).await;
+    assert_eq!(response.assert_success(StatusCode::OK)["number"], 3);
+    let moved = chapter_revisions(&app, first.id).await;
+    assert_eq!(moved.len(), 2);
+    assert_eq!(moved[1].action, RevisionAction::Update);
+    assert_eq!(moved[1].before.as_ref().unwrap()["number"], 2);
+    assert_eq!(moved[1].after.as_ref().unwrap()["number"], 3);
+
+    let response = app
+        .post_as(&admin, &format!("/api/classics/{}/chapters/renumber", classic.id), json!({ "order": [first.id, second.id, data["id"]] }))
+        .await;
+    response.assert_success(StatusCode::OK);
+    assert_eq!(titles(&response), ["1甲", "2乙", "3序"]);
+    let renumbered = chapter_revisions(&app, first.id).await;
+    assert_eq!(renumbered.len(), 3);
+    assert_eq!(renumbered[2].after.as_ref().unwrap()["number"], 1);
+    assert_eq!(chapter_revisions(&app, second.id).await.len(), 1, "a chapter left in place is not recorded");
+
+    let response = app.post_as(&admin, &format!("/api/classics/{}/chapters/insert", Uuid::new_v4()), json!({ "position": 1, "title": "序" })).await;
+    response.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn concurrent_splits_of_one_sentence_see_each_other() {
+    // The in-memory store serialises everything; only Postgres can interleave
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic(&format!("split-{}", Uuid::new_v4().simple()), "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "甲乙丙丁").await;
+    let path = format!("/api/sentences/{}/split", sentence.id);
+
+    let (first, second) = tokio::join!(
+        app.post_as(&admin, &path, json!({ "offset": 1 })),
+        app.post_as(&admin, &path, json!({ "offset": 2 })),
+    );
+
+    // Whichever runs second splits what the first left behind, or is refused
+    // an offset past its end; the text is never duplicated or lost
+    let succeeded = [&first, &second].iter().filter(|r| r.status == StatusCode::OK).count();
+    assert!(succeeded >= 1, "first: {}, second: {}", first.text, second.text);
+    let sentences = sentences_of(&app, &chapter).await;
+    let numbers: Vec<i64> = sentences.iter().map(|(number, _)| *number).collect();
+    assert_eq!(numbers, (1..=succeeded as i64 + 1).collect::<Vec<_>>());
+    let text: String = sentences.iter().map(|(_, text)| text.as_str()).collect();
+    assert_eq!(text, "甲乙丙丁");
+}
@@ -0,0 +1,111 @@
+use serde_json::json;
+use warp::http::StatusCode;
+
+use crate::support::{self, TestApp};
+
+#[tokio::test]
+async fn unknown_route_is_not_found() {
+    let app = TestApp::new();
+
+    let response = app.get("/api/unknown").await;
+
+    assert_eq!(response.assert_error(StatusCode::NOT_FOUND), "路由未找到");
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn non_uuid_id_is_not_found() {
+    let app = TestApp::new();
+
+    let response = app.get("/api/chapters/not-a-uuid").await;
+
+    response.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn unsupported_method_is_not_allowed() {
+    let app = TestApp::new();
+
+    let response = app.send(warp::test::request().method("PATCH").path("/api/sentences")).await;
+
+    assert_eq!(response.assert_error(StatusCode::METHOD_NOT_ALLOWED), "HTTP方法不允许");
+}
+
+#[tokio::test]
+async fn malformed_json_is_a_bad_request() {
+    let app = TestApp::new();
+
+    let response = app
+        .send(
+            warp::test::request()
+                .method("POST")
+                .path("/api/classics")
+                .header("content-type", "application/json")
+                .body("{\"slug\":"),
+        )
+        .await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "请求体格式错误");
+}
+
+#[tokio::test]
+async fn rate_limited_requests_get_429_with_retry_after() {
+    let mut config = support::config();
+    config.rate_limit.enabled = true;
+    config.rate_limit.auth_burst = 2;
+    let app = TestApp::with_config(config);
+    let login = json!({"email": "nobody@example.com", "password": support::PASSWORD});
+
+    for _ in 0..2 {
+        let response = app.post("/api/auth/login", login.clone()).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    }
+    let limited = app.post("/api/auth/login", login).await;
+
+    assert_eq!(limited.assert_error(StatusCode::TOO_MANY_REQUESTS), "请求过于频繁，请稍后再试");
+    assert!(limited.header("retry-after").is_some());
+    assert_eq!(limited.header("ratelimit-remaining"), Some("0"));
+}
+
+#[tokio::test]
+async fn spoofed_forwarded_for_entries_share_the_proxy_reported_bucket() {
+    let mut config = support::config();
+    config.rate_limit.enabled = true;
+    config.rate_limit.trust_proxy = true;
+    config.rate_limit.auth_burst = 2;
+    let app = TestApp::with_config(config);
+    let login = |spoofed: usize| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/auth/login")
+            .header("x-forwarded-for", format!("10.0.0.{}, 203.0.113.7", spoofed))
+            .json(&json!({"email": "nobody@example.com", "password": support::PASSWORD}))
+    };
+
+    for spoofed in 0..2 {
+        assert_eq!(app.send(login(spoofed)).await.status, StatusCode::UNAUTHORIZED);
+    }
+    let limited = app.send(login(2)).await;
+
+    assert_eq!(limited.status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn cors_preflight_allows_configured_origins_only() {
+    let app = TestApp::new();
+    let preflight = |origin: &str| {
+        warp::test::request()
+            .method("OPTIONS")
+            .path("/api/classics")
+            .header("origin", origin)
+            .header("access-control-request-method", "POST")
+    };
+
+    let allowed = app.send(preflight("http://localhost:3000")).await;
+    assert_eq!(allowed.status, StatusCode::NO_CONTENT);
+    assert_eq!(allowed.header("access-control-allow-origin"), Some("http://localhost:3000"));
+
+    let denied = app.send(preflight("https://evil.example.com")).await;
+    assert_eq!(denied.status, StatusCode::FORBIDDEN);
+    assert!(denied.header("access-control-allow-origin").is_none());
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use warp::http::StatusCode;
+
+use xiaoxiao_dushulang_backend::metrics;
+
+use crate::support::TestApp;
+
+#[tokio::test]
+async fn health_reports_the_service() {
+    let app = TestApp::new();
+
+    let response = app.get("/health").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    // Read from NODE_ENV, which differs between machines
+    insta::assert_json_snapshot!(response.body, {
+        ".timestamp" => "[timestamp]",
+        ".environment" => "[environment]",
+    });
+}
+
+#[tokio::test]
+async fn metrics_are_exposed_in_prometheus_text_format() {
+    let app = TestApp::new();
+    app.get("/health").await;
+
+    let response = app.get("/metrics").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.header("content-type").unwrap().starts_with("text/plain"));
+    assert!(response.text.contains("http_requests_total"), "{}", response.text);
+}
+
+#[tokio::test]
+async fn reviews_and_completed_sessions_are_counted() {
+    let app = TestApp::new();
+    let reviews = metrics::REVIEWS_SUBMITTED_TOTAL.get();
+    let reading = metrics::SESSIONS_COMPLETED_TOTAL.with_label_values(&["reading"]).get();
+    let other = metrics::SESSIONS_COMPLETED_TOTAL.with_label_values(&["other"]).get();
+
+    metrics::observe_review_submitted();
+    metrics::observe_session_completed("reading");
+    metrics::observe_session_completed("../../etc/passwd");
+
+    // Other tests share the registry, so compare with what was there before
+    assert!(metrics::REVIEWS_SUBMITTED_TOTAL.get() > reviews);
+    assert!(metrics::SESSIONS_COMPLETED_TOTAL.with_label_values(&["reading"]).get() > reading);
+    assert!(metrics::SESSIONS_COMPLETED_TOTAL.with_label_values(&["other"]).get() > other);
+    let response = app.get("/metrics").await;
+    assert!(response.text.contains("xiaoxiao_reviews_submitted_total"), "{}", response.text);
+    assert!(response.text.contains("xiaoxiao_sessions_completed_total{session_type=\"reading\"}"), "{}", response.text);
+    assert!(!response.text.contains("passwd"));
+}
+
+#[tokio::test]
+async fn every_response_carries_a_request_id_and_security_headers() {
+    let app = TestApp::new();
+
+    let response = app.get("/health").await;
+
+    assert!(response.header("x-request-id").is_some());
+    assert_eq!(response.header("x-content-type-options"), Some("nosniff"));
+    assert_eq!(response.header("x-frame-options"), Some("DENY"));
+}
+
+#[tokio::test]
+async fn closing_the_database_gives_up_at_the_deadline() {
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let db = app.state.db.clone().expect("postgres app has a database");
+    // Stands in for a request the drain cut off, still holding its connection
+    let held = db.writer().acquire().await.expect("acquire a connection");
+
+    let started = std::time::Instant::now();
+    let closed = db.close_within(Duration::from_millis(100)).await;
+
+    assert!(!closed);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    drop(held);
+}
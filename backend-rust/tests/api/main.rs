@@ -0,0 +1,26 @@
+//! End-to-end tests for every route, served by `routes::build` over in-memory
+//! repositories. JSON bodies are snapshotted under `snapshots/`; after an
+//! intentional response change, review and accept them with `cargo insta review`.
+//!
+//! The few tests that need Postgres itself run against `TEST_DATABASE_URL` and
+//! pass without checking anything when it is unset.
+
+#[macro_use]
+mod support;
+
+mod auth;
+mod chapters;
+mod classics;
+mod compression;
+mod config;
+mod editing;
+mod errors;
+mod health;
+mod openapi;
+mod pagination;
+mod publishing;
+mod revisions;
+mod segmentation;
+mod sentences;
+mod trash;
+mod tree;
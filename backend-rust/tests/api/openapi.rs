@@ -0,0 +1,70 @@
+use serde_json::{json, Value};
+use warp::http::StatusCode;
+
+use crate::support::TestApp;
+
+#[tokio::test]
+async fn openapi_document_describes_the_api() {
+    let app = TestApp::new();
+
+    let response = app.get("/api/openapi.json").await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["openapi"], "3.1.0");
+    // Review with `cargo insta review` whenever a request or response type changes
+    insta::assert_json_snapshot!(response.body);
+}
+
+#[tokio::test]
+async fn every_documented_operation_is_routed() {
+    let app = TestApp::new();
+    let spec = app.get("/api/openapi.json").await.body;
+
+    for (path, operations) in spec["paths"].as_object().unwrap() {
+        let path = path
+            .replace("{slug}", "no-such-classic")
+            .replace("{id}", "00000000-0000-0000-0000-000000000000")
+            .replace("{classic_id}", "00000000-0000-0000-0000-000000000000")
+            .replace("{chapter_id}", "00000000-0000-0000-0000-000000000000")
+            .replace("{kind}", "classics")
+            .replace("{version}", "1");
+
+        for method in operations.as_object().unwrap().keys() {
+            let request = warp::test::request().method(&method.to_uppercase()).path(&path);
+            let request = match method.as_str() {
+                "post" | "put" | "patch" => request.json(&json!({})),
+                _ => request,
+            };
+            let response = app.send(request).await;
+
+            assert_ne!(response.status, StatusCode::METHOD_NOT_ALLOWED, "{} {}", method, path);
+            assert_ne!(response.body["error"], Value::from("路由未找到"), "{} {}", method, path);
+        }
+    }
+}
+
+#[tokio::test]
+async fn docs_redirect_to_the_swagger_ui_page() {
+    let app = TestApp::new();
+
+    let response = app.get("/api/docs").await;
+
+    assert_eq!(response.status, StatusCode::FOUND);
+    assert_eq!(response.header("location"), Some("/api/docs/"));
+}
+
+#[tokio::test]
+async fn docs_serve_the_bundled_swagger_ui() {
+    let app = TestApp::new();
+
+    let page = app.get("/api/docs/").await;
+    assert_eq!(page.status, StatusCode::OK);
+    assert!(page.header("content-type").unwrap().starts_with("text/html"));
+    assert!(page.text.contains("swagger-ui"), "{}", page.text);
+
+    let initializer = app.get("/api/docs/swagger-initializer.js").await;
+    assert_eq!(initializer.status, StatusCode::OK);
+    assert!(initializer.text.contains("/api/openapi.json"), "{}", initializer.text);
+
+    assert_eq!(app.get("/api/docs/no-such-asset.js").await.status, StatusCode::NOT_FOUND);
+}
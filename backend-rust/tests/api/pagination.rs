@@ -0,0 +1,225 @@
+use serde_json::Value;
+use warp::http::StatusCode;
+
+use xiaoxiao_dushulang_backend::models::{Classic, CreateClassicRequest, Edit};
+
+use crate::support::TestApp;
+
+async fn seed_classic_from(app: &TestApp, slug: &str, title: &str, dynasty: Option<&str>, author: &str) -> Classic {
+    let classic = app
+        .state
+        .repos
+        .classics
+        .create(
+            CreateClassicRequest {
+                slug: slug.to_string(),
+                title: title.to_string(),
+                author: Some(author.to_string()),
+                dynasty: dynasty.map(str::to_string),
+                description: None,
+            },
+            &Edit::default(),
+        )
+        .await
+        .expect("seed classic");
+    app.publish(classic).await
+}
+
+fn field<'a>(data: &'a Value, name: &str) -> Vec<&'a Value> {
+    data.as_array().unwrap().iter().map(|item| &item[name]).collect()
+}
+
+#[tokio::test]
+async fn offset_pages_report_totals() {
+    let app = TestApp::new();
+    for (slug, title) in [("a", "甲"), ("b", "乙"), ("c", "丙"), ("d", "丁"), ("e", "戊")] {
+        app.seed_classic(slug, title).await;
+    }
+
+    let response = app.get("/api/classics?page=2&limit=2").await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(field(data, "slug"), ["c", "d"]);
+    let pagination = &response.body["pagination"];
+    assert_eq!(pagination["page"], 2);
+    assert_eq!(pagination["limit"], 2);
+    assert_eq!(pagination["total"], 5);
+    assert_eq!(pagination["total_pages"], 3);
+    assert_eq!(pagination["has_more"], true);
+}
+
+#[tokio::test]
+async fn lists_default_to_twenty_items() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    for number in 1..=25 {
+        app.seed_chapter(&classic, number, &format!("第{}章", number)).await;
+    }
+
+    let response = app.get("/api/chapters").await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data.as_array().unwrap().len(), 20);
+    assert_eq!(response.body["pagination"]["total"], 25);
+}
+
+#[tokio::test]
+async fn cursors_walk_the_whole_list_once() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    for number in 1..=3 {
+        let chapter = app.seed_chapter(&classic, number, &format!("第{}章", number)).await;
+        for sentence in 1..=3 {
+            app.seed_sentence(&chapter, sentence, "人之初").await;
+        }
+    }
+
+    let mut seen = Vec::new();
+    let mut path = "/api/sentences?limit=4".to_string();
+    loop {
+        let response = app.get(&path).await;
+        let data = response.assert_success(StatusCode::OK);
+        seen.extend(field(data, "id").into_iter().cloned());
+
+        match response.body["pagination"]["next_cursor"].as_str() {
+            Some(cursor) => path = format!("/api/sentences?limit=4&cursor={}", cursor),
+            None => {
+                assert_eq!(response.body["pagination"]["has_more"], false);
+                break;
+            }
+        }
+    }
+
+    assert_eq!(seen.len(), 9);
+    seen.dedup();
+    assert_eq!(seen.len(), 9);
+}
+
+#[tokio::test]
+async fn cursor_pages_skip_the_count() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    for number in 1..=3 {
+        app.seed_chapter(&classic, number, "章").await;
+    }
+    let first = app.get("/api/chapters?limit=2").await;
+    let cursor = first.body["pagination"]["next_cursor"].as_str().unwrap();
+
+    let response = app.get(&format!("/api/chapters?limit=2&cursor={}", cursor)).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(field(data, "number"), [3]);
+    let pagination = response.body["pagination"].as_object().unwrap();
+    assert!(!pagination.contains_key("total"));
+    assert!(!pagination.contains_key("page"));
+}
+
+#[tokio::test]
+async fn sort_accepts_whitelisted_fields_in_both_directions() {
+    let app = TestApp::new();
+    seed_classic_from(&app, "lunyu", "论语", Some("春秋"), "孔子").await;
+    seed_classic_from(&app, "shijing", "诗经", None, "佚名").await;
+    seed_classic_from(&app, "daodejing", "道德经", Some("春秋"), "老子").await;
+
+    let ascending = app.get("/api/classics?sort=title").await;
+    let descending = app.get("/api/classics?sort=-title").await;
+    let by_dynasty = app.get("/api/classics?sort=-dynasty").await;
+
+    assert_eq!(field(ascending.assert_success(StatusCode::OK), "slug"), ["lunyu", "shijing", "daodejing"]);
+    assert_eq!(field(descending.assert_success(StatusCode::OK), "slug"), ["daodejing", "shijing", "lunyu"]);
+    // NULLs last in either direction
+    assert_eq!(field(by_dynasty.assert_success(StatusCode::OK), "slug")[2], "shijing");
+    // Cursors only follow the default order
+    assert!(ascending.body["pagination"].get("next_cursor").is_none());
+}
+
+#[tokio::test]
+async fn filters_narrow_the_list_and_the_total() {
+    let app = TestApp::new();
+    seed_classic_from(&app, "lunyu", "论语", Some("春秋"), "孔子").await;
+    seed_classic_from(&app, "mengzi", "孟子", Some("战国"), "孟子").await;
+    seed_classic_from(&app, "daodejing", "道德经", Some("春秋"), "老子").await;
+
+    let by_dynasty = app.get("/api/classics?dynasty=%E6%98%A5%E7%A7%8B").await;
+    let by_author = app.get("/api/classics?author=%E5%AD%9F%E5%AD%90").await;
+
+    assert_eq!(field(by_dynasty.assert_success(StatusCode::OK), "slug"), ["lunyu", "daodejing"]);
+    assert_eq!(by_dynasty.body["pagination"]["total"], 2);
+    assert_eq!(field(by_author.assert_success(StatusCode::OK), "slug"), ["mengzi"]);
+}
+
+#[tokio::test]
+async fn sentences_filter_by_classic() {
+    let app = TestApp::new();
+    let sanzijing = app.seed_classic("sanzijing", "三字经").await;
+    let qianziwen = app.seed_classic("qianziwen", "千字文").await;
+    let first = app.seed_chapter(&sanzijing, 1, "第一章").await;
+    let other = app.seed_chapter(&qianziwen, 1, "第一章").await;
+    app.seed_sentence(&first, 1, "人之初").await;
+    app.seed_sentence(&other, 1, "天地玄黄").await;
+
+    let response = app.get(&format!("/api/sentences?classic_id={}", qianziwen.id)).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(field(data, "text"), ["天地玄黄"]);
+}
+
+#[tokio::test]
+async fn nested_lists_are_paginated() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    for number in 1..=3 {
+        app.seed_chapter(&classic, number, "章").await;
+    }
+
+    let response = app.get(&format!("/api/classics/{}/chapters?limit=2&sort=-number", classic.id)).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(field(data, "number"), [3, 2]);
+    assert_eq!(response.body["pagination"]["total"], 3);
+}
+
+#[tokio::test]
+async fn page_metadata_is_part_of_the_etag() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    app.seed_chapter(&classic, 1, "第一章").await;
+    let before = app.get("/api/chapters?limit=1").await;
+
+    // Lands on page 2: page 1's rows are unchanged, its total is not
+    app.seed_chapter(&classic, 2, "第二章").await;
+    let after = app.get("/api/chapters?limit=1").await;
+
+    assert_eq!(before.body["data"], after.body["data"]);
+    assert_ne!(before.header("etag"), after.header("etag"));
+}
+
+#[tokio::test]
+async fn invalid_parameters_are_rejected() {
+    let app = TestApp::new();
+
+    for (path, message) in [
+        ("/api/classics?limit=0", "limit 必须在 1 到 100 之间"),
+        ("/api/classics?limit=101", "limit 必须在 1 到 100 之间"),
+        ("/api/classics?page=0", "page 超出范围"),
+        ("/api/classics?page=1&cursor=abc", "page 和 cursor 不能同时使用"),
+        ("/api/classics?cursor=abc", "无效的 cursor"),
+        ("/api/classics?sort=title&cursor=abc", "cursor 只能用于默认排序"),
+        ("/api/chapters?limit=0", "limit 必须在 1 到 100 之间"),
+        ("/api/sentences?cursor=%E4%BA%BA", "无效的 cursor"),
+    ] {
+        let response = app.get(path).await;
+        assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), message, "{}", path);
+    }
+
+    for path in [
+        "/api/classics?sort=slug",
+        "/api/classics?classic_id=1",
+        "/api/chapters?dynasty=%E5%AE%8B",
+        "/api/sentences?page=first",
+    ] {
+        let response = app.get(path).await;
+        let error = response.assert_error(StatusCode::BAD_REQUEST);
+        assert!(error.starts_with("查询参数无效"), "{}: {}", path, error);
+    }
+}
@@ -0,0 +1,269 @@
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use warp::http::StatusCode;
+
+use xiaoxiao_dushulang_backend::{
+    models::{CreateUserRequest, UserRole},
+    services::cache,
+    utils::jwt::create_jwt_token,
+};
+
+use crate::support::{TestApp, TestResponse};
+
+async fn change_status(app: &TestApp, token: &str, id: impl std::fmt::Display, body: Value) -> TestResponse {
+    app.send(
+        warp::test::request()
+            .method("POST")
+            .path(&format!("/api/classics/{}/status", id))
+            .header("authorization", format!("Bearer {}", token))
+            .json(&body),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn drafts_are_hidden_from_the_public_but_not_from_admins() {
+    let app = TestApp::new();
+    app.seed_classic("sanzijing", "三字经").await;
+    let draft = app.seed_draft_classic("dizigui", "弟子规").await;
+    let chapter = app.seed_chapter(&draft, 1, "总叙").await;
+    let sentence = app.seed_sentence(&chapter, 1, "弟子规，圣人训").await;
+    let token = app.admin_token().await;
+
+    let public = app.get("/api/classics").await;
+    assert_eq!(public.assert_success(StatusCode::OK).as_array().unwrap().len(), 1);
+    app.get("/api/classics/dizigui").await.assert_error(StatusCode::NOT_FOUND);
+    app.get(&format!("/api/chapters/{}", chapter.id)).await.assert_error(StatusCode::NOT_FOUND);
+    app.get(&format!("/api/sentences/{}", sentence.id)).await.assert_error(StatusCode::NOT_FOUND);
+    let chapters = app.get("/api/chapters").await;
+    assert_eq!(chapters.assert_success(StatusCode::OK), &json!([]));
+
+    let admin = app.get_as(&token, "/api/classics").await;
+    assert_eq!(admin.assert_success(StatusCode::OK).as_array().unwrap().len(), 2);
+    let fetched = app.get_as(&token, "/api/classics/dizigui").await;
+    assert_eq!(fetched.assert_success(StatusCode::OK)["status"], "draft");
+    app.get_as(&token, &format!("/api/sentences/{}", sentence.id))
+        .await
+        .assert_success(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn editors_submit_and_admins_approve() {
+    let app = TestApp::new();
+    let draft = app.seed_draft_classic("dizigui", "弟子规").await;
+    let editor = app.editor_token().await;
+    let admin = app.admin_token().await;
+
+    let response = change_status(&app, &editor, draft.id, json!({ "status": "in_review" })).await;
+    assert_eq!(response.assert_success(StatusCode::OK)["status"], "in_review");
+
+    let queue = app.get_as(&admin, "/api/classics?status=in_review").await;
+    assert_eq!(queue.assert_success(StatusCode::OK)[0]["slug"], "dizigui");
+
+    let response = change_status(&app, &editor, draft.id, json!({ "status": "published" })).await;
+    response.assert_error(StatusCode::FORBIDDEN);
+
+    let response = change_status(&app, &admin, draft.id, json!({ "status": "published", "comment": "校对完毕" })).await;
+    assert_eq!(response.assert_success(StatusCode::OK)["status"], "published");
+    app.get("/api/classics/dizigui").await.assert_success(StatusCode::OK);
+
+    let response = app.get_as(&admin, &format!("/api/classics/{}/revisions", draft.id)).await;
+    let revisions = response.assert_success(StatusCode::OK).as_array().unwrap();
+    assert_eq!(revisions.len(), 3);
+    assert_eq!(revisions[2]["after"]["status"], "published");
+    assert_eq!(revisions[2]["comment"], "校对完毕");
+}
+
+#[tokio::test]
+async fn transitions_outside_the_workflow_are_rejected() {
+    let app = TestApp::new();
+    let draft = app.seed_draft_classic("dizigui", "弟子规").await;
+    let admin = app.admin_token().await;
+
+    let response = change_status(&app, &admin, draft.id, json!({ "status": "published" })).await;
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "不能从 draft 变为 published");
+
+    let response = change_status(
+        &app,
+        &admin,
+        draft.id,
+        json!({ "status": "in_review", "publish_at": Utc::now() }),
+    )
+    .await;
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "只有发布时可以设置上线和下线时间");
+
+    let response = change_status(&app, &admin, uuid::Uuid::new_v4(), json!({ "status": "in_review" })).await;
+    response.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn status_changes_require_sign_in() {
+    let app = TestApp::new();
+    let draft = app.seed_draft_classic("dizigui", "弟子规").await;
+
+    let response = app
+        .post(&format!("/api/classics/{}/status", draft.id), json!({ "status": "in_review" }))
+        .await;
+
+    response.assert_error(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn content_changes_require_sign_in() {
+    let app = TestApp::new();
+    let classic = app.seed_draft_classic("dizigui", "弟子规").await;
+    let chapter = app.seed_chapter(&classic, 1, "总叙").await;
+    let sentence = app.seed_sentence(&chapter, 1, "弟子规，圣人训").await;
+
+    let classic_path = format!("/api/classics/{}", classic.id);
+    app.post("/api/classics", json!({ "slug": "sanzijing", "title": "三字经" }))
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED);
+    app.put(&classic_path, json!({ "title": "弟子规" })).await.assert_error(StatusCode::UNAUTHORIZED);
+    app.delete(&classic_path).await.assert_error(StatusCode::UNAUTHORIZED);
+    app.patch(&format!("/api/chapters/{}", chapter.id), json!({ "title": "入则孝" }))
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED);
+    app.post(&format!("/api/classics/{}/chapters/renumber", classic.id), json!({}))
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED);
+    app.post(&format!("/api/sentences/{}/split", sentence.id), json!({ "offset": 3 }))
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED);
+    app.post(&format!("/api/sentences/{}/merge", sentence.id), json!({}))
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED);
+
+    let child = app
+        .state
+        .repos
+        .users
+        .create(CreateUserRequest {
+            username: "child".to_string(),
+            email: "child@example.com".to_string(),
+            password: crate::support::PASSWORD.to_string(),
+            phone: None,
+            role: Some(UserRole::Child),
+        })
+        .await
+        .unwrap();
+    let child = create_jwt_token(child.id, &app.state.config.auth.jwt_secret).unwrap();
+    app.put_as(&child, &classic_path, json!({ "title": "弟子规" }))
+        .await
+        .assert_error(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn editors_change_drafts_and_only_admins_published_classics() {
+    let app = TestApp::new();
+    let editor = app.editor_token().await;
+    let admin = app.admin_token().await;
+    let published = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&published, 1, "第一章").await;
+    let draft = app.seed_draft_classic("dizigui", "弟子规").await;
+
+    let path = format!("/api/chapters/{}", chapter.id);
+    app.put_as(&editor, &path, json!({ "title": "人之初" })).await.assert_error(StatusCode::FORBIDDEN);
+    app.delete_as(&editor, &path).await.assert_error(StatusCode::FORBIDDEN);
+    app.post_as(&editor, "/api/chapters", json!({ "classic_id": published.id, "number": 2, "title": "第二章" }))
+        .await
+        .assert_error(StatusCode::FORBIDDEN);
+    app.put_as(&admin, &path, json!({ "title": "人之初" })).await.assert_success(StatusCode::OK);
+
+    let path = format!("/api/classics/{}", draft.id);
+    let read = app.get_as(&admin, "/api/classics/dizigui").await.header("etag").unwrap().to_string();
+    let response = app.put_as(&editor, &path, json!({ "author": "李毓秀" })).await;
+    assert_eq!(response.assert_success(StatusCode::OK), &Value::Null, "drafts are not sent to editors");
+    assert!(response.header("etag").is_some());
+
+    let response = app
+        .send(
+            warp::test::request()
+                .method("PUT")
+                .path(&path)
+                .header("authorization", format!("Bearer {}", editor))
+                .header("if-match", &read)
+                .json(&json!({ "author": "贾存仁" })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::PRECONDITION_FAILED, "body: {}", response.text);
+    assert!(response.body.get("data").is_none(), "body: {}", response.text);
+    assert_eq!(response.header("etag"), Some(app.get_as(&admin, "/api/classics/dizigui").await.header("etag").unwrap()));
+}
+
+#[tokio::test]
+async fn scheduled_classics_are_public_only_inside_their_window() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let upcoming = app.seed_draft_classic("dizigui", "弟子规").await;
+    let expired = app.seed_draft_classic("qianziwen", "千字文").await;
+    for id in [upcoming.id, expired.id] {
+        change_status(&app, &admin, id, json!({ "status": "in_review" })).await;
+    }
+
+    let now = Utc::now();
+    change_status(
+        &app,
+        &admin,
+        upcoming.id,
+        json!({ "status": "published", "publish_at": now + Duration::days(1) }),
+    )
+    .await
+    .assert_success(StatusCode::OK);
+    change_status(
+        &app,
+        &admin,
+        expired.id,
+        json!({ "status": "published", "publish_at": now - Duration::days(2), "unpublish_at": now - Duration::days(1) }),
+    )
+    .await
+    .assert_success(StatusCode::OK);
+
+    app.get("/api/classics/dizigui").await.assert_error(StatusCode::NOT_FOUND);
+    app.get("/api/classics/qianziwen").await.assert_error(StatusCode::NOT_FOUND);
+    assert_eq!(app.get("/api/classics").await.assert_success(StatusCode::OK), &json!([]));
+
+    let response = change_status(
+        &app,
+        &admin,
+        upcoming.id,
+        json!({ "status": "published", "publish_at": now, "unpublish_at": now }),
+    )
+    .await;
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "下线时间必须晚于上线时间");
+}
+
+#[tokio::test]
+async fn public_list_pages_expire_by_the_next_scheduled_change() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let upcoming = app.seed_draft_classic("dizigui", "弟子规").await;
+    change_status(&app, &admin, upcoming.id, json!({ "status": "in_review" })).await;
+    let classics = &app.state.repos.classics;
+    let now = Utc::now();
+    let next = classics.next_scheduled_change().await.unwrap();
+    assert_eq!(cache::ttl_until(next, now), cache::DEFAULT_TTL_SECS);
+
+    let unpublish_at = now + Duration::minutes(30);
+    change_status(
+        &app,
+        &admin,
+        classic.id,
+        json!({ "status": "published", "unpublish_at": unpublish_at }),
+    )
+    .await
+    .assert_success(StatusCode::OK);
+    change_status(
+        &app,
+        &admin,
+        upcoming.id,
+        json!({ "status": "published", "publish_at": now + Duration::minutes(10) }),
+    )
+    .await
+    .assert_success(StatusCode::OK);
+
+    let next = classics.next_scheduled_change().await.unwrap();
+    assert_eq!(next, Some(now + Duration::minutes(10)));
+    assert_eq!(cache::ttl_until(next, now), 600);
+}
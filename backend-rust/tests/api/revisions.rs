@@ -0,0 +1,211 @@
+use serde_json::json;
+use warp::http::StatusCode;
+
+use xiaoxiao_dushulang_backend::utils::jwt::create_jwt_token;
+
+use crate::support::TestApp;
+
+#[tokio::test]
+async fn update_records_before_after_editor_and_comment() {
+    let app = TestApp::new();
+    let classic = app.seed_draft_classic("daodejing", "道德经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let user = app.seed_user("editor", "editor@example.com").await;
+    let token = create_jwt_token(user.id, &app.state.config.auth.jwt_secret).unwrap();
+
+    let response = app
+        .send(
+            warp::test::request()
+                .method("PUT")
+                .path(&format!("/api/chapters/{}", chapter.id))
+                .header("authorization", format!("Bearer {}", token))
+                .json(&json!({ "content": "道可道，非常道。", "comment": "补全正文" })),
+        )
+        .await;
+    response.assert_success(StatusCode::OK);
+
+    let admin = app.admin_token().await;
+    let response = app.get_as(&admin, &format!("/api/chapters/{}/revisions", chapter.id)).await;
+    let revisions = response.assert_success(StatusCode::OK).as_array().unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0]["action"], "create");
+    assert_eq!(revisions[0]["before"], json!(null));
+    let revision = &revisions[1];
+    assert_eq!(revision["version"], 2);
+    assert_eq!(revision["action"], "update");
+    assert_eq!(revision["entity_type"], "chapter");
+    assert_eq!(revision["before"]["content"], json!(null));
+    assert_eq!(revision["after"]["content"], "道可道，非常道。");
+    assert_eq!(revision["editor_id"], user.id.to_string());
+    assert_eq!(revision["comment"], "补全正文");
+}
+
+#[tokio::test]
+async fn invalid_token_is_rejected_rather_than_recorded_as_anonymous() {
+    let app = TestApp::new();
+    let classic = app.seed_draft_classic("daodejing", "道德经").await;
+
+    let response = app
+        .send(
+            warp::test::request()
+                .method("PUT")
+                .path(&format!("/api/classics/{}", classic.id))
+                .header("authorization", "Bearer not-a-token")
+                .json(&json!({ "title": "老子" })),
+        )
+        .await;
+
+    response.assert_error(StatusCode::UNAUTHORIZED);
+    let admin = app.admin_token().await;
+    let response = app.get_as(&admin, &format!("/api/classics/{}/revisions", classic.id)).await;
+    let revisions = response.assert_success(StatusCode::OK).as_array().unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0]["action"], "create");
+}
+
+#[tokio::test]
+async fn history_is_for_admins_only() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("daodejing", "道德经").await;
+    let draft = app.seed_draft_classic("laozi", "老子").await;
+    app.put_as(&admin, &format!("/api/classics/{}", draft.id), json!({ "description": "未发布的简介" })).await;
+    let editor = app.seed_user("editor", "editor@example.com").await;
+    let editor = create_jwt_token(editor.id, &app.state.config.auth.jwt_secret).unwrap();
+
+    for id in [classic.id, draft.id] {
+        for path in [
+            format!("/api/classics/{}/revisions", id),
+            format!("/api/classics/{}/revisions/diff?from=0&to=1", id),
+        ] {
+            app.get(&path).await.assert_error(StatusCode::UNAUTHORIZED);
+            app.get_as(&editor, &path).await.assert_error(StatusCode::FORBIDDEN);
+        }
+    }
+}
+
+#[tokio::test]
+async fn diff_lists_changed_fields_between_versions() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("daodejing", "道德经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "道可道").await;
+    let path = format!("/api/sentences/{}", sentence.id);
+    app.put_as(&admin, &path, json!({ "text": "道可道，非常道" })).await;
+    app.put_as(&admin, &path, json!({ "pinyin": "dào kě dào, fēi cháng dào" })).await;
+
+    let response = app.get_as(&admin, &format!("{}/revisions/diff?from=1&to=3", path)).await;
+    let diff = response.assert_success(StatusCode::OK);
+
+    assert_eq!(
+        diff["changes"],
+        json!([
+            { "field": "pinyin", "from": null, "to": "dào kě dào, fēi cháng dào" },
+            { "field": "text", "from": "道可道", "to": "道可道，非常道" },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn diff_rejects_unknown_versions() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_draft_classic("daodejing", "道德经").await;
+    app.put_as(&admin, &format!("/api/classics/{}", classic.id), json!({ "author": "老子" })).await;
+
+    let response = app
+        .get_as(&admin, &format!("/api/classics/{}/revisions/diff?from=0&to=5", classic.id))
+        .await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "版本不存在: 5");
+}
+
+#[tokio::test]
+async fn restore_brings_back_content_and_records_a_revision() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("daodejing", "道德经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let path = format!("/api/chapters/{}", chapter.id);
+    app.put_as(&admin, &path, json!({ "content": "道可道，非常道。" })).await;
+    app.put_as(&admin, &path, json!({ "title": "体道", "content": "名可名，非常名。" })).await;
+
+    let response = app
+        .post_as(&admin, &format!("{}/revisions/2/restore", path), json!({ "comment": "回退误改" }))
+        .await;
+    let restored = response.assert_success(StatusCode::OK);
+    assert_eq!(restored["title"], "第一章");
+    assert_eq!(restored["content"], "道可道，非常道。");
+
+    let response = app.get_as(&admin, &format!("{}/revisions", path)).await;
+    let revisions = response.assert_success(StatusCode::OK).as_array().unwrap();
+    assert_eq!(revisions.len(), 4);
+    assert_eq!(revisions[3]["action"], "restore");
+    assert_eq!(revisions[3]["before"]["title"], "体道");
+    assert_eq!(revisions[3]["comment"], "回退误改");
+}
+
+#[tokio::test]
+async fn restore_is_for_admins_only() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("daodejing", "道德经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let path = format!("/api/chapters/{}", chapter.id);
+    app.put_as(&admin, &path, json!({ "content": "道可道，非常道。" })).await;
+    let restore = format!("{}/revisions/1/restore", path);
+
+    app.post(&restore, json!({})).await.assert_error(StatusCode::UNAUTHORIZED);
+    let editor = app.seed_user("editor", "editor@example.com").await;
+    let token = create_jwt_token(editor.id, &app.state.config.auth.jwt_secret).unwrap();
+    app.post_as(&token, &restore, json!({})).await.assert_error(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn deleted_entities_keep_their_history_but_cannot_be_restored() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_draft_classic("daodejing", "道德经").await;
+    let path = format!("/api/classics/{}", classic.id);
+    app.put_as(&admin, &path, json!({ "author": "老子" })).await;
+    app.delete_as(&admin, &path).await.assert_success(StatusCode::OK);
+
+    let response = app.get_as(&admin, &format!("{}/revisions", path)).await;
+    let revisions = response.assert_success(StatusCode::OK).as_array().unwrap();
+    assert_eq!(revisions.len(), 3);
+    assert_eq!(revisions[2]["action"], "delete");
+    assert_eq!(revisions[2]["after"], json!(null));
+
+    let response = app.post_as(&admin, &format!("{}/revisions/2/restore", path), json!({})).await;
+    response.assert_error(StatusCode::NOT_FOUND);
+    let response = app.post_as(&admin, &format!("{}/revisions/3/restore", path), json!({})).await;
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "版本 3 中内容已删除，无法恢复");
+}
+
+#[tokio::test]
+async fn create_records_the_new_row_and_its_editor() {
+    let app = TestApp::new();
+    let admin = app.seed_admin().await;
+    let token = create_jwt_token(admin.id, &app.state.config.auth.jwt_secret).unwrap();
+
+    let response = app.post_as(&token, "/api/classics", json!({ "slug": "daodejing", "title": "道德经" })).await;
+    let classic = response.assert_success(StatusCode::CREATED)["id"].as_str().unwrap().to_string();
+    let response = app.post_as(&token, "/api/chapters", json!({ "classic_id": classic, "number": 1, "title": "第一章" })).await;
+    let chapter = response.assert_success(StatusCode::CREATED)["id"].as_str().unwrap().to_string();
+    let response = app.post_as(&token, "/api/sentences", json!({ "chapter_id": chapter, "number": 1, "text": "道可道" })).await;
+    let sentence = response.assert_success(StatusCode::CREATED)["id"].as_str().unwrap().to_string();
+
+    for path in [
+        format!("/api/classics/{}/revisions", classic),
+        format!("/api/chapters/{}/revisions", chapter),
+        format!("/api/sentences/{}/revisions", sentence),
+    ] {
+        let response = app.get_as(&token, &path).await;
+        let revisions = response.assert_success(StatusCode::OK).as_array().unwrap();
+        assert_eq!(revisions.len(), 1, "{}", path);
+        assert_eq!(revisions[0]["action"], "create");
+        assert_eq!(revisions[0]["before"], json!(null));
+        assert_eq!(revisions[0]["editor_id"], admin.id.to_string());
+    }
+}
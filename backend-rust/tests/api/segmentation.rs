@@ -0,0 +1,169 @@
+use serde_json::{json, Value};
+use warp::http::StatusCode;
+
+use xiaoxiao_dushulang_backend::models::{Chapter, CreateChapterRequest, Edit, EntityType, RevisionAction, UpdateSentenceRequest};
+use xiaoxiao_dushulang_backend::utils::conditional::IfMatch;
+
+use crate::support::TestApp;
+
+async fn seed_chapter_with_content(app: &TestApp, content: &str) -> Chapter {
+    let classic = app.seed_classic("lunyu", "论语").await;
+    app.state
+        .repos
+        .chapters
+        .create(
+            CreateChapterRequest {
+                classic_id: classic.id,
+                number: 1,
+                title: "学而".to_string(),
+                content: Some(content.to_string()),
+            },
+            &Edit::default(),
+        )
+        .await
+        .expect("seed chapter")
+}
+
+fn texts(data: &Value) -> Vec<&str> {
+    data.as_array().unwrap().iter().map(|s| s["text"].as_str().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn preview_splits_on_terminators_and_keeps_quotes_whole() {
+    let app = TestApp::new();
+    let chapter = seed_chapter_with_content(
+        &app,
+        "子曰：“学而时习之，不亦说乎？有朋自远方来，不亦乐乎？”\n人之初，性本善。性相近，习相远；苟不教，性乃迁！？  \n\n教之道",
+    )
+    .await;
+
+    let response = app.get(&format!("/api/chapters/{}/segmentation", chapter.id)).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(
+        texts(&data["sentences"]),
+        [
+            "子曰：“学而时习之，不亦说乎？有朋自远方来，不亦乐乎？”",
+            "人之初，性本善。",
+            "性相近，习相远；",
+            "苟不教，性乃迁！？",
+            "教之道",
+        ]
+    );
+    assert_eq!(data["sentences"][4]["number"], 5);
+    assert_eq!(data["removed"], 0);
+    // Previewing changes nothing
+    assert!(app.state.repos.sentences.find_by_chapter_id(chapter.id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn preview_of_an_unpublished_chapter_is_hidden_from_the_public() {
+    let app = TestApp::new();
+    let classic = app.seed_draft_classic("lunyu", "论语").await;
+    let chapter = app.seed_chapter(&classic, 1, "学而").await;
+    let path = format!("/api/chapters/{}/segmentation", chapter.id);
+
+    app.get(&path).await.assert_error(StatusCode::NOT_FOUND);
+    let admin = app.admin_token().await;
+    app.get_as(&admin, &path).await.assert_success(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn confirm_creates_numbered_sentences_once() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let chapter = seed_chapter_with_content(&app, "人之初，性本善。性相近，习相远。").await;
+    let path = format!("/api/chapters/{}/segmentation", chapter.id);
+
+    let response = app.post_as(&admin, &path, json!({})).await;
+
+    let data = response.assert_success(StatusCode::CREATED);
+    assert_eq!(texts(data), ["人之初，性本善。", "性相近，习相远。"]);
+    assert_eq!(data[1]["number"], 2);
+
+    let response = app.post_as(&admin, &path, json!({})).await;
+    response.assert_error(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn resegmenting_keeps_unchanged_sentences() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let chapter = seed_chapter_with_content(&app, "人之初，性本善。性相近，习相远。").await;
+    let created = app
+        .post_as(&admin, &format!("/api/chapters/{}/segmentation", chapter.id), json!({}))
+        .await
+        .assert_success(StatusCode::CREATED)
+        .clone();
+    let kept_id = created[1]["id"].as_str().unwrap().parse().unwrap();
+    app.state
+        .repos
+        .sentences
+        .update(
+            kept_id,
+            UpdateSentenceRequest {
+                number: None,
+                text: None,
+                pinyin: Some(Some("xìng xiāng jìn, xí xiāng yuǎn".to_string())),
+                translation: None,
+                comment: None,
+            },
+            &IfMatch::default(),
+            &Edit::default(),
+        )
+        .await
+        .unwrap();
+
+    let response = app
+        .put_as(
+            &admin,
+            &format!("/api/chapters/{}", chapter.id),
+            json!({ "content": "性相近，习相远。苟不教，性乃迁。", "resegment": true }),
+        )
+        .await;
+    response.assert_success(StatusCode::OK);
+
+    let sentences = app.state.repos.sentences.find_by_chapter_id(chapter.id).await.unwrap();
+    let summary: Vec<(i32, &str)> = sentences.iter().map(|s| (s.number, s.text.as_str())).collect();
+    assert_eq!(summary, [(1, "性相近，习相远。"), (2, "苟不教，性乃迁。")]);
+    assert_eq!(sentences[0].id, kept_id);
+    assert!(sentences[0].pinyin.is_some());
+
+    let trashed = app.state.repos.trash.list(Some(EntityType::Sentence)).await.unwrap();
+    let labels: Vec<&str> = trashed.iter().map(|item| item.label.as_str()).collect();
+    assert_eq!(labels, ["人之初，性本善。"]);
+
+    let revisions = &app.state.repos.revisions;
+    let removed = revisions.list(EntityType::Sentence, trashed[0].id).await.unwrap();
+    assert_eq!(removed.last().unwrap().action, RevisionAction::Delete);
+    let created = revisions.list(EntityType::Sentence, sentences[1].id).await.unwrap();
+    assert_eq!(created.iter().map(|r| r.action).collect::<Vec<_>>(), [RevisionAction::Create]);
+}
+
+#[tokio::test]
+async fn an_update_that_cannot_be_resegmented_is_not_applied() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let chapter = seed_chapter_with_content(&app, "人之初，性本善。").await;
+    let path = format!("/api/chapters/{}", chapter.id);
+
+    let response = app.put_as(&admin, &path, json!({ "title": "首章", "content": null, "resegment": true })).await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "Chapter has no content to segment");
+    let unchanged = app.state.repos.chapters.find_by_id(chapter.id).await.unwrap().unwrap();
+    assert_eq!(unchanged.title, "学而");
+    assert_eq!(unchanged.content.as_deref(), Some("人之初，性本善。"));
+    let revisions = app.state.repos.revisions.list(EntityType::Chapter, chapter.id).await.unwrap();
+    assert_eq!(revisions.len(), 1, "only the chapter's creation is recorded");
+}
+
+#[tokio::test]
+async fn segmenting_needs_content() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let chapter = seed_chapter_with_content(&app, " \n ").await;
+
+    let response = app.post_as(&admin, &format!("/api/chapters/{}/segmentation", chapter.id), json!({})).await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "Chapter has no content to segment");
+}
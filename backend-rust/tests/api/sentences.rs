@@ -0,0 +1,337 @@
+use serde_json::json;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+use crate::support::TestApp;
+
+#[tokio::test]
+async fn list_returns_every_sentence() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    app.seed_sentence(&chapter, 1, "人之初").await;
+    app.seed_sentence(&chapter, 2, "性本善").await;
+
+    let response = app.get("/api/sentences").await;
+
+    response.assert_success(StatusCode::OK);
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn list_by_chapter_is_ordered_by_number() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let other = app.seed_chapter(&classic, 2, "第二章").await;
+    app.seed_sentence(&chapter, 2, "性本善").await;
+    app.seed_sentence(&chapter, 1, "人之初").await;
+    app.seed_sentence(&other, 1, "苟不教").await;
+
+    let response = app.get(&format!("/api/chapters/{}/sentences", chapter.id)).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    let texts: Vec<&str> = data.as_array().unwrap().iter().map(|s| s["text"].as_str().unwrap()).collect();
+    assert_eq!(texts, ["人之初", "性本善"]);
+}
+
+#[tokio::test]
+async fn get_by_id() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+
+    let response = app.get(&format!("/api/sentences/{}", sentence.id)).await;
+
+    response.assert_success(StatusCode::OK);
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn get_unknown_sentence_is_not_found() {
+    let app = TestApp::new();
+
+    let response = app.get(&format!("/api/sentences/{}", Uuid::new_v4())).await;
+
+    assert_eq!(response.assert_error(StatusCode::NOT_FOUND), "资源未找到");
+}
+
+#[tokio::test]
+async fn create_returns_the_sentence() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+
+    let response = app
+        .post_as(
+            &admin,
+            "/api/sentences",
+            json!({
+                "chapter_id": chapter.id,
+                "number": 1,
+                "text": "人之初",
+                "pinyin": "rén zhī chū",
+                "translation": "人刚出生的时候"
+            }),
+        )
+        .await;
+
+    response.assert_success(StatusCode::CREATED);
+    assert_body_snapshot!(response);
+}
+
+#[tokio::test]
+async fn create_rejects_a_duplicate_number() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    app.seed_sentence(&chapter, 1, "人之初").await;
+
+    let response = app
+        .post_as(&admin, "/api/sentences", json!({"chapter_id": chapter.id, "number": 1, "text": "重复"}))
+        .await;
+
+    assert_eq!(
+        response.assert_error(StatusCode::BAD_REQUEST),
+        "Sentence number already exists for this chapter"
+    );
+}
+
+#[tokio::test]
+async fn update_changes_only_given_fields() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+
+    let response = app
+        .put_as(&admin, &format!("/api/sentences/{}", sentence.id), json!({"pinyin": "rén zhī chū"}))
+        .await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data["text"], "人之初");
+    assert_eq!(data["pinyin"], "rén zhī chū");
+}
+
+#[tokio::test]
+async fn patch_distinguishes_null_from_absent() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+    let path = format!("/api/sentences/{}", sentence.id);
+    app.put_as(&admin, &path, json!({"pinyin": "rén zhī chū", "translation": "人刚出生的时候"}))
+        .await
+        .assert_success(StatusCode::OK);
+
+    let response = app.patch_as(&admin, &path, json!({"pinyin": null})).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data["pinyin"], json!(null));
+    assert_eq!(data["translation"], "人刚出生的时候");
+}
+
+#[tokio::test]
+async fn patch_rejects_null_for_required_fields() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+    let path = format!("/api/sentences/{}", sentence.id);
+
+    for body in [json!({"text": null}), json!({"number": null})] {
+        app.patch_as(&admin, &path, body).await.assert_error(StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn a_stale_if_match_does_not_overwrite_the_other_editor() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+    let path = format!("/api/sentences/{}", sentence.id);
+    let read = app.get(&path).await.header("etag").expect("etag").to_string();
+    let update = |body: serde_json::Value, etag: &str| {
+        warp::test::request()
+            .method("PATCH")
+            .path(&path)
+            .header("authorization", format!("Bearer {}", admin))
+            .header("if-match", etag)
+            .json(&body)
+    };
+
+    let first = app.send(update(json!({"text": "人之初，性本善"}), &read)).await;
+    first.assert_success(StatusCode::OK);
+    let current = first.header("etag").expect("etag").to_string();
+    assert_ne!(current, read);
+
+    let second = app.send(update(json!({"text": "人之初"}), &read)).await;
+    assert_eq!(second.status, StatusCode::PRECONDITION_FAILED, "body: {}", second.text);
+    assert_eq!(second.body["success"], false);
+    assert_eq!(second.body["error"], "Sentence was changed since it was read");
+    assert_eq!(second.body["data"]["text"], "人之初，性本善");
+    assert_eq!(second.header("etag"), Some(current.as_str()));
+
+    let retried = app.send(update(json!({"pinyin": "rén zhī chū"}), &current)).await;
+    let data = retried.assert_success(StatusCode::OK);
+    assert_eq!(data["text"], "人之初，性本善");
+    assert_eq!(data["pinyin"], "rén zhī chū");
+}
+
+#[tokio::test]
+async fn a_stale_if_match_answers_with_the_row_the_update_locked() {
+    // Read replicas may lag; the 412 must not come from one
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let admin = app.admin_token().await;
+    let slug = format!("stale-{}", Uuid::new_v4().simple());
+    let classic = app.seed_classic(&slug, "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+    let path = format!("/api/sentences/{}", sentence.id);
+    let read = app.get(&path).await.header("etag").expect("etag").to_string();
+    let current = app.put_as(&admin, &path, json!({"text": "人之初，性本善"})).await;
+    let current = current.header("etag").expect("etag").to_string();
+
+    let response = app
+        .send(
+            warp::test::request()
+                .method("PUT")
+                .path(&path)
+                .header("authorization", format!("Bearer {}", admin))
+                .header("if-match", &read)
+                .json(&json!({"text": "人之初"})),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::PRECONDITION_FAILED, "body: {}", response.text);
+    assert_eq!(response.body["data"]["text"], "人之初，性本善");
+    assert_eq!(response.header("etag"), Some(current.as_str()));
+}
+
+#[tokio::test]
+async fn if_match_uses_strong_comparison() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+    let path = format!("/api/sentences/{}", sentence.id);
+    let etag = app.get(&path).await.header("etag").expect("etag").to_string();
+    let put = |if_match: String| {
+        warp::test::request()
+            .method("PUT")
+            .path(&path)
+            .header("authorization", format!("Bearer {}", admin))
+            .header("if-match", if_match)
+            .json(&json!({"translation": "人刚出生的时候"}))
+    };
+
+    let weak = app.send(put(format!("W/{}", etag))).await;
+    assert_eq!(weak.status, StatusCode::PRECONDITION_FAILED);
+
+    app.send(put(format!("\"other\", {}", etag))).await.assert_success(StatusCode::OK);
+    app.send(put("*".to_string())).await.assert_success(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn update_to_a_taken_number_fails() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    app.seed_sentence(&chapter, 1, "人之初").await;
+    let second = app.seed_sentence(&chapter, 2, "性本善").await;
+
+    let response = app.put_as(&admin, &format!("/api/sentences/{}", second.id), json!({"number": 1})).await;
+
+    response.assert_error(StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn update_unknown_sentence_is_not_found() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+
+    let response = app.put_as(&admin, &format!("/api/sentences/{}", Uuid::new_v4()), json!({"text": "新"})).await;
+
+    response.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_removes_the_sentence() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+
+    let response = app.delete_as(&admin, &format!("/api/sentences/{}", sentence.id)).await;
+
+    response.assert_success(StatusCode::OK);
+    assert_body_snapshot!(response);
+    app.get(&format!("/api/sentences/{}", sentence.id)).await.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_unknown_sentence_is_not_found() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+
+    let response = app.delete_as(&admin, &format!("/api/sentences/{}", Uuid::new_v4())).await;
+
+    response.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn context_crosses_chapter_boundaries() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let second = app.seed_chapter(&classic, 2, "第二章").await;
+    let first = app.seed_chapter(&classic, 1, "第一章").await;
+    let opening = app.seed_sentence(&first, 1, "人之初").await;
+    let last_of_first = app.seed_sentence(&first, 2, "性本善").await;
+    let first_of_second = app.seed_sentence(&second, 1, "苟不教").await;
+    // Another classic's sentences stay out of the order
+    let other = app.seed_classic("qianziwen", "千字文").await;
+    let other_chapter = app.seed_chapter(&other, 1, "第一章").await;
+    app.seed_sentence(&other_chapter, 1, "天地玄黄").await;
+
+    let response = app.get(&format!("/api/sentences/{}/context", last_of_first.id)).await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data["sentence"]["text"], "性本善");
+    assert_eq!(data["chapter"]["title"], "第一章");
+    assert_eq!(data["classic"]["slug"], "sanzijing");
+    assert_eq!(data["previous_id"], opening.id.to_string());
+    assert_eq!(data["next_id"], first_of_second.id.to_string());
+    assert_eq!(data["position"], 2);
+    assert_eq!(data["total"], 3);
+
+    let response = app.get(&format!("/api/sentences/{}/context", first_of_second.id)).await;
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data["previous_id"], last_of_first.id.to_string());
+    assert!(data["next_id"].is_null());
+    assert_eq!(data["position"], 3);
+
+    let response = app.get(&format!("/api/sentences/{}/context", opening.id)).await;
+    assert!(response.assert_success(StatusCode::OK)["previous_id"].is_null());
+}
+
+#[tokio::test]
+async fn context_of_missing_sentence_is_not_found() {
+    let app = TestApp::new();
+
+    let response = app.get(&format!("/api/sentences/{}/context", Uuid::new_v4())).await;
+
+    response.assert_error(StatusCode::NOT_FOUND);
+}
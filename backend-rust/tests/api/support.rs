@@ -0,0 +1,336 @@
+use serde_json::Value;
+use warp::{
+    filters::BoxedFilter,
+    http::{HeaderMap, StatusCode},
+    test::RequestBuilder,
+    Filter, Reply,
+};
+use xiaoxiao_dushulang_backend::{
+    config::{Config, Environment},
+    database::Database,
+    handlers::AppState,
+    models::{
+        Audience, ChangeStatusRequest, Chapter, Classic, CreateChapterRequest,
+        CreateClassicRequest, CreateSentenceRequest, CreateUserRequest, Edit, PublicationStatus,
+        Sentence, User, UserRole,
+    },
+    repositories::Repositories,
+    routes,
+    utils::jwt::create_jwt_token,
+};
+
+/// Snapshot a response body with the values that change between runs redacted.
+macro_rules! assert_body_snapshot {
+    ($response:expr) => {
+        insta::assert_json_snapshot!($response.body, {
+            ".timestamp" => "[timestamp]",
+            ".**.id" => "[id]",
+            ".**.classic_id" => "[id]",
+            ".**.chapter_id" => "[id]",
+            ".**.created_at" => "[datetime]",
+            ".**.updated_at" => "[datetime]",
+            ".**.last_login" => "[datetime]",
+            ".**.token" => "[token]",
+        })
+    };
+}
+
+pub const PASSWORD: &str = "correct-horse-battery";
+
+/// Test configuration with rate limiting off, so tests can make any number of requests.
+pub fn config() -> Config {
+    let mut config = Config::defaults(Environment::Test);
+    config.rate_limit.enabled = false;
+    config
+}
+
+pub struct TestApp {
+    pub state: AppState,
+    routes: BoxedFilter<(warp::reply::Response,)>,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// Parsed JSON body, or `Null` when the body is empty or not JSON
+    pub body: Value,
+    pub text: String,
+    /// The body as sent, before any decoding
+    pub bytes: Vec<u8>,
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        Self::with_config(config())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        Self::with_state(AppState::in_memory(config))
+    }
+
+    /// The app over the migrated database in `TEST_DATABASE_URL`, for behaviour
+    /// the in-memory store does not model; `None` when the variable is unset.
+    /// The database is shared and not cleaned up, so seed unique slugs and names.
+    pub async fn postgres() -> Option<Self> {
+        let url = std::env::var("TEST_DATABASE_URL").ok()?;
+        let mut config = config();
+        config.database.url = url;
+        let db = Database::connect(&config.database).await.expect("connect to TEST_DATABASE_URL");
+        db.migrate().await.expect("migrate test database");
+        Some(Self::with_state(AppState {
+            repos: Repositories::postgres(db.clone()),
+            db: Some(db),
+            redis: None,
+            config,
+        }))
+    }
+
+    fn with_state(state: AppState) -> Self {
+        let routes = routes::build(state.clone())
+            .map(Reply::into_response)
+            .boxed();
+        Self { state, routes }
+    }
+
+    pub async fn send(&self, request: RequestBuilder) -> TestResponse {
+        let response = request.reply(&self.routes).await;
+        let text = String::from_utf8_lossy(response.body()).into_owned();
+        TestResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: serde_json::from_str(&text).unwrap_or(Value::Null),
+            text,
+            bytes: response.body().to_vec(),
+        }
+    }
+
+    pub async fn get(&self, path: &str) -> TestResponse {
+        self.send(warp::test::request().method("GET").path(path)).await
+    }
+
+    /// A GET with a bearer token.
+    pub async fn get_as(&self, token: &str, path: &str) -> TestResponse {
+        self.send(
+            warp::test::request()
+                .path(path)
+                .header("authorization", format!("Bearer {}", token)),
+        )
+        .await
+    }
+
+    /// A POST with a bearer token.
+    pub async fn post_as(&self, token: &str, path: &str, body: Value) -> TestResponse {
+        self.send(
+            warp::test::request()
+                .method("POST")
+                .path(path)
+                .header("authorization", format!("Bearer {}", token))
+                .json(&body),
+        )
+        .await
+    }
+
+    pub async fn post(&self, path: &str, body: Value) -> TestResponse {
+        self.send(warp::test::request().method("POST").path(path).json(&body)).await
+    }
+
+    /// A PUT with a bearer token.
+    pub async fn put_as(&self, token: &str, path: &str, body: Value) -> TestResponse {
+        self.send(
+            warp::test::request()
+                .method("PUT")
+                .path(path)
+                .header("authorization", format!("Bearer {}", token))
+                .json(&body),
+        )
+        .await
+    }
+
+    pub async fn put(&self, path: &str, body: Value) -> TestResponse {
+        self.send(warp::test::request().method("PUT").path(path).json(&body)).await
+    }
+
+    /// A JSON merge patch with a bearer token.
+    pub async fn patch_as(&self, token: &str, path: &str, body: Value) -> TestResponse {
+        let request = warp::test::request()
+            .method("PATCH")
+            .path(path)
+            .header("authorization", format!("Bearer {}", token))
+            .header("content-type", "application/merge-patch+json")
+            .body(body.to_string());
+        self.send(request).await
+    }
+
+    /// A JSON merge patch, sent as `application/merge-patch+json`.
+    pub async fn patch(&self, path: &str, body: Value) -> TestResponse {
+        let request = warp::test::request()
+            .method("PATCH")
+            .path(path)
+            .header("content-type", "application/merge-patch+json")
+            .body(body.to_string());
+        self.send(request).await
+    }
+
+    /// A DELETE with a bearer token.
+    pub async fn delete_as(&self, token: &str, path: &str) -> TestResponse {
+        self.send(
+            warp::test::request()
+                .method("DELETE")
+                .path(path)
+                .header("authorization", format!("Bearer {}", token)),
+        )
+        .await
+    }
+
+    pub async fn delete(&self, path: &str) -> TestResponse {
+        self.send(warp::test::request().method("DELETE").path(path)).await
+    }
+
+    /// A published classic, submitted and approved the way an editor and an admin would.
+    pub async fn seed_classic(&self, slug: &str, title: &str) -> Classic {
+        let classic = self.seed_draft_classic(slug, title).await;
+        self.publish(classic).await
+    }
+
+    /// Take a draft through review to published.
+    pub async fn publish(&self, mut classic: Classic) -> Classic {
+        for status in [PublicationStatus::InReview, PublicationStatus::Published] {
+            let req = ChangeStatusRequest { status, publish_at: None, unpublish_at: None, comment: None };
+            classic = self
+                .state
+                .repos
+                .classics
+                .change_status(classic.id, &req, Audience::Admin, &Edit::default())
+                .await
+                .expect("publish seed classic")
+                .expect("seed classic exists");
+        }
+        classic
+    }
+
+    /// A classic as created, visible to admins only.
+    pub async fn seed_draft_classic(&self, slug: &str, title: &str) -> Classic {
+        self.state
+            .repos
+            .classics
+            .create(
+                CreateClassicRequest {
+                    slug: slug.to_string(),
+                    title: title.to_string(),
+                    author: Some("王应麟".to_string()),
+                    dynasty: Some("宋".to_string()),
+                    description: None,
+                },
+                &Edit::default(),
+            )
+            .await
+            .expect("seed classic")
+    }
+
+    pub async fn seed_chapter(&self, classic: &Classic, number: i32, title: &str) -> Chapter {
+        self.state
+            .repos
+            .chapters
+            .create(
+                CreateChapterRequest {
+                    classic_id: classic.id,
+                    number,
+                    title: title.to_string(),
+                    content: None,
+                },
+                &Edit::default(),
+            )
+            .await
+            .expect("seed chapter")
+    }
+
+    pub async fn seed_sentence(&self, chapter: &Chapter, number: i32, text: &str) -> Sentence {
+        self.state
+            .repos
+            .sentences
+            .create(
+                CreateSentenceRequest {
+                    chapter_id: chapter.id,
+                    number,
+                    text: text.to_string(),
+                    pinyin: None,
+                    translation: None,
+                },
+                &Edit::default(),
+            )
+            .await
+            .expect("seed sentence")
+    }
+
+    pub async fn seed_user(&self, username: &str, email: &str) -> User {
+        self.state
+            .repos
+            .users
+            .create(CreateUserRequest {
+                username: username.to_string(),
+                email: email.to_string(),
+                password: PASSWORD.to_string(),
+                phone: None,
+                role: None,
+            })
+            .await
+            .expect("seed user")
+    }
+
+    pub async fn seed_admin(&self) -> User {
+        self.state
+            .repos
+            .users
+            .create(CreateUserRequest {
+                username: "admin".to_string(),
+                email: "admin@example.com".to_string(),
+                password: PASSWORD.to_string(),
+                phone: None,
+                role: Some(UserRole::Admin),
+            })
+            .await
+            .expect("seed admin")
+    }
+
+    /// A token for the admin, seeded on first use.
+    pub async fn admin_token(&self) -> String {
+        let admin = match self.state.repos.users.find_by_username("admin").await.expect("find admin") {
+            Some(admin) => admin,
+            None => self.seed_admin().await,
+        };
+        create_jwt_token(admin.id, &self.state.config.auth.jwt_secret).unwrap()
+    }
+
+    /// A token for a signed-in user who is not an admin, seeded here, so call
+    /// it once per app.
+    pub async fn editor_token(&self) -> String {
+        let editor = self.seed_user("editor", "editor@example.com").await;
+        create_jwt_token(editor.id, &self.state.config.auth.jwt_secret).unwrap()
+    }
+}
+
+impl TestResponse {
+    /// A 2xx `ApiResponse` envelope: `success: true`, a timestamp and `data`.
+    #[track_caller]
+    pub fn assert_success(&self, status: StatusCode) -> &Value {
+        assert_eq!(self.status, status, "body: {}", self.text);
+        assert_eq!(self.body["success"], true, "body: {}", self.text);
+        assert!(self.body["timestamp"].is_string(), "body: {}", self.text);
+        assert!(self.body.get("error").is_none(), "body: {}", self.text);
+        &self.body["data"]
+    }
+
+    /// An error envelope: `success: false` with a message and no `data`.
+    #[track_caller]
+    pub fn assert_error(&self, status: StatusCode) -> &str {
+        assert_eq!(self.status, status, "body: {}", self.text);
+        assert_eq!(self.body["success"], false, "body: {}", self.text);
+        assert!(self.body["timestamp"].is_string(), "body: {}", self.text);
+        assert!(self.body.get("data").is_none(), "body: {}", self.text);
+        self.body["error"].as_str().expect("error message")
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+}
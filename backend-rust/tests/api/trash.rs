@@ -0,0 +1,201 @@
+use serde_json::json;
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+use xiaoxiao_dushulang_backend::utils::jwt::create_jwt_token;
+
+use crate::support::TestApp;
+
+#[tokio::test]
+async fn deleting_a_classic_trashes_its_chapters_and_restore_brings_them_back() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+
+    app.delete_as(&admin, &format!("/api/classics/{}", classic.id)).await.assert_success(StatusCode::OK);
+    app.get(&format!("/api/chapters/{}", chapter.id)).await.assert_error(StatusCode::NOT_FOUND);
+    app.get(&format!("/api/sentences/{}", sentence.id)).await.assert_error(StatusCode::NOT_FOUND);
+
+    let response = app.get_as(&admin, "/api/trash").await;
+    let items = response.assert_success(StatusCode::OK).as_array().unwrap();
+    assert_eq!(items.len(), 1, "children deleted with the classic are not listed: {:?}", items);
+    assert_eq!(items[0]["entity_type"], "classic");
+    assert_eq!(items[0]["label"], "三字经");
+
+    let response = app.post_as(&admin, &format!("/api/trash/classics/{}/restore", classic.id), json!({})).await;
+    assert_eq!(response.assert_success(StatusCode::OK)["slug"], "sanzijing");
+    app.get(&format!("/api/chapters/{}", chapter.id)).await.assert_success(StatusCode::OK);
+    app.get(&format!("/api/sentences/{}", sentence.id)).await.assert_success(StatusCode::OK);
+    let response = app.get_as(&admin, "/api/trash").await;
+    assert_eq!(response.assert_success(StatusCode::OK), &json!([]));
+
+    let response = app.get_as(&admin, &format!("/api/classics/{}/revisions", classic.id)).await;
+    let revisions = response.assert_success(StatusCode::OK).as_array().unwrap();
+    assert_eq!(revisions.last().unwrap()["action"], "restore");
+}
+
+#[tokio::test]
+async fn restoring_a_classic_leaves_chapters_deleted_before_it_in_the_trash() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let kept = app.seed_chapter(&classic, 1, "第一章").await;
+    let dropped = app.seed_chapter(&classic, 2, "第二章").await;
+
+    app.delete_as(&admin, &format!("/api/chapters/{}", dropped.id)).await.assert_success(StatusCode::OK);
+    app.delete_as(&admin, &format!("/api/classics/{}", classic.id)).await.assert_success(StatusCode::OK);
+    app.post_as(&admin, &format!("/api/trash/classics/{}/restore", classic.id), json!({}))
+        .await
+        .assert_success(StatusCode::OK);
+
+    app.get(&format!("/api/chapters/{}", kept.id)).await.assert_success(StatusCode::OK);
+    app.get(&format!("/api/chapters/{}", dropped.id)).await.assert_error(StatusCode::NOT_FOUND);
+    let response = app.get_as(&admin, "/api/trash?entity_type=chapter").await;
+    let items = response.assert_success(StatusCode::OK).as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["id"], dropped.id.to_string());
+    assert_eq!(items[0]["parent_id"], classic.id.to_string());
+}
+
+#[tokio::test]
+async fn listing_the_trash_is_for_admins_only() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_draft_classic("sanzijing", "三字经").await;
+    app.delete_as(&admin, &format!("/api/classics/{}", classic.id)).await.assert_success(StatusCode::OK);
+
+    app.get("/api/trash").await.assert_error(StatusCode::UNAUTHORIZED);
+    let editor = app.seed_user("editor", "editor@example.com").await;
+    let token = create_jwt_token(editor.id, &app.state.config.auth.jwt_secret).unwrap();
+    app.get_as(&token, "/api/trash").await.assert_error(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn restore_is_for_admins_only() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    app.delete_as(&admin, &format!("/api/classics/{}", classic.id)).await.assert_success(StatusCode::OK);
+    let path = format!("/api/trash/classics/{}/restore", classic.id);
+
+    app.post(&path, json!({})).await.assert_error(StatusCode::UNAUTHORIZED);
+    let editor = app.seed_user("editor", "editor@example.com").await;
+    let token = create_jwt_token(editor.id, &app.state.config.auth.jwt_secret).unwrap();
+    app.post_as(&token, &path, json!({})).await.assert_error(StatusCode::FORBIDDEN);
+    app.get(&format!("/api/classics/{}", classic.id)).await.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn restoring_a_chapter_requires_its_classic_to_be_restored_first() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    app.delete_as(&admin, &format!("/api/classics/{}", classic.id)).await.assert_success(StatusCode::OK);
+
+    let response = app.post_as(&admin, &format!("/api/trash/chapters/{}/restore", chapter.id), json!({})).await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "所属经典已删除，请先恢复经典");
+}
+
+#[tokio::test]
+async fn restoring_into_a_number_taken_meanwhile_is_rejected() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let sentence = app.seed_sentence(&chapter, 1, "人之初").await;
+    app.delete_as(&admin, &format!("/api/sentences/{}", sentence.id)).await.assert_success(StatusCode::OK);
+    app.seed_sentence(&chapter, 1, "性本善").await;
+
+    let response = app.post_as(&admin, &format!("/api/trash/sentences/{}/restore", sentence.id), json!({})).await;
+
+    assert_eq!(response.assert_error(StatusCode::BAD_REQUEST), "句子编号 1 已被占用，请先调整编号");
+}
+
+#[tokio::test]
+async fn restore_of_something_not_in_the_trash_is_not_found() {
+    let app = TestApp::new();
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+
+    let admin = app.admin_token().await;
+    for id in [classic.id, Uuid::new_v4()] {
+        let response = app.post_as(&admin, &format!("/api/trash/classics/{}/restore", id), json!({})).await;
+        response.assert_error(StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn a_trashed_classic_keeps_its_slug_until_purged() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    app.delete_as(&admin, &format!("/api/classics/{}", classic.id)).await.assert_success(StatusCode::OK);
+
+    let body = json!({"slug": "sanzijing", "title": "三字经"});
+    app.post_as(&admin, "/api/classics", body.clone()).await.assert_error(StatusCode::CONFLICT);
+
+    app.delete_as(&admin, &format!("/api/trash/classics/{}", classic.id))
+        .await
+        .assert_success(StatusCode::OK);
+    app.post_as(&admin, "/api/classics", body).await.assert_success(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn purge_is_for_admins_only() {
+    let app = TestApp::new();
+    let admin = app.admin_token().await;
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    app.delete_as(&admin, &format!("/api/chapters/{}", chapter.id)).await.assert_success(StatusCode::OK);
+    let path = format!("/api/trash/chapters/{}", chapter.id);
+
+    app.delete(&path).await.assert_error(StatusCode::UNAUTHORIZED);
+    let editor = app.seed_user("editor", "editor@example.com").await;
+    let token = create_jwt_token(editor.id, &app.state.config.auth.jwt_secret).unwrap();
+    app.delete_as(&token, &path).await.assert_error(StatusCode::FORBIDDEN);
+
+    app.delete_as(&admin, &path).await.assert_success(StatusCode::OK);
+    app.delete_as(&admin, &path).await.assert_error(StatusCode::NOT_FOUND);
+    let response = app.post_as(&admin, &format!("/api/trash/chapters/{}/restore", chapter.id), json!({})).await;
+    response.assert_error(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn purging_a_classic_keeps_practice_sessions_without_the_reference() {
+    // Practice sessions exist only in the schema
+    let Some(app) = TestApp::postgres().await else {
+        return;
+    };
+    let admin = app.admin_token().await;
+    let name = format!("purge-{}", Uuid::new_v4().simple());
+    let classic = app.seed_classic(&name, "三字经").await;
+    let chapter = app.seed_chapter(&classic, 1, "第一章").await;
+    let user = app.seed_user(&name, &format!("{}@example.com", name)).await;
+    let pool = app.state.db.as_ref().unwrap().writer();
+    let session = sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO practice_sessions (user_id, session_type, classic_id, chapter_id)
+         VALUES ($1, 'reading', $2, $3)
+         RETURNING id"
+    )
+    .bind(user.id)
+    .bind(classic.id)
+    .bind(chapter.id)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+
+    app.delete_as(&admin, &format!("/api/classics/{}", classic.id)).await.assert_success(StatusCode::OK);
+    assert!(app.state.repos.classics.purge(classic.id).await.unwrap());
+
+    let references = sqlx::query_as::<_, (Option<Uuid>, Option<Uuid>)>(
+        "SELECT classic_id, chapter_id FROM practice_sessions WHERE id = $1"
+    )
+    .bind(session)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert_eq!(references, (None, None));
+}
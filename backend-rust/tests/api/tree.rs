@@ -0,0 +1,117 @@
+use serde_json::Value;
+use warp::http::StatusCode;
+
+use xiaoxiao_dushulang_backend::models::Chapter;
+
+use crate::support::TestApp;
+
+/// Seeds 三字经 with three chapters and returns the one without sentences.
+async fn seed_tree(app: &TestApp) -> Chapter {
+    let classic = app.seed_classic("sanzijing", "三字经").await;
+    // Seeded out of order; the tree follows chapter numbers
+    let second = app.seed_chapter(&classic, 2, "第二章").await;
+    let first = app.seed_chapter(&classic, 1, "第一章").await;
+    let third = app.seed_chapter(&classic, 3, "第三章").await;
+    app.seed_sentence(&first, 2, "性相近").await;
+    app.seed_sentence(&first, 1, "人之初").await;
+    app.seed_sentence(&second, 1, "苟不教").await;
+    third
+}
+
+fn chapter_field<'a>(data: &'a Value, name: &str) -> Vec<&'a Value> {
+    data["chapters"].as_array().unwrap().iter().map(|chapter| &chapter[name]).collect()
+}
+
+#[tokio::test]
+async fn tree_lists_chapters_in_order_with_content() {
+    let app = TestApp::new();
+    seed_tree(&app).await;
+
+    let response = app.get("/api/classics/sanzijing/tree").await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(data["classic"]["slug"], "sanzijing");
+    assert_eq!(chapter_field(data, "number"), [1, 2, 3]);
+    assert_eq!(chapter_field(data, "sentence_count"), [2, 1, 0]);
+    let first = &data["chapters"][0];
+    assert!(first.get("content").is_some());
+    assert!(first.get("sentences").is_none());
+}
+
+#[tokio::test]
+async fn tree_includes_sentences_on_request() {
+    let app = TestApp::new();
+    seed_tree(&app).await;
+
+    let response = app.get("/api/classics/sanzijing/tree?include=sentences").await;
+
+    let data = response.assert_success(StatusCode::OK);
+    let texts: Vec<Vec<&Value>> = data["chapters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|chapter| chapter["sentences"].as_array().unwrap().iter().map(|s| &s["text"]).collect())
+        .collect();
+    assert_eq!(texts, vec![vec!["人之初", "性相近"], vec!["苟不教"], vec![]]);
+    assert!(data["chapters"][0].get("content").is_none());
+}
+
+#[tokio::test]
+async fn toc_mode_keeps_titles_and_counts_only() {
+    let app = TestApp::new();
+    seed_tree(&app).await;
+
+    let response = app.get("/api/classics/sanzijing/tree?mode=toc").await;
+
+    let data = response.assert_success(StatusCode::OK);
+    assert_eq!(chapter_field(data, "title"), ["第一章", "第二章", "第三章"]);
+    let first = &data["chapters"][0];
+    assert!(first.get("content").is_none());
+    assert!(first.get("sentences").is_none());
+    assert_eq!(first["sentence_count"], 2);
+}
+
+#[tokio::test]
+async fn tree_rejects_unknown_includes() {
+    let app = TestApp::new();
+    seed_tree(&app).await;
+
+    let response = app.get("/api/classics/sanzijing/tree?include=words").await;
+    let message = response.assert_error(StatusCode::BAD_REQUEST);
+    assert!(message.contains("content, sentences"), "{}", message);
+
+    let response = app.get("/api/classics/sanzijing/tree?mode=toc&include=content").await;
+    response.assert_error(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn tree_of_missing_classic_is_not_found() {
+    let app = TestApp::new();
+
+    let response = app.get("/api/classics/missing/tree").await;
+
+    assert_eq!(response.assert_error(StatusCode::NOT_FOUND), "经典未找到");
+}
+
+#[tokio::test]
+async fn tree_revalidates_with_etag() {
+    let app = TestApp::new();
+    let empty = seed_tree(&app).await;
+    let path = "/api/classics/sanzijing/tree?mode=toc";
+
+    let first = app.get(path).await;
+    let etag = first.header("etag").expect("etag").to_string();
+    assert_ne!(app.get("/api/classics/sanzijing/tree").await.header("etag"), Some(etag.as_str()));
+
+    let revalidated = app
+        .send(warp::test::request().path(path).header("if-none-match", &etag))
+        .await;
+    assert_eq!(revalidated.status, StatusCode::NOT_MODIFIED);
+
+    // A new sentence only shows in the counts, which still change the validator
+    app.seed_sentence(&empty, 1, "性乃迁").await;
+    let changed = app
+        .send(warp::test::request().path(path).header("if-none-match", &etag))
+        .await;
+    assert_eq!(changed.status, StatusCode::OK);
+}
@@ -1,18 +1,53 @@
 use diesel::prelude::*;
-use diesel::r2d2::{self, ConnectionManager, Pool};
+use diesel::r2d2::{self, ConnectionManager, CustomizeConnection, Pool};
 use std::env;
+use std::time::Duration;
 
 pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Sets the Postgres `statement_timeout` on every new pooled connection.
+#[derive(Debug)]
+struct StatementTimeout(u64);
+
+impl CustomizeConnection<PgConnection, r2d2::Error> for StatementTimeout {
+    fn on_acquire(&self, conn: &mut PgConnection) -> Result<(), r2d2::Error> {
+        diesel::sql_query(format!("SET statement_timeout = {}", self.0))
+            .execute(conn)
+            .map(|_| ())
+            .map_err(r2d2::Error::QueryError)
+    }
+}
+
 pub async fn establish_connection() -> Result<DbPool, Box<dyn std::error::Error>> {
     let database_url = env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set");
 
+    let max_size: u32 = env_or("DB_POOL_MAX_CONNECTIONS", 10);
+    let min_idle: u32 = env_or("DB_POOL_MIN_CONNECTIONS", 0);
+    let acquire_timeout: u64 = env_or("DB_POOL_ACQUIRE_TIMEOUT", 5);
+    let idle_timeout: u64 = env_or("DB_POOL_IDLE_TIMEOUT", 600);
+    let statement_timeout_ms: u64 = env_or("DB_STATEMENT_TIMEOUT_MS", 30_000);
+
     let manager = ConnectionManager::<PgConnection>::new(database_url);
-    let pool = r2d2::Pool::builder()
-        .max_size(10)
-        .build(manager)?;
+    let mut builder = r2d2::Pool::builder()
+        .max_size(max_size)
+        .min_idle(Some(min_idle))
+        .connection_timeout(Duration::from_secs(acquire_timeout))
+        .idle_timeout((idle_timeout > 0).then(|| Duration::from_secs(idle_timeout)));
+
+    if statement_timeout_ms > 0 {
+        builder = builder.connection_customizer(Box::new(StatementTimeout(statement_timeout_ms)));
+    }
+
+    let pool = builder.build(manager)?;
 
-    println!("✅ Database connection pool established");
+    println!("✅ Database connection pool established (max {} connections)", max_size);
     Ok(pool)
 }